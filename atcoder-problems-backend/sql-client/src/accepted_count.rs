@@ -9,8 +9,14 @@ use std::collections::{BTreeMap, BTreeSet};
 #[async_trait]
 pub trait AcceptedCountClient {
     async fn load_accepted_count(&self) -> Result<Vec<UserProblemCount>>;
+    async fn load_accepted_count_in_range(
+        &self,
+        offset: i64,
+        limit: i64,
+    ) -> Result<Vec<UserProblemCount>>;
     async fn get_users_accepted_count(&self, user_id: &str) -> Option<i32>;
     async fn get_accepted_count_rank(&self, accepted_count: i32) -> Result<i64>;
+    async fn get_accepted_count_position(&self, user_id: &str, accepted_count: i32) -> Result<i64>;
     async fn update_accepted_count(&self, submissions: &[Submission]) -> Result<()>;
 }
 
@@ -37,6 +43,34 @@ impl AcceptedCountClient for PgPool {
         Ok(count)
     }
 
+    async fn load_accepted_count_in_range(
+        &self,
+        offset: i64,
+        limit: i64,
+    ) -> Result<Vec<UserProblemCount>> {
+        let count = sqlx::query(
+            r"
+            SELECT user_id, problem_count FROM accepted_count
+            ORDER BY problem_count DESC, user_id ASC
+            OFFSET $1 LIMIT $2
+            ",
+        )
+        .bind(offset)
+        .bind(limit)
+        .try_map(|row: PgRow| {
+            let user_id: String = row.try_get("user_id")?;
+            let problem_count: i32 = row.try_get("problem_count")?;
+            Ok(UserProblemCount {
+                user_id,
+                problem_count,
+            })
+        })
+        .fetch_all(self)
+        .await?;
+
+        Ok(count)
+    }
+
     async fn get_users_accepted_count(&self, user_id: &str) -> Option<i32> {
         let count = sqlx::query(
             r"
@@ -69,6 +103,24 @@ impl AcceptedCountClient for PgPool {
         Ok(rank)
     }
 
+    async fn get_accepted_count_position(&self, user_id: &str, accepted_count: i32) -> Result<i64> {
+        let position = sqlx::query(
+            r"
+            SELECT COUNT(*) AS position
+            FROM accepted_count
+            WHERE problem_count > $1
+            OR (problem_count = $1 AND user_id < $2)
+            ",
+        )
+        .bind(accepted_count)
+        .bind(user_id)
+        .try_map(|row: PgRow| row.try_get::<i64, _>("position"))
+        .fetch_one(self)
+        .await?;
+
+        Ok(position)
+    }
+
     async fn update_accepted_count(&self, submissions: &[Submission]) -> Result<()> {
         let accepted_count = submissions
             .iter()
@@ -2,3 +2,8 @@ pub mod problem_list_manager;
 pub mod progress_reset_manager;
 pub mod user_manager;
 pub mod virtual_contest_manager;
+pub mod virtual_contest_series_manager;
+
+/// Soft-deleted contests and lists can be restored within this period after the deletion, and
+/// are purged permanently after it.
+pub const DELETED_RETENTION_SECOND: i64 = 30 * 24 * 60 * 60;
@@ -1,19 +1,28 @@
+use crate::internal::DELETED_RETENTION_SECOND;
 use crate::PgPool;
 use anyhow::{bail, Context, Result};
 use async_trait::async_trait;
 use serde::Serialize;
 use sqlx::postgres::PgRow;
-use sqlx::Row;
-use std::collections::BTreeMap;
+use sqlx::{Postgres, Row, Transaction};
+use std::collections::{BTreeMap, BTreeSet};
+use std::result::Result as StdResult;
 
 const MAX_LIST_NUM: usize = 256;
 const MAX_ITEM_NUM: usize = 1024;
+const MAX_TAG_NUM: usize = 16;
+const MAX_TAG_LENGTH: usize = 255;
+const MAX_MEMO_LENGTH: usize = 255;
 
 #[derive(Serialize, Debug, PartialEq, Eq)]
 pub struct ProblemList {
     pub internal_list_id: String,
     pub internal_list_name: String,
     pub internal_user_id: String,
+    /// The id of the list which this list was forked from.
+    pub source_list_id: Option<String>,
+    /// The number of the lists forked from this list and not deleted.
+    pub fork_count: i64,
     pub items: Vec<ListItem>,
 }
 
@@ -21,132 +30,225 @@ pub struct ProblemList {
 pub struct ListItem {
     pub problem_id: String,
     pub memo: String,
+    pub tags: Vec<String>,
+}
+
+/// A user who can edit the items of a list of another user.
+#[derive(Serialize, Debug, PartialEq, Eq)]
+pub struct ListEditor {
+    pub internal_user_id: String,
+    pub atcoder_user_id: Option<String>,
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportRejection {
+    UnknownProblem,
+    /// The problem is already in the list, or appears twice in the imported items.
+    Duplicate,
+    TooLongMemo,
+    TooManyItems,
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq)]
+pub struct RejectedItem {
+    /// The index of the item in the imported items.
+    pub index: usize,
+    pub problem_id: String,
+    pub reason: ImportRejection,
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum ItemStatus {
+    Accepted,
+    Tried,
+    Untouched,
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq)]
+pub struct ItemProgress {
+    pub problem_id: String,
+    pub status: ItemStatus,
+    /// The time of the first accepted submission, which is `None` unless the item is accepted.
+    pub first_ac_epoch_second: Option<i64>,
 }
 
 #[async_trait]
 pub trait ProblemListManager {
     async fn get_list(&self, internal_user_id: &str) -> Result<Vec<ProblemList>>;
     async fn get_single_list(&self, internal_list_id: &str) -> Result<ProblemList>;
+    /// Returns the lists which `internal_user_id` can edit as an editor.
+    async fn get_shared_lists(&self, internal_user_id: &str) -> Result<Vec<ProblemList>>;
+    async fn get_list_editors(&self, internal_list_id: &str) -> Result<Vec<ListEditor>>;
+    async fn add_list_editor(&self, internal_list_id: &str, internal_user_id: &str) -> Result<()>;
+    async fn remove_list_editor(
+        &self,
+        internal_list_id: &str,
+        internal_user_id: &str,
+    ) -> Result<()>;
     async fn create_list(&self, internal_user_id: &str, name: &str) -> Result<String>;
     async fn update_list(&self, internal_list_id: &str, name: &str) -> Result<()>;
-    async fn delete_list(&self, internal_list_id: &str) -> Result<()>;
+    /// Marks the list owned by `internal_user_id` as deleted at `now`.
+    async fn delete_list(
+        &self,
+        internal_list_id: &str,
+        internal_user_id: &str,
+        now: i64,
+    ) -> Result<()>;
+    /// Restores the list owned by `internal_user_id` if it was deleted within
+    /// [DELETED_RETENTION_SECOND] before `now`.
+    async fn restore_list(
+        &self,
+        internal_list_id: &str,
+        internal_user_id: &str,
+        now: i64,
+    ) -> Result<()>;
+    /// Returns the lists owned by `internal_user_id` which are deleted but still restorable.
+    /// Their items are not loaded.
+    async fn get_deleted_lists(&self, internal_user_id: &str, now: i64)
+        -> Result<Vec<ProblemList>>;
+    /// Permanently deletes the lists whose retention period has passed, and returns the number
+    /// of them.
+    async fn purge_deleted_lists(&self, now: i64) -> Result<u64>;
     async fn add_item(&self, internal_list_id: &str, problem_id: &str) -> Result<()>;
     async fn update_item(&self, internal_list_id: &str, problem_id: &str, memo: &str)
         -> Result<()>;
     async fn delete_item(&self, internal_list_id: &str, problem_id: &str) -> Result<()>;
+    /// Sorts the items of the list in the order of `problem_ids`, which must consist of all the
+    /// problems in the list.
+    async fn reorder_items(&self, internal_list_id: &str, problem_ids: &[&str]) -> Result<()>;
+    /// Replaces the tags of the item with `tags`, which are trimmed and deduplicated. Fails if a
+    /// tag is empty or too long.
+    async fn update_item_tags(
+        &self,
+        internal_list_id: &str,
+        problem_id: &str,
+        tags: &[&str],
+    ) -> Result<()>;
+    /// Appends the pairs of a problem id and a memo to the list, and returns the rejected ones.
+    async fn import_items(
+        &self,
+        internal_list_id: &str,
+        items: &[(&str, &str)],
+    ) -> Result<Vec<RejectedItem>>;
+    /// Creates a list with the pairs of a problem id and a memo as in
+    /// [ProblemListManager::import_items], and returns the id of the list and the rejected items.
+    /// Nothing is created if it fails.
+    async fn create_list_with_items(
+        &self,
+        internal_user_id: &str,
+        name: &str,
+        items: &[(&str, &str)],
+    ) -> Result<(String, Vec<RejectedItem>)>;
+    /// Returns the progress of `atcoder_user_id` on the items of the list in their order.
+    /// If the owner of the list is `atcoder_user_id`, the submissions before the progress resets
+    /// of the owner are ignored.
+    async fn get_list_progress(
+        &self,
+        internal_list_id: &str,
+        atcoder_user_id: &str,
+    ) -> Result<Vec<ItemProgress>>;
+    /// Copies the list and its items under `internal_user_id`, and returns the id of the new list.
+    async fn fork_list(&self, internal_list_id: &str, internal_user_id: &str) -> Result<String>;
 }
 
 #[async_trait]
 impl ProblemListManager for PgPool {
     async fn get_list(&self, internal_user_id: &str) -> Result<Vec<ProblemList>> {
-        let items = sqlx::query(
+        let items = sqlx::query(&select_lists("a.internal_user_id = $1"))
+            .bind(internal_user_id)
+            .try_map(list_row_mapper)
+            .fetch_all(self)
+            .await?;
+        Ok(group_items(items))
+    }
+
+    async fn get_single_list(&self, internal_list_id: &str) -> Result<ProblemList> {
+        let items = sqlx::query(&select_lists("a.internal_list_id = $1"))
+            .bind(internal_list_id)
+            .try_map(list_row_mapper)
+            .fetch_all(self)
+            .await?;
+        let list = group_items(items)
+            .into_iter()
+            .next()
+            .context("list not found")?;
+        Ok(list)
+    }
+
+    async fn get_shared_lists(&self, internal_user_id: &str) -> Result<Vec<ProblemList>> {
+        let items = sqlx::query(&select_lists(
             r"
-        SELECT
-            a.internal_list_id,
-            a.internal_list_name,
-            a.internal_user_id,
-            b.problem_id,
-            b.memo
-        FROM internal_problem_lists AS a
-        LEFT JOIN internal_problem_list_items AS b
-        ON a.internal_list_id = b.internal_list_id
-        WHERE a.internal_user_id = $1
+            a.internal_list_id IN (
+                SELECT internal_list_id FROM internal_problem_list_editors
+                WHERE internal_user_id = $1
+            )",
+        ))
+        .bind(internal_user_id)
+        .try_map(list_row_mapper)
+        .fetch_all(self)
+        .await?;
+        Ok(group_items(items))
+    }
+
+    async fn get_list_editors(&self, internal_list_id: &str) -> Result<Vec<ListEditor>> {
+        let editors = sqlx::query(
+            r"
+            SELECT a.internal_user_id, b.atcoder_user_id
+            FROM internal_problem_list_editors AS a
+            LEFT JOIN internal_users AS b
+            ON a.internal_user_id = b.internal_user_id
+            WHERE a.internal_list_id = $1
+            ORDER BY a.internal_user_id ASC
             ",
         )
-        .bind(internal_user_id)
+        .bind(internal_list_id)
         .try_map(|row: PgRow| {
-            let internal_list_id: String = row.try_get("internal_list_id")?;
-            let internal_list_name: String = row.try_get("internal_list_name")?;
             let internal_user_id: String = row.try_get("internal_user_id")?;
-            let problem_id: Option<String> = row.try_get("problem_id")?;
-            let memo: Option<String> = row.try_get("memo")?;
-            Ok((
-                internal_list_id,
-                internal_list_name,
+            let atcoder_user_id: Option<String> = row.try_get("atcoder_user_id")?;
+            Ok(ListEditor {
                 internal_user_id,
-                problem_id,
-                memo,
-            ))
+                atcoder_user_id,
+            })
         })
         .fetch_all(self)
         .await?;
-        let mut map = BTreeMap::new();
-        for (list_id, list_name, user_id, problem_id, memo) in items.into_iter() {
-            let list = map
-                .entry(list_id)
-                .or_insert((list_name, user_id, Vec::new()));
-            if let (Some(problem_id), Some(memo)) = (problem_id, memo) {
-                list.2.push(ListItem { problem_id, memo });
-            }
-        }
-        let list = map
-            .into_iter()
-            .map(
-                |(internal_list_id, (internal_list_name, internal_user_id, items))| ProblemList {
-                    internal_list_id,
-                    internal_user_id,
-                    internal_list_name,
-                    items,
-                },
-            )
-            .collect();
-        Ok(list)
+        Ok(editors)
     }
 
-    async fn get_single_list(&self, internal_list_id: &str) -> Result<ProblemList> {
-        let items = sqlx::query(
+    async fn add_list_editor(&self, internal_list_id: &str, internal_user_id: &str) -> Result<()> {
+        sqlx::query(
             r"
-        SELECT
-            a.internal_list_id,
-            a.internal_list_name,
-            a.internal_user_id,
-            b.problem_id,
-            b.memo
-        FROM internal_problem_lists AS a
-        LEFT JOIN internal_problem_list_items AS b
-        ON a.internal_list_id = b.internal_list_id
-        WHERE a.internal_list_id = $1
+            INSERT INTO internal_problem_list_editors (internal_list_id, internal_user_id)
+            VALUES ($1, $2)
+            ON CONFLICT DO NOTHING
             ",
         )
         .bind(internal_list_id)
-        .map(|row: PgRow| {
-            let internal_list_id: String = row.get(0);
-            let internal_list_name: String = row.get(1);
-            let internal_user_id: String = row.get(2);
-            let problem_id: Option<String> = row.get(3);
-            let memo: Option<String> = row.get(4);
-            (
-                internal_list_id,
-                internal_list_name,
-                internal_user_id,
-                problem_id,
-                memo,
-            )
-        })
-        .fetch_all(self)
+        .bind(internal_user_id)
+        .execute(self)
         .await?;
-        let mut map = BTreeMap::new();
-        for (list_id, list_name, user_id, problem_id, memo) in items.into_iter() {
-            let list = map
-                .entry(list_id)
-                .or_insert((list_name, user_id, Vec::new()));
-            if let (Some(problem_id), Some(memo)) = (problem_id, memo) {
-                list.2.push(ListItem { problem_id, memo });
-            }
-        }
-        let list = map
-            .into_iter()
-            .map(
-                |(internal_list_id, (internal_list_name, internal_user_id, items))| ProblemList {
-                    internal_list_id,
-                    internal_user_id,
-                    internal_list_name,
-                    items,
-                },
-            )
-            .next()
-            .context("list not found")?;
-        Ok(list)
+        Ok(())
+    }
+
+    async fn remove_list_editor(
+        &self,
+        internal_list_id: &str,
+        internal_user_id: &str,
+    ) -> Result<()> {
+        sqlx::query(
+            r"
+            DELETE FROM internal_problem_list_editors
+            WHERE internal_list_id = $1
+            AND internal_user_id = $2
+            ",
+        )
+        .bind(internal_list_id)
+        .bind(internal_user_id)
+        .execute(self)
+        .await?;
+        Ok(())
     }
 
     async fn create_list(&self, internal_user_id: &str, name: &str) -> Result<String> {
@@ -178,6 +280,7 @@ impl ProblemListManager for PgPool {
         UPDATE internal_problem_lists
         SET internal_list_name = $1
         WHERE internal_list_id = $2
+        AND deleted_at IS NULL
             ",
         )
         .bind(name)
@@ -187,12 +290,104 @@ impl ProblemListManager for PgPool {
         Ok(())
     }
 
-    async fn delete_list(&self, internal_list_id: &str) -> Result<()> {
-        sqlx::query("DELETE FROM internal_problem_lists WHERE internal_list_id = $1")
-            .bind(internal_list_id)
+    async fn delete_list(
+        &self,
+        internal_list_id: &str,
+        internal_user_id: &str,
+        now: i64,
+    ) -> Result<()> {
+        let result = sqlx::query(
+            r"
+        UPDATE internal_problem_lists
+        SET deleted_at = $1
+        WHERE internal_list_id = $2
+        AND internal_user_id = $3
+        AND deleted_at IS NULL
+            ",
+        )
+        .bind(now)
+        .bind(internal_list_id)
+        .bind(internal_user_id)
+        .execute(self)
+        .await?;
+        if result.rows_affected() == 0 {
+            bail!("The target list does not exist or is not owned by the user.");
+        }
+        Ok(())
+    }
+
+    async fn restore_list(
+        &self,
+        internal_list_id: &str,
+        internal_user_id: &str,
+        now: i64,
+    ) -> Result<()> {
+        let list = self.get_list(internal_user_id).await?;
+        if list.len() >= MAX_LIST_NUM {
+            bail!("Cannot restore a list anymore");
+        }
+
+        let result = sqlx::query(
+            r"
+        UPDATE internal_problem_lists
+        SET deleted_at = NULL
+        WHERE internal_list_id = $1
+        AND internal_user_id = $2
+        AND deleted_at > $3
+            ",
+        )
+        .bind(internal_list_id)
+        .bind(internal_user_id)
+        .bind(now - DELETED_RETENTION_SECOND)
+        .execute(self)
+        .await?;
+        if result.rows_affected() == 0 {
+            bail!("The target list is not a restorable list of the user.");
+        }
+        Ok(())
+    }
+
+    async fn get_deleted_lists(
+        &self,
+        internal_user_id: &str,
+        now: i64,
+    ) -> Result<Vec<ProblemList>> {
+        let list = sqlx::query(
+            r"
+        SELECT internal_list_id, internal_list_name, internal_user_id, source_list_id
+        FROM internal_problem_lists
+        WHERE internal_user_id = $1
+        AND deleted_at > $2
+        ORDER BY deleted_at DESC
+            ",
+        )
+        .bind(internal_user_id)
+        .bind(now - DELETED_RETENTION_SECOND)
+        .try_map(|row: PgRow| {
+            let internal_list_id: String = row.try_get("internal_list_id")?;
+            let internal_list_name: String = row.try_get("internal_list_name")?;
+            let internal_user_id: String = row.try_get("internal_user_id")?;
+            let source_list_id: Option<String> = row.try_get("source_list_id")?;
+            Ok(ProblemList {
+                internal_list_id,
+                internal_list_name,
+                internal_user_id,
+                source_list_id,
+                fork_count: 0,
+                items: Vec::new(),
+            })
+        })
+        .fetch_all(self)
+        .await?;
+        Ok(list)
+    }
+
+    async fn purge_deleted_lists(&self, now: i64) -> Result<u64> {
+        let result = sqlx::query("DELETE FROM internal_problem_lists WHERE deleted_at <= $1")
+            .bind(now - DELETED_RETENTION_SECOND)
             .execute(self)
             .await?;
-        Ok(())
+        Ok(result.rows_affected())
     }
 
     async fn add_item(&self, internal_list_id: &str, problem_id: &str) -> Result<()> {
@@ -207,16 +402,26 @@ impl ProblemListManager for PgPool {
             bail!("Cannot create a list item anymore");
         }
 
-        sqlx::query(
+        let result = sqlx::query(
             r"
-            INSERT INTO internal_problem_list_items (internal_list_id, problem_id)
-            VALUES ($1, $2)
+            INSERT INTO internal_problem_list_items (internal_list_id, problem_id, position)
+            SELECT internal_list_id, $2, (
+                SELECT COALESCE(MAX(position) + 1, 0)
+                FROM internal_problem_list_items
+                WHERE internal_list_id = $1
+            )
+            FROM internal_problem_lists
+            WHERE internal_list_id = $1
+            AND deleted_at IS NULL
             ",
         )
         .bind(internal_list_id)
         .bind(problem_id)
         .execute(self)
         .await?;
+        if result.rows_affected() == 0 {
+            bail!("The target list does not exist.");
+        }
         Ok(())
     }
 
@@ -231,6 +436,9 @@ impl ProblemListManager for PgPool {
         UPDATE internal_problem_list_items
         SET memo = $1
         WHERE internal_list_id = $2 AND problem_id = $3
+        AND internal_list_id IN (
+            SELECT internal_list_id FROM internal_problem_lists WHERE deleted_at IS NULL
+        )
             ",
         )
         .bind(memo)
@@ -246,6 +454,9 @@ impl ProblemListManager for PgPool {
             r"
             DELETE FROM internal_problem_list_items
             WHERE internal_list_id = $1 AND problem_id = $2
+            AND internal_list_id IN (
+                SELECT internal_list_id FROM internal_problem_lists WHERE deleted_at IS NULL
+            )
             ",
         )
         .bind(internal_list_id)
@@ -254,4 +465,400 @@ impl ProblemListManager for PgPool {
         .await?;
         Ok(())
     }
+
+    async fn reorder_items(&self, internal_list_id: &str, problem_ids: &[&str]) -> Result<()> {
+        let list = self.get_single_list(internal_list_id).await?;
+        let current = list
+            .items
+            .iter()
+            .map(|item| item.problem_id.as_str())
+            .collect::<BTreeSet<_>>();
+        let requested = problem_ids.iter().copied().collect::<BTreeSet<_>>();
+        if requested.len() != problem_ids.len() || requested != current {
+            bail!("The problems do not match the items of the list.");
+        }
+
+        sqlx::query(
+            r"
+            UPDATE internal_problem_list_items AS a
+            SET position = b.position - 1
+            FROM UNNEST($2::VARCHAR(255)[]) WITH ORDINALITY AS b(problem_id, position)
+            WHERE a.internal_list_id = $1
+            AND a.problem_id = b.problem_id
+            ",
+        )
+        .bind(internal_list_id)
+        .bind(problem_ids)
+        .execute(self)
+        .await?;
+        Ok(())
+    }
+
+    async fn update_item_tags(
+        &self,
+        internal_list_id: &str,
+        problem_id: &str,
+        tags: &[&str],
+    ) -> Result<()> {
+        let tags = tags.iter().map(|tag| tag.trim()).collect::<BTreeSet<_>>();
+        if tags.len() > MAX_TAG_NUM {
+            bail!("Too many tags");
+        }
+        if tags
+            .iter()
+            .any(|tag| tag.is_empty() || tag.chars().count() > MAX_TAG_LENGTH)
+        {
+            bail!("Invalid tag");
+        }
+        let tags = tags.into_iter().collect::<Vec<_>>();
+
+        let mut tx = self.begin().await?;
+        let item = sqlx::query(
+            r"
+            SELECT a.problem_id FROM internal_problem_list_items AS a
+            JOIN internal_problem_lists AS b
+            ON a.internal_list_id = b.internal_list_id
+            WHERE a.internal_list_id = $1 AND a.problem_id = $2
+            AND b.deleted_at IS NULL
+            ",
+        )
+        .bind(internal_list_id)
+        .bind(problem_id)
+        .fetch_optional(&mut tx)
+        .await?;
+        if item.is_none() {
+            bail!("The target item does not exist.");
+        }
+
+        sqlx::query(
+            r"
+            DELETE FROM internal_problem_list_item_tags
+            WHERE internal_list_id = $1 AND problem_id = $2
+            ",
+        )
+        .bind(internal_list_id)
+        .bind(problem_id)
+        .execute(&mut tx)
+        .await?;
+        sqlx::query(
+            r"
+            INSERT INTO internal_problem_list_item_tags (internal_list_id, problem_id, tag)
+            SELECT $1, $2, UNNEST($3::VARCHAR(255)[])
+            ",
+        )
+        .bind(internal_list_id)
+        .bind(problem_id)
+        .bind(tags)
+        .execute(&mut tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn import_items(
+        &self,
+        internal_list_id: &str,
+        items: &[(&str, &str)],
+    ) -> Result<Vec<RejectedItem>> {
+        let list = self.get_single_list(internal_list_id).await?;
+        let problem_ids = list
+            .items
+            .iter()
+            .map(|item| item.problem_id.clone())
+            .collect::<BTreeSet<_>>();
+        let (accepted, rejected) = check_imported_items(self, problem_ids, items).await?;
+
+        let mut tx = self.begin().await?;
+        insert_imported_items(&mut tx, internal_list_id, &accepted).await?;
+        tx.commit().await?;
+        Ok(rejected)
+    }
+
+    async fn create_list_with_items(
+        &self,
+        internal_user_id: &str,
+        name: &str,
+        items: &[(&str, &str)],
+    ) -> Result<(String, Vec<RejectedItem>)> {
+        let list = self.get_list(internal_user_id).await?;
+        if list.len() >= MAX_LIST_NUM {
+            bail!("Cannot create a list anymore");
+        }
+        let (accepted, rejected) = check_imported_items(self, BTreeSet::new(), items).await?;
+
+        let new_list_id = uuid::Uuid::new_v4().to_string();
+        let mut tx = self.begin().await?;
+        sqlx::query(
+            r"
+            INSERT INTO internal_problem_lists
+            (internal_user_id, internal_list_id, internal_list_name)
+            VALUES ($1, $2, $3)
+            ",
+        )
+        .bind(internal_user_id)
+        .bind(new_list_id.as_str())
+        .bind(name)
+        .execute(&mut tx)
+        .await?;
+        insert_imported_items(&mut tx, &new_list_id, &accepted).await?;
+        tx.commit().await?;
+        Ok((new_list_id, rejected))
+    }
+
+    async fn get_list_progress(
+        &self,
+        internal_list_id: &str,
+        atcoder_user_id: &str,
+    ) -> Result<Vec<ItemProgress>> {
+        self.get_single_list(internal_list_id).await?;
+
+        let progress = sqlx::query(
+            r"
+            SELECT
+                b.problem_id,
+                COUNT(e.id) AS submission_count,
+                MIN(e.epoch_second) FILTER (WHERE e.result = 'AC') AS first_ac_epoch_second
+            FROM internal_problem_lists AS a
+            JOIN internal_problem_list_items AS b
+            ON a.internal_list_id = b.internal_list_id
+            LEFT JOIN internal_users AS c
+            ON a.internal_user_id = c.internal_user_id
+            AND LOWER(c.atcoder_user_id) = LOWER($2)
+            LEFT JOIN internal_progress_reset AS d
+            ON c.internal_user_id = d.internal_user_id
+            AND b.problem_id = d.problem_id
+            LEFT JOIN submissions AS e
+            ON b.problem_id = e.problem_id
+            AND LOWER(e.user_id) = LOWER($2)
+            AND (d.reset_epoch_second IS NULL OR e.epoch_second > d.reset_epoch_second)
+            WHERE a.internal_list_id = $1
+            AND a.deleted_at IS NULL
+            GROUP BY b.problem_id, b.position
+            ORDER BY b.position, b.problem_id
+            ",
+        )
+        .bind(internal_list_id)
+        .bind(atcoder_user_id)
+        .try_map(|row: PgRow| {
+            let problem_id: String = row.try_get("problem_id")?;
+            let submission_count: i64 = row.try_get("submission_count")?;
+            let first_ac_epoch_second: Option<i64> = row.try_get("first_ac_epoch_second")?;
+            let status = match (first_ac_epoch_second, submission_count) {
+                (Some(_), _) => ItemStatus::Accepted,
+                (None, 0) => ItemStatus::Untouched,
+                (None, _) => ItemStatus::Tried,
+            };
+            Ok(ItemProgress {
+                problem_id,
+                status,
+                first_ac_epoch_second,
+            })
+        })
+        .fetch_all(self)
+        .await?;
+        Ok(progress)
+    }
+
+    async fn fork_list(&self, internal_list_id: &str, internal_user_id: &str) -> Result<String> {
+        let list = self.get_list(internal_user_id).await?;
+        if list.len() >= MAX_LIST_NUM {
+            bail!("Cannot create a list anymore");
+        }
+
+        let new_list_id = uuid::Uuid::new_v4().to_string();
+        let mut tx = self.begin().await?;
+
+        let result = sqlx::query(
+            r"
+            INSERT INTO internal_problem_lists
+            (internal_user_id, internal_list_id, internal_list_name, source_list_id)
+            SELECT $1, $2, internal_list_name, internal_list_id
+            FROM internal_problem_lists
+            WHERE internal_list_id = $3
+            AND deleted_at IS NULL
+            ",
+        )
+        .bind(internal_user_id)
+        .bind(new_list_id.as_str())
+        .bind(internal_list_id)
+        .execute(&mut tx)
+        .await?;
+        if result.rows_affected() == 0 {
+            bail!("The target list does not exist.");
+        }
+
+        sqlx::query(
+            r"
+            INSERT INTO internal_problem_list_items (internal_list_id, problem_id, memo, position)
+            SELECT $1, problem_id, memo, position
+            FROM internal_problem_list_items
+            WHERE internal_list_id = $2
+            ",
+        )
+        .bind(new_list_id.as_str())
+        .bind(internal_list_id)
+        .execute(&mut tx)
+        .await?;
+
+        sqlx::query(
+            r"
+            INSERT INTO internal_problem_list_item_tags (internal_list_id, problem_id, tag)
+            SELECT $1, problem_id, tag
+            FROM internal_problem_list_item_tags
+            WHERE internal_list_id = $2
+            ",
+        )
+        .bind(new_list_id.as_str())
+        .bind(internal_list_id)
+        .execute(&mut tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(new_list_id)
+    }
+}
+
+/// Splits the imported pairs of a problem id and a memo into the accepted ones and the rejected
+/// ones, given the problems already in the list.
+async fn check_imported_items<'a>(
+    conn: &PgPool,
+    mut problem_ids: BTreeSet<String>,
+    items: &[(&'a str, &'a str)],
+) -> Result<(Vec<(&'a str, &'a str)>, Vec<RejectedItem>)> {
+    let requested = items.iter().map(|(id, _)| *id).collect::<Vec<_>>();
+    let known_problems = sqlx::query("SELECT id FROM problems WHERE id = ANY($1)")
+        .bind(requested)
+        .try_map(|row: PgRow| row.try_get::<String, _>("id"))
+        .fetch_all(conn)
+        .await?
+        .into_iter()
+        .collect::<BTreeSet<_>>();
+
+    let mut accepted = Vec::new();
+    let mut rejected = Vec::new();
+    for (index, &(problem_id, memo)) in items.iter().enumerate() {
+        let reason = if !known_problems.contains(problem_id) {
+            Some(ImportRejection::UnknownProblem)
+        } else if problem_ids.contains(problem_id) {
+            Some(ImportRejection::Duplicate)
+        } else if memo.chars().count() > MAX_MEMO_LENGTH {
+            Some(ImportRejection::TooLongMemo)
+        } else if problem_ids.len() >= MAX_ITEM_NUM {
+            Some(ImportRejection::TooManyItems)
+        } else {
+            None
+        };
+        match reason {
+            Some(reason) => rejected.push(RejectedItem {
+                index,
+                problem_id: problem_id.to_string(),
+                reason,
+            }),
+            None => {
+                problem_ids.insert(problem_id.to_string());
+                accepted.push((problem_id, memo));
+            }
+        }
+    }
+    Ok((accepted, rejected))
+}
+
+/// Appends the pairs of a problem id and a memo to the end of the list.
+async fn insert_imported_items(
+    tx: &mut Transaction<'_, Postgres>,
+    internal_list_id: &str,
+    items: &[(&str, &str)],
+) -> Result<()> {
+    if items.is_empty() {
+        return Ok(());
+    }
+    let (problem_ids, memos): (Vec<_>, Vec<_>) = items.iter().copied().unzip();
+    sqlx::query(
+        r"
+        INSERT INTO internal_problem_list_items (internal_list_id, problem_id, memo, position)
+        SELECT $1, u.problem_id, u.memo, u.position - 1 + (
+            SELECT COALESCE(MAX(position) + 1, 0)
+            FROM internal_problem_list_items
+            WHERE internal_list_id = $1
+        )
+        FROM UNNEST($2::VARCHAR(255)[], $3::VARCHAR(255)[])
+        WITH ORDINALITY AS u(problem_id, memo, position)
+        ",
+    )
+    .bind(internal_list_id)
+    .bind(problem_ids)
+    .bind(memos)
+    .execute(&mut *tx)
+    .await?;
+    Ok(())
+}
+
+/// A list, and the problem id, the memo and the tags of one of its items if any.
+type ListRow = (ProblemList, Option<String>, Option<String>, Vec<String>);
+
+/// Returns the query of the lists which are not deleted and satisfy `condition`, joined with
+/// their items.
+fn select_lists(condition: &str) -> String {
+    format!(
+        r"
+        SELECT
+            a.internal_list_id,
+            a.internal_list_name,
+            a.internal_user_id,
+            a.source_list_id,
+            (
+                SELECT COUNT(*) FROM internal_problem_lists AS f
+                WHERE f.source_list_id = a.internal_list_id
+                AND f.deleted_at IS NULL
+            ) AS fork_count,
+            b.problem_id,
+            b.memo,
+            ARRAY(
+                SELECT t.tag::TEXT FROM internal_problem_list_item_tags AS t
+                WHERE t.internal_list_id = b.internal_list_id
+                AND t.problem_id = b.problem_id
+                ORDER BY t.tag
+            ) AS tags
+        FROM internal_problem_lists AS a
+        LEFT JOIN internal_problem_list_items AS b
+        ON a.internal_list_id = b.internal_list_id
+        WHERE {}
+        AND a.deleted_at IS NULL
+        ORDER BY a.internal_list_id, b.position, b.problem_id
+        ",
+        condition
+    )
+}
+
+fn list_row_mapper(row: PgRow) -> StdResult<ListRow, sqlx::Error> {
+    let list = ProblemList {
+        internal_list_id: row.try_get("internal_list_id")?,
+        internal_list_name: row.try_get("internal_list_name")?,
+        internal_user_id: row.try_get("internal_user_id")?,
+        source_list_id: row.try_get("source_list_id")?,
+        fork_count: row.try_get("fork_count")?,
+        items: Vec::new(),
+    };
+    let problem_id: Option<String> = row.try_get("problem_id")?;
+    let memo: Option<String> = row.try_get("memo")?;
+    let tags: Vec<String> = row.try_get("tags")?;
+    Ok((list, problem_id, memo, tags))
+}
+
+/// Groups the rows of lists joined with their items, keeping the lists ordered by their ids and
+/// the items in the order of the rows.
+fn group_items(rows: Vec<ListRow>) -> Vec<ProblemList> {
+    let mut map = BTreeMap::new();
+    for (list, problem_id, memo, tags) in rows.into_iter() {
+        let list = map.entry(list.internal_list_id.clone()).or_insert(list);
+        if let (Some(problem_id), Some(memo)) = (problem_id, memo) {
+            list.items.push(ListItem {
+                problem_id,
+                memo,
+                tags,
+            });
+        }
+    }
+    map.into_iter().map(|(_, list)| list).collect()
 }
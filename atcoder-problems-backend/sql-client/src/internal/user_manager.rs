@@ -1,25 +1,36 @@
 use crate::PgPool;
-use anyhow::Result;
+use anyhow::{bail, Result};
 use async_trait::async_trait;
 use serde::Serialize;
 use sqlx::postgres::PgRow;
 use sqlx::Row;
+use uuid::Uuid;
 
 #[derive(Serialize, Debug, PartialEq, Eq)]
 pub struct InternalUserInfo {
     pub internal_user_id: String,
     pub atcoder_user_id: Option<String>,
+    /// Offset from UTC in minutes, which decides the day boundaries of the user's statistics.
+    pub timezone_offset: i32,
 }
 
 #[async_trait]
 pub trait UserManager {
     async fn register_user(&self, internal_user_id: &str) -> Result<()>;
+    /// Updates the AtCoder ID and the timezone of the user at once. `None` keeps the current
+    /// timezone. Fails if the user does not exist.
     async fn update_internal_user_info(
         &self,
         internal_user_id: &str,
         atcoder_user_id: &str,
+        timezone_offset: Option<i32>,
     ) -> Result<()>;
     async fn get_internal_user_info(&self, internal_user_id: &str) -> Result<InternalUserInfo>;
+
+    async fn get_calendar_token(&self, internal_user_id: &str) -> Result<Option<String>>;
+    /// Generates a new secret token of the user's calendar feed, which revokes the previous one.
+    async fn generate_calendar_token(&self, internal_user_id: &str) -> Result<String>;
+    async fn get_user_by_calendar_token(&self, token: &str) -> Result<Option<String>>;
 }
 
 #[async_trait]
@@ -42,25 +53,30 @@ impl UserManager for PgPool {
         &self,
         internal_user_id: &str,
         atcoder_user_id: &str,
+        timezone_offset: Option<i32>,
     ) -> Result<()> {
-        sqlx::query(
+        let result = sqlx::query(
             r"
             UPDATE internal_users
-            SET atcoder_user_id = $1
-            WHERE internal_user_id = $2
+            SET atcoder_user_id = $1, timezone_offset = COALESCE($2, timezone_offset)
+            WHERE internal_user_id = $3
             ",
         )
         .bind(atcoder_user_id)
+        .bind(timezone_offset)
         .bind(internal_user_id)
         .execute(self)
         .await?;
+        if result.rows_affected() == 0 {
+            bail!("The target user does not exist.");
+        }
         Ok(())
     }
 
     async fn get_internal_user_info(&self, internal_user_id: &str) -> Result<InternalUserInfo> {
         let res = sqlx::query(
             r"
-            SELECT internal_user_id, atcoder_user_id
+            SELECT internal_user_id, atcoder_user_id, timezone_offset
             FROM internal_users
             WHERE internal_user_id = $1
             ",
@@ -69,13 +85,64 @@ impl UserManager for PgPool {
         .try_map(|row: PgRow| {
             let internal_user_id: String = row.try_get("internal_user_id")?;
             let atcoder_user_id: Option<String> = row.try_get("atcoder_user_id")?;
+            let timezone_offset: i32 = row.try_get("timezone_offset")?;
             Ok(InternalUserInfo {
                 internal_user_id,
                 atcoder_user_id,
+                timezone_offset,
             })
         })
         .fetch_one(self)
         .await?;
         Ok(res)
     }
+
+    async fn get_calendar_token(&self, internal_user_id: &str) -> Result<Option<String>> {
+        let token = sqlx::query(
+            r"
+            SELECT calendar_token
+            FROM internal_users
+            WHERE internal_user_id = $1
+            ",
+        )
+        .bind(internal_user_id)
+        .try_map(|row: PgRow| row.try_get::<Option<String>, _>("calendar_token"))
+        .fetch_one(self)
+        .await?;
+        Ok(token)
+    }
+
+    async fn generate_calendar_token(&self, internal_user_id: &str) -> Result<String> {
+        let token = Uuid::new_v4().to_string();
+        let result = sqlx::query(
+            r"
+            UPDATE internal_users
+            SET calendar_token = $1
+            WHERE internal_user_id = $2
+            ",
+        )
+        .bind(&token)
+        .bind(internal_user_id)
+        .execute(self)
+        .await?;
+        if result.rows_affected() == 0 {
+            bail!("The target user does not exist.");
+        }
+        Ok(token)
+    }
+
+    async fn get_user_by_calendar_token(&self, token: &str) -> Result<Option<String>> {
+        let internal_user_id = sqlx::query(
+            r"
+            SELECT internal_user_id
+            FROM internal_users
+            WHERE calendar_token = $1
+            ",
+        )
+        .bind(token)
+        .try_map(|row: PgRow| row.try_get::<String, _>("internal_user_id"))
+        .fetch_optional(self)
+        .await?;
+        Ok(internal_user_id)
+    }
 }
@@ -1,14 +1,55 @@
+use crate::internal::DELETED_RETENTION_SECOND;
 use crate::PgPool;
-use anyhow::{bail, Context, Result};
+use anyhow::{anyhow, bail, Context, Result};
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use sqlx::postgres::PgRow;
-use sqlx::Row;
+use sqlx::{Postgres, Row, Transaction};
+use std::fmt;
 use std::result::Result as StdResult;
+use std::str::FromStr;
 use uuid::Uuid;
 
 pub const MAX_PROBLEM_NUM_PER_CONTEST: usize = 300;
 pub const RECENT_CONTEST_NUM: i64 = 1000;
+pub const MAX_TEAM_MEMBER_NUM: usize = 10;
+
+/// The mode of a contest. `None` in `VirtualContestInfo` means the normal mode.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum VirtualContestMode {
+    /// Only the first participant who solves a problem gets its point.
+    Lockout,
+    /// Every problem is worth 1 point.
+    Training,
+}
+
+impl VirtualContestMode {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            VirtualContestMode::Lockout => "lockout",
+            VirtualContestMode::Training => "training",
+        }
+    }
+}
+
+impl FromStr for VirtualContestMode {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "lockout" => Ok(VirtualContestMode::Lockout),
+            "training" => Ok(VirtualContestMode::Training),
+            _ => Err(anyhow!("Invalid mode: {}", s)),
+        }
+    }
+}
+
+/// Reads the mode of a contest. An unknown value, e.g. one stored before the mode was typed, is read
+/// as the normal mode so that a single contest does not break the queries of many contests.
+pub(crate) fn get_mode(row: &PgRow) -> StdResult<Option<VirtualContestMode>, sqlx::Error> {
+    let mode: Option<String> = row.try_get("mode")?;
+    Ok(mode.and_then(|mode| mode.parse().ok()))
+}
 
 #[derive(Serialize, Debug, PartialEq, Eq, Clone)]
 pub struct VirtualContestInfo {
@@ -18,9 +59,11 @@ pub struct VirtualContestInfo {
     pub owner_user_id: String, // column name is `internal_user_id`
     pub start_epoch_second: i64,
     pub duration_second: i64,
-    pub mode: Option<String>,
+    pub mode: Option<VirtualContestMode>,
     pub is_public: bool,
     pub penalty_second: i64,
+    /// The standings are frozen for this many seconds before the end. `0` means no freeze.
+    pub freeze_second: i64,
 }
 
 fn virtual_contest_info_mapper(row: PgRow) -> StdResult<VirtualContestInfo, sqlx::Error> {
@@ -30,9 +73,10 @@ fn virtual_contest_info_mapper(row: PgRow) -> StdResult<VirtualContestInfo, sqlx
     let owner_user_id: String = row.try_get("internal_user_id")?;
     let start_epoch_second: i64 = row.try_get("start_epoch_second")?;
     let duration_second: i64 = row.try_get("duration_second")?;
-    let mode: Option<String> = row.try_get("mode")?;
+    let mode = get_mode(&row)?;
     let is_public: bool = row.try_get("is_public")?;
     let penalty_second: i64 = row.try_get("penalty_second")?;
+    let freeze_second: i64 = row.try_get("freeze_second")?;
     Ok(VirtualContestInfo {
         id,
         title,
@@ -43,6 +87,7 @@ fn virtual_contest_info_mapper(row: PgRow) -> StdResult<VirtualContestInfo, sqlx
         mode,
         is_public,
         penalty_second,
+        freeze_second,
     })
 }
 
@@ -53,6 +98,95 @@ pub struct VirtualContestItem {
     pub order: Option<i64>,
 }
 
+#[derive(Serialize, Debug, PartialEq, Eq)]
+pub struct VirtualContestUser {
+    pub internal_user_id: String,
+    pub atcoder_user_id: Option<String>,
+}
+
+fn virtual_contest_user_mapper(row: PgRow) -> StdResult<VirtualContestUser, sqlx::Error> {
+    let internal_user_id: String = row.try_get("internal_user_id")?;
+    let atcoder_user_id: Option<String> = row.try_get("atcoder_user_id")?;
+    Ok(VirtualContestUser {
+        internal_user_id,
+        atcoder_user_id,
+    })
+}
+
+/// The error returned when a user is not allowed to join a contest, so that callers can tell it
+/// from other failures.
+#[derive(Debug)]
+pub struct NotJoinableError;
+
+impl fmt::Display for NotJoinableError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "The target contest does not exist or the user is not allowed to join it."
+        )
+    }
+}
+
+impl std::error::Error for NotJoinableError {}
+
+/// A team which participates in a contest as a unit.
+#[derive(Serialize, Debug, PartialEq, Eq, Clone)]
+pub struct VirtualContestTeam {
+    pub id: String,
+    pub name: String,
+    /// The user who registered the team.
+    pub internal_user_id: String,
+    /// AtCoder IDs of the members in lowercase and in ascending order.
+    pub members: Vec<String>,
+}
+
+/// State of a contest relative to a given time.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum VirtualContestState {
+    Upcoming,
+    Running,
+    Finished,
+}
+
+impl VirtualContestState {
+    fn as_str(self) -> &'static str {
+        match self {
+            VirtualContestState::Upcoming => "upcoming",
+            VirtualContestState::Running => "running",
+            VirtualContestState::Finished => "finished",
+        }
+    }
+}
+
+/// Conditions of [VirtualContestManager::search_contests]. `None` means "no restriction".
+#[derive(Default)]
+pub struct VirtualContestSearch<'a> {
+    /// Case-insensitive substring of the title.
+    pub keyword: Option<&'a str>,
+    pub owner_user_id: Option<&'a str>,
+    pub state: Option<VirtualContestState>,
+    /// The contest must contain this problem.
+    pub problem_id: Option<&'a str>,
+    pub offset: i64,
+    pub count: i64,
+}
+
+/// Escapes the wildcards of `LIKE` so that `keyword` matches literally.
+fn escape_like(keyword: &str) -> String {
+    keyword
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
+/// Restrictions on joining a contest. `None` means "no restriction".
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Default)]
+pub struct VirtualContestRegistration {
+    pub max_participants: Option<i64>,
+    pub registration_deadline_second: Option<i64>,
+}
+
 #[async_trait]
 pub trait VirtualContestManager {
     async fn create_contest(
@@ -62,9 +196,10 @@ pub trait VirtualContestManager {
         internal_user_id: &str,
         start_epoch_second: i64,
         duration_second: i64,
-        mode: Option<&str>,
+        mode: Option<VirtualContestMode>,
         is_public: bool,
         penalty_second: i64,
+        freeze_second: i64,
     ) -> Result<String>;
     async fn update_contest(
         &self,
@@ -73,10 +208,44 @@ pub trait VirtualContestManager {
         memo: &str,
         start_epoch_second: i64,
         duration_second: i64,
-        mode: Option<&str>,
+        mode: Option<VirtualContestMode>,
         is_public: bool,
         penalty_second: i64,
+        freeze_second: i64,
+        internal_user_id: &str,
+    ) -> Result<()>;
+    /// Creates a new contest owned by `internal_user_id` with the same settings and problems as
+    /// the given contest, and returns the ID of the new contest.
+    async fn clone_contest(
+        &self,
+        contest_id: &str,
+        internal_user_id: &str,
+        start_epoch_second: i64,
+    ) -> Result<String>;
+    /// Marks the contest owned by `internal_user_id` as deleted at `now`.
+    async fn delete_contest(
+        &self,
+        contest_id: &str,
+        internal_user_id: &str,
+        now: i64,
+    ) -> Result<()>;
+    /// Restores the contest owned by `internal_user_id` if it was deleted within
+    /// [DELETED_RETENTION_SECOND] before `now`.
+    async fn restore_contest(
+        &self,
+        contest_id: &str,
+        internal_user_id: &str,
+        now: i64,
     ) -> Result<()>;
+    /// Returns the contests owned by `internal_user_id` which are deleted but still restorable.
+    async fn get_deleted_contests(
+        &self,
+        internal_user_id: &str,
+        now: i64,
+    ) -> Result<Vec<VirtualContestInfo>>;
+    /// Permanently deletes the contests whose retention period has passed, and returns the
+    /// number of them.
+    async fn purge_deleted_contests(&self, now: i64) -> Result<u64>;
 
     async fn get_own_contests(&self, internal_user_id: &str) -> Result<Vec<VirtualContestInfo>>;
     async fn get_participated_contests(
@@ -90,7 +259,21 @@ pub trait VirtualContestManager {
         contest_id: &str,
     ) -> Result<Vec<VirtualContestItem>>;
     async fn get_recent_contest_info(&self) -> Result<Vec<VirtualContestInfo>>;
+    /// Returns public contests which satisfy `search`, in the same order as
+    /// `get_recent_contest_info`. The state is decided relative to `now`.
+    async fn search_contests(
+        &self,
+        search: VirtualContestSearch<'_>,
+        now: i64,
+    ) -> Result<Vec<VirtualContestInfo>>;
     async fn get_running_contest_problems(&self, time: i64) -> Result<Vec<(String, i64)>>;
+    /// Returns the contests which are frozen at `now` and contain any of the problems. Contests
+    /// are rarely frozen, so this is usually an empty lookup on a partial index.
+    async fn get_frozen_contests(
+        &self,
+        problem_ids: &[&str],
+        now: i64,
+    ) -> Result<Vec<VirtualContestInfo>>;
 
     async fn update_items(
         &self,
@@ -99,8 +282,64 @@ pub trait VirtualContestManager {
         user_id: &str,
     ) -> Result<()>;
 
-    async fn join_contest(&self, contest_id: &str, internal_user_id: &str) -> Result<()>;
+    /// Joins the contest. A private contest requires the invite token unless the user organizes it.
+    /// Banned users and members of a team in the contest cannot join, nor anyone after the
+    /// registration deadline at `now` or once the contest is full. Fails with [NotJoinableError] in
+    /// these cases.
+    async fn join_contest(
+        &self,
+        contest_id: &str,
+        internal_user_id: &str,
+        invite_token: Option<&str>,
+        now: i64,
+    ) -> Result<()>;
     async fn leave_contest(&self, contest_id: &str, internal_user_id: &str) -> Result<()>;
+
+    async fn get_contest_organizers(&self, contest_id: &str) -> Result<Vec<VirtualContestUser>>;
+    async fn add_contest_organizer(&self, contest_id: &str, internal_user_id: &str) -> Result<()>;
+    async fn remove_contest_organizer(
+        &self,
+        contest_id: &str,
+        internal_user_id: &str,
+    ) -> Result<()>;
+
+    async fn get_invite_token(&self, contest_id: &str) -> Result<Option<String>>;
+    /// Generates a new invite token, which revokes the previous one.
+    async fn generate_invite_token(&self, contest_id: &str) -> Result<String>;
+    async fn revoke_invite_token(&self, contest_id: &str) -> Result<()>;
+
+    async fn get_contest_participant_users(
+        &self,
+        contest_id: &str,
+    ) -> Result<Vec<VirtualContestUser>>;
+    async fn get_banned_users(&self, contest_id: &str) -> Result<Vec<VirtualContestUser>>;
+    /// Bans the user from the contest, removing the user from the participants and deleting the
+    /// teams which the user registered or is a member of.
+    async fn ban_user(&self, contest_id: &str, internal_user_id: &str) -> Result<()>;
+    async fn unban_user(&self, contest_id: &str, internal_user_id: &str) -> Result<()>;
+    async fn get_registration(&self, contest_id: &str) -> Result<VirtualContestRegistration>;
+    async fn update_registration(
+        &self,
+        contest_id: &str,
+        registration: &VirtualContestRegistration,
+    ) -> Result<()>;
+
+    async fn get_contest_teams(&self, contest_id: &str) -> Result<Vec<VirtualContestTeam>>;
+    /// Registers a team of the given AtCoder users, and returns the ID of the team. The AtCoder IDs
+    /// are case-insensitive and stored in lowercase. An AtCoder user can be a member of only one
+    /// team in a contest. The user who registers the team must be able to join the contest as in
+    /// [VirtualContestManager::join_contest], none of the members may be banned from it or take
+    /// part in it individually, and a team counts as one participant.
+    async fn create_team(
+        &self,
+        contest_id: &str,
+        internal_user_id: &str,
+        invite_token: Option<&str>,
+        name: &str,
+        members: &[&str],
+        now: i64,
+    ) -> Result<String>;
+    async fn delete_team(&self, contest_id: &str, team_id: &str) -> Result<()>;
 }
 
 #[async_trait]
@@ -112,16 +351,17 @@ impl VirtualContestManager for PgPool {
         internal_user_id: &str,
         start_epoch_second: i64,
         duration_second: i64,
-        mode: Option<&str>,
+        mode: Option<VirtualContestMode>,
         is_public: bool,
         penalty_second: i64,
+        freeze_second: i64,
     ) -> Result<String> {
         let uuid = Uuid::new_v4().to_string();
         sqlx::query(
             r"
             INSERT INTO internal_virtual_contests
-            (id, title, memo, internal_user_id, start_epoch_second, duration_second, mode, is_public, penalty_second)
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            (id, title, memo, internal_user_id, start_epoch_second, duration_second, mode, is_public, penalty_second, freeze_second)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
             ",
         )
         .bind(&uuid)
@@ -130,9 +370,10 @@ impl VirtualContestManager for PgPool {
         .bind(internal_user_id)
         .bind(start_epoch_second)
         .bind(duration_second)
-        .bind(mode)
+        .bind(mode.map(VirtualContestMode::as_str))
         .bind(is_public)
         .bind(penalty_second)
+        .bind(freeze_second)
         .execute(self).await?;
         Ok(uuid)
     }
@@ -144,11 +385,13 @@ impl VirtualContestManager for PgPool {
         memo: &str,
         start_epoch_second: i64,
         duration_second: i64,
-        mode: Option<&str>,
+        mode: Option<VirtualContestMode>,
         is_public: bool,
         penalty_second: i64,
+        freeze_second: i64,
+        internal_user_id: &str,
     ) -> Result<()> {
-        sqlx::query(
+        let result = sqlx::query(
             r"
             UPDATE internal_virtual_contests
             SET
@@ -158,23 +401,183 @@ impl VirtualContestManager for PgPool {
                 duration_second = $4,
                 mode = $5,
                 is_public = $6,
-                penalty_second = $7
-            WHERE id = $8
+                penalty_second = $7,
+                freeze_second = $8
+            WHERE id = $9
+            AND deleted_at IS NULL
+            AND (
+                internal_user_id = $10
+                OR EXISTS (
+                    SELECT 1 FROM internal_virtual_contest_organizers
+                    WHERE internal_virtual_contest_id = $9
+                    AND internal_user_id = $10
+                )
+            )
             ",
         )
         .bind(title)
         .bind(memo)
         .bind(start_epoch_second)
         .bind(duration_second)
-        .bind(mode)
+        .bind(mode.map(VirtualContestMode::as_str))
         .bind(is_public)
         .bind(penalty_second)
+        .bind(freeze_second)
         .bind(id)
+        .bind(internal_user_id)
+        .execute(self)
+        .await?;
+        if result.rows_affected() == 0 {
+            bail!("The target contest does not exist or is not organized by the user.");
+        }
+        Ok(())
+    }
+
+    async fn clone_contest(
+        &self,
+        contest_id: &str,
+        internal_user_id: &str,
+        start_epoch_second: i64,
+    ) -> Result<String> {
+        let uuid = Uuid::new_v4().to_string();
+        let mut tx = self.begin().await?;
+
+        let result = sqlx::query(
+            r"
+            INSERT INTO internal_virtual_contests
+            (id, title, memo, internal_user_id, start_epoch_second, duration_second, mode, is_public, penalty_second, freeze_second)
+            SELECT $1, title, memo, $2, $3, duration_second, mode, is_public, penalty_second, freeze_second
+            FROM internal_virtual_contests
+            WHERE id = $4
+            AND deleted_at IS NULL
+            ",
+        )
+        .bind(&uuid)
+        .bind(internal_user_id)
+        .bind(start_epoch_second)
+        .bind(contest_id)
+        .execute(&mut tx)
+        .await?;
+        if result.rows_affected() == 0 {
+            bail!("The target contest does not exist.");
+        }
+
+        sqlx::query(
+            r"
+            INSERT INTO internal_virtual_contest_items
+            (internal_virtual_contest_id, problem_id, user_defined_point, user_defined_order)
+            SELECT $1, problem_id, user_defined_point, user_defined_order
+            FROM internal_virtual_contest_items
+            WHERE internal_virtual_contest_id = $2
+            ",
+        )
+        .bind(&uuid)
+        .bind(contest_id)
+        .execute(&mut tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(uuid)
+    }
+
+    async fn delete_contest(
+        &self,
+        contest_id: &str,
+        internal_user_id: &str,
+        now: i64,
+    ) -> Result<()> {
+        let result = sqlx::query(
+            r"
+            UPDATE internal_virtual_contests
+            SET deleted_at = $1
+            WHERE id = $2
+            AND internal_user_id = $3
+            AND deleted_at IS NULL
+            ",
+        )
+        .bind(now)
+        .bind(contest_id)
+        .bind(internal_user_id)
+        .execute(self)
+        .await?;
+        if result.rows_affected() == 0 {
+            bail!("The target contest does not exist or is not owned by the user.");
+        }
+        Ok(())
+    }
+
+    async fn restore_contest(
+        &self,
+        contest_id: &str,
+        internal_user_id: &str,
+        now: i64,
+    ) -> Result<()> {
+        let result = sqlx::query(
+            r"
+            UPDATE internal_virtual_contests
+            SET deleted_at = NULL
+            WHERE id = $1
+            AND internal_user_id = $2
+            AND deleted_at > $3
+            ",
+        )
+        .bind(contest_id)
+        .bind(internal_user_id)
+        .bind(now - DELETED_RETENTION_SECOND)
         .execute(self)
         .await?;
+        if result.rows_affected() == 0 {
+            bail!("The target contest is not a restorable contest of the user.");
+        }
         Ok(())
     }
 
+    async fn get_deleted_contests(
+        &self,
+        internal_user_id: &str,
+        now: i64,
+    ) -> Result<Vec<VirtualContestInfo>> {
+        let contests = sqlx::query(
+            r"
+            SELECT
+                id,
+                title,
+                memo,
+                internal_user_id,
+                start_epoch_second,
+                duration_second,
+                mode,
+                is_public,
+                penalty_second,
+                freeze_second
+            FROM internal_virtual_contests
+            WHERE internal_user_id = $1
+            AND deleted_at > $2
+            ORDER BY deleted_at DESC
+            ",
+        )
+        .bind(internal_user_id)
+        .bind(now - DELETED_RETENTION_SECOND)
+        .try_map(virtual_contest_info_mapper)
+        .fetch_all(self)
+        .await?;
+
+        Ok(contests)
+    }
+
+    async fn purge_deleted_contests(&self, now: i64) -> Result<u64> {
+        let result = sqlx::query(
+            r"
+            DELETE FROM internal_virtual_contests
+            WHERE deleted_at <= $1
+            ",
+        )
+        .bind(now - DELETED_RETENTION_SECOND)
+        .execute(self)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
     async fn get_own_contests(&self, internal_user_id: &str) -> Result<Vec<VirtualContestInfo>> {
         let contests = sqlx::query(
             r"
@@ -187,9 +590,18 @@ impl VirtualContestManager for PgPool {
                 duration_second,
                 mode,
                 is_public,
-                penalty_second
+                penalty_second,
+                freeze_second
             FROM internal_virtual_contests
-            WHERE internal_user_id = $1
+            WHERE (
+                internal_user_id = $1
+                OR id IN (
+                    SELECT internal_virtual_contest_id
+                    FROM internal_virtual_contest_organizers
+                    WHERE internal_user_id = $1
+                )
+            )
+            AND deleted_at IS NULL
             ",
         )
         .bind(internal_user_id)
@@ -215,11 +627,13 @@ impl VirtualContestManager for PgPool {
                 a.duration_second,
                 a.mode,
                 a.is_public,
-                a.penalty_second
+                a.penalty_second,
+                a.freeze_second
             FROM internal_virtual_contests AS a
             LEFT JOIN internal_virtual_contest_participants AS b
             ON a.id = b.internal_virtual_contest_id
             WHERE b.internal_user_id = $1
+            AND a.deleted_at IS NULL
             ",
         )
         .bind(internal_user_id)
@@ -242,9 +656,11 @@ impl VirtualContestManager for PgPool {
                 duration_second,
                 mode,
                 is_public,
-                penalty_second
+                penalty_second,
+                freeze_second
             FROM internal_virtual_contests
             WHERE id = $1
+            AND deleted_at IS NULL
             ",
         )
         .bind(contest_id)
@@ -319,9 +735,11 @@ impl VirtualContestManager for PgPool {
                 duration_second,
                 mode,
                 is_public,
-                penalty_second
+                penalty_second,
+                freeze_second
             FROM internal_virtual_contests
             WHERE is_public IS TRUE
+            AND deleted_at IS NULL
             ORDER BY start_epoch_second + duration_second DESC
             LIMIT $1
             ",
@@ -334,6 +752,65 @@ impl VirtualContestManager for PgPool {
         Ok(contests)
     }
 
+    async fn search_contests(
+        &self,
+        search: VirtualContestSearch<'_>,
+        now: i64,
+    ) -> Result<Vec<VirtualContestInfo>> {
+        let contests = sqlx::query(
+            r"
+            SELECT 
+                id,
+                title,
+                memo,
+                internal_user_id,
+                start_epoch_second,
+                duration_second,
+                mode,
+                is_public,
+                penalty_second,
+                freeze_second
+            FROM internal_virtual_contests
+            WHERE is_public IS TRUE
+            AND deleted_at IS NULL
+            AND ($1::VARCHAR(255) IS NULL OR title ILIKE '%' || $1 || '%')
+            AND ($2::VARCHAR(255) IS NULL OR internal_user_id = $2)
+            AND (
+                $3::VARCHAR(255) IS NULL
+                OR ($3 = 'upcoming' AND $4 < start_epoch_second)
+                OR (
+                    $3 = 'running'
+                    AND start_epoch_second <= $4
+                    AND $4 < start_epoch_second + duration_second
+                )
+                OR ($3 = 'finished' AND start_epoch_second + duration_second <= $4)
+            )
+            AND (
+                $5::VARCHAR(255) IS NULL
+                OR EXISTS (
+                    SELECT 1 FROM internal_virtual_contest_items
+                    WHERE internal_virtual_contest_id = internal_virtual_contests.id
+                    AND problem_id = $5
+                )
+            )
+            ORDER BY start_epoch_second + duration_second DESC, id
+            LIMIT $6 OFFSET $7
+            ",
+        )
+        .bind(search.keyword.map(escape_like))
+        .bind(search.owner_user_id)
+        .bind(search.state.map(VirtualContestState::as_str))
+        .bind(now)
+        .bind(search.problem_id)
+        .bind(search.count)
+        .bind(search.offset)
+        .try_map(virtual_contest_info_mapper)
+        .fetch_all(self)
+        .await?;
+
+        Ok(contests)
+    }
+
     async fn get_running_contest_problems(&self, time: i64) -> Result<Vec<(String, i64)>> {
         let problems = sqlx::query(
             r"
@@ -343,6 +820,7 @@ impl VirtualContestManager for PgPool {
             ON a.internal_virtual_contest_id = b.id
             WHERE b.start_epoch_second <= $1
             AND b.start_epoch_second + b.duration_second >= $1
+            AND b.deleted_at IS NULL
             ",
         )
         .bind(time)
@@ -357,6 +835,45 @@ impl VirtualContestManager for PgPool {
         Ok(problems)
     }
 
+    async fn get_frozen_contests(
+        &self,
+        problem_ids: &[&str],
+        now: i64,
+    ) -> Result<Vec<VirtualContestInfo>> {
+        let contests = sqlx::query(
+            r"
+            SELECT
+                a.id,
+                a.title,
+                a.memo,
+                a.internal_user_id,
+                a.start_epoch_second,
+                a.duration_second,
+                a.mode,
+                a.is_public,
+                a.penalty_second,
+                a.freeze_second
+            FROM internal_virtual_contests AS a
+            WHERE a.freeze_second > 0
+            AND a.start_epoch_second + a.duration_second > $2
+            AND a.start_epoch_second + a.duration_second - a.freeze_second <= $2
+            AND a.deleted_at IS NULL
+            AND EXISTS (
+                SELECT 1 FROM internal_virtual_contest_items AS b
+                WHERE b.internal_virtual_contest_id = a.id
+                AND b.problem_id = ANY($1)
+            )
+            ",
+        )
+        .bind(problem_ids)
+        .bind(now)
+        .try_map(virtual_contest_info_mapper)
+        .fetch_all(self)
+        .await?;
+
+        Ok(contests)
+    }
+
     async fn update_items(
         &self,
         contest_id: &str,
@@ -372,8 +889,16 @@ impl VirtualContestManager for PgPool {
             r"
             SELECT id
             FROM internal_virtual_contests
-            WHERE internal_user_id = $1
-            AND id = $2
+            WHERE id = $2
+            AND deleted_at IS NULL
+            AND (
+                internal_user_id = $1
+                OR EXISTS (
+                    SELECT 1 FROM internal_virtual_contest_organizers
+                    WHERE internal_virtual_contest_id = $2
+                    AND internal_user_id = $1
+                )
+            )
             ",
         )
         .bind(user_id)
@@ -432,8 +957,40 @@ impl VirtualContestManager for PgPool {
         Ok(())
     }
 
-    async fn join_contest(&self, contest_id: &str, internal_user_id: &str) -> Result<()> {
-        sqlx::query(
+    async fn join_contest(
+        &self,
+        contest_id: &str,
+        internal_user_id: &str,
+        invite_token: Option<&str>,
+        now: i64,
+    ) -> Result<()> {
+        let mut tx = self.begin().await?;
+        lock_joinable_contest(
+            &mut tx,
+            contest_id,
+            internal_user_id,
+            invite_token,
+            &[],
+            now,
+        )
+        .await?;
+        let team_member = sqlx::query(
+            r"
+            SELECT 1 FROM internal_virtual_contest_team_members AS a
+            JOIN internal_users AS b
+            ON a.atcoder_user_id = LOWER(b.atcoder_user_id)
+            WHERE a.internal_virtual_contest_id = $1
+            AND b.internal_user_id = $2
+            ",
+        )
+        .bind(contest_id)
+        .bind(internal_user_id)
+        .fetch_optional(&mut tx)
+        .await?;
+        if team_member.is_some() {
+            return Err(NotJoinableError.into());
+        }
+        sqlx::query(
             r"
             INSERT INTO internal_virtual_contest_participants
             (internal_virtual_contest_id, internal_user_id)
@@ -442,12 +999,191 @@ impl VirtualContestManager for PgPool {
         )
         .bind(contest_id)
         .bind(internal_user_id)
+        .execute(&mut tx)
+        .await
+        .context("The user has already joined the contest.")?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn leave_contest(&self, contest_id: &str, internal_user_id: &str) -> Result<()> {
+        sqlx::query(
+            r"
+            DELETE FROM internal_virtual_contest_participants
+            WHERE internal_virtual_contest_id = $1
+            AND internal_user_id = $2
+            ",
+        )
+        .bind(contest_id)
+        .bind(internal_user_id)
         .execute(self)
         .await?;
         Ok(())
     }
 
-    async fn leave_contest(&self, contest_id: &str, internal_user_id: &str) -> Result<()> {
+    async fn get_contest_organizers(&self, contest_id: &str) -> Result<Vec<VirtualContestUser>> {
+        let organizers = sqlx::query(
+            r"
+            SELECT a.internal_user_id, b.atcoder_user_id
+            FROM internal_virtual_contest_organizers AS a
+            LEFT JOIN internal_users AS b
+            ON a.internal_user_id = b.internal_user_id
+            WHERE a.internal_virtual_contest_id = $1
+            ORDER BY a.internal_user_id ASC
+            ",
+        )
+        .bind(contest_id)
+        .try_map(virtual_contest_user_mapper)
+        .fetch_all(self)
+        .await?;
+        Ok(organizers)
+    }
+
+    async fn add_contest_organizer(&self, contest_id: &str, internal_user_id: &str) -> Result<()> {
+        sqlx::query(
+            r"
+            INSERT INTO internal_virtual_contest_organizers
+            (internal_virtual_contest_id, internal_user_id)
+            VALUES ($1, $2)
+            ON CONFLICT DO NOTHING
+            ",
+        )
+        .bind(contest_id)
+        .bind(internal_user_id)
+        .execute(self)
+        .await?;
+        Ok(())
+    }
+
+    async fn remove_contest_organizer(
+        &self,
+        contest_id: &str,
+        internal_user_id: &str,
+    ) -> Result<()> {
+        sqlx::query(
+            r"
+            DELETE FROM internal_virtual_contest_organizers
+            WHERE internal_virtual_contest_id = $1
+            AND internal_user_id = $2
+            ",
+        )
+        .bind(contest_id)
+        .bind(internal_user_id)
+        .execute(self)
+        .await?;
+        Ok(())
+    }
+
+    async fn get_invite_token(&self, contest_id: &str) -> Result<Option<String>> {
+        let token = sqlx::query(
+            r"
+            SELECT invite_token
+            FROM internal_virtual_contests
+            WHERE id = $1
+            AND deleted_at IS NULL
+            ",
+        )
+        .bind(contest_id)
+        .try_map(|row: PgRow| row.try_get::<Option<String>, _>("invite_token"))
+        .fetch_one(self)
+        .await?;
+        Ok(token)
+    }
+
+    async fn generate_invite_token(&self, contest_id: &str) -> Result<String> {
+        let token = Uuid::new_v4().to_string();
+        let result = sqlx::query(
+            r"
+            UPDATE internal_virtual_contests
+            SET invite_token = $1
+            WHERE id = $2
+            AND deleted_at IS NULL
+            ",
+        )
+        .bind(&token)
+        .bind(contest_id)
+        .execute(self)
+        .await?;
+        if result.rows_affected() == 0 {
+            bail!("The target contest does not exist.");
+        }
+        Ok(token)
+    }
+
+    async fn revoke_invite_token(&self, contest_id: &str) -> Result<()> {
+        sqlx::query(
+            r"
+            UPDATE internal_virtual_contests
+            SET invite_token = NULL
+            WHERE id = $1
+            AND deleted_at IS NULL
+            ",
+        )
+        .bind(contest_id)
+        .execute(self)
+        .await?;
+        Ok(())
+    }
+
+    async fn get_contest_participant_users(
+        &self,
+        contest_id: &str,
+    ) -> Result<Vec<VirtualContestUser>> {
+        let participants = sqlx::query(
+            r"
+            SELECT a.internal_user_id, b.atcoder_user_id
+            FROM internal_virtual_contest_participants AS a
+            LEFT JOIN internal_users AS b
+            ON a.internal_user_id = b.internal_user_id
+            WHERE a.internal_virtual_contest_id = $1
+            ORDER BY a.internal_user_id ASC
+            ",
+        )
+        .bind(contest_id)
+        .try_map(virtual_contest_user_mapper)
+        .fetch_all(self)
+        .await?;
+        Ok(participants)
+    }
+
+    async fn get_banned_users(&self, contest_id: &str) -> Result<Vec<VirtualContestUser>> {
+        let banned_users = sqlx::query(
+            r"
+            SELECT a.internal_user_id, b.atcoder_user_id
+            FROM internal_virtual_contest_bans AS a
+            LEFT JOIN internal_users AS b
+            ON a.internal_user_id = b.internal_user_id
+            WHERE a.internal_virtual_contest_id = $1
+            ORDER BY a.internal_user_id ASC
+            ",
+        )
+        .bind(contest_id)
+        .try_map(virtual_contest_user_mapper)
+        .fetch_all(self)
+        .await?;
+        Ok(banned_users)
+    }
+
+    async fn ban_user(&self, contest_id: &str, internal_user_id: &str) -> Result<()> {
+        let mut tx = self.begin().await?;
+        // Takes the same lock as the registrations so that no team of the user is registered
+        // concurrently.
+        sqlx::query("SELECT id FROM internal_virtual_contests WHERE id = $1 FOR UPDATE")
+            .bind(contest_id)
+            .fetch_optional(&mut tx)
+            .await?;
+        sqlx::query(
+            r"
+            INSERT INTO internal_virtual_contest_bans
+            (internal_virtual_contest_id, internal_user_id)
+            VALUES ($1, $2)
+            ON CONFLICT DO NOTHING
+            ",
+        )
+        .bind(contest_id)
+        .bind(internal_user_id)
+        .execute(&mut tx)
+        .await?;
         sqlx::query(
             r"
             DELETE FROM internal_virtual_contest_participants
@@ -457,8 +1193,292 @@ impl VirtualContestManager for PgPool {
         )
         .bind(contest_id)
         .bind(internal_user_id)
+        .execute(&mut tx)
+        .await?;
+        sqlx::query(
+            r"
+            DELETE FROM internal_virtual_contest_teams
+            WHERE internal_virtual_contest_id = $1
+            AND (
+                internal_user_id = $2
+                OR id IN (
+                    SELECT a.team_id FROM internal_virtual_contest_team_members AS a
+                    JOIN internal_users AS b
+                    ON a.atcoder_user_id = LOWER(b.atcoder_user_id)
+                    WHERE a.internal_virtual_contest_id = $1
+                    AND b.internal_user_id = $2
+                )
+            )
+            ",
+        )
+        .bind(contest_id)
+        .bind(internal_user_id)
+        .execute(&mut tx)
+        .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn unban_user(&self, contest_id: &str, internal_user_id: &str) -> Result<()> {
+        sqlx::query(
+            r"
+            DELETE FROM internal_virtual_contest_bans
+            WHERE internal_virtual_contest_id = $1
+            AND internal_user_id = $2
+            ",
+        )
+        .bind(contest_id)
+        .bind(internal_user_id)
+        .execute(self)
+        .await?;
+        Ok(())
+    }
+
+    async fn get_registration(&self, contest_id: &str) -> Result<VirtualContestRegistration> {
+        let registration = sqlx::query(
+            r"
+            SELECT max_participants, registration_deadline_second
+            FROM internal_virtual_contests
+            WHERE id = $1
+            AND deleted_at IS NULL
+            ",
+        )
+        .bind(contest_id)
+        .try_map(|row: PgRow| {
+            let max_participants: Option<i64> = row.try_get("max_participants")?;
+            let registration_deadline_second: Option<i64> =
+                row.try_get("registration_deadline_second")?;
+            Ok(VirtualContestRegistration {
+                max_participants,
+                registration_deadline_second,
+            })
+        })
+        .fetch_one(self)
+        .await?;
+        Ok(registration)
+    }
+
+    async fn update_registration(
+        &self,
+        contest_id: &str,
+        registration: &VirtualContestRegistration,
+    ) -> Result<()> {
+        sqlx::query(
+            r"
+            UPDATE internal_virtual_contests
+            SET max_participants = $1, registration_deadline_second = $2
+            WHERE id = $3
+            AND deleted_at IS NULL
+            ",
+        )
+        .bind(registration.max_participants)
+        .bind(registration.registration_deadline_second)
+        .bind(contest_id)
         .execute(self)
         .await?;
         Ok(())
     }
+
+    async fn get_contest_teams(&self, contest_id: &str) -> Result<Vec<VirtualContestTeam>> {
+        let rows = sqlx::query(
+            r"
+            SELECT a.id, a.name, a.internal_user_id, b.atcoder_user_id
+            FROM internal_virtual_contest_teams AS a
+            LEFT JOIN internal_virtual_contest_team_members AS b
+            ON a.id = b.team_id
+            WHERE a.internal_virtual_contest_id = $1
+            ORDER BY a.name ASC, b.atcoder_user_id ASC
+            ",
+        )
+        .bind(contest_id)
+        .try_map(|row: PgRow| {
+            let id: String = row.try_get("id")?;
+            let name: String = row.try_get("name")?;
+            let internal_user_id: String = row.try_get("internal_user_id")?;
+            let member: Option<String> = row.try_get("atcoder_user_id")?;
+            Ok((id, name, internal_user_id, member))
+        })
+        .fetch_all(self)
+        .await?;
+
+        let mut teams: Vec<VirtualContestTeam> = Vec::new();
+        for (id, name, internal_user_id, member) in rows {
+            match teams.last_mut() {
+                Some(team) if team.id == id => {}
+                _ => teams.push(VirtualContestTeam {
+                    id,
+                    name,
+                    internal_user_id,
+                    members: Vec::new(),
+                }),
+            }
+            if let (Some(team), Some(member)) = (teams.last_mut(), member) {
+                team.members.push(member);
+            }
+        }
+        Ok(teams)
+    }
+
+    async fn create_team(
+        &self,
+        contest_id: &str,
+        internal_user_id: &str,
+        invite_token: Option<&str>,
+        name: &str,
+        members: &[&str],
+        now: i64,
+    ) -> Result<String> {
+        if members.is_empty() || members.len() > MAX_TEAM_MEMBER_NUM {
+            bail!("The number of team members is invalid.");
+        }
+
+        let members = members
+            .iter()
+            .map(|member| member.to_lowercase())
+            .collect::<Vec<_>>();
+        let uuid = Uuid::new_v4().to_string();
+        let mut tx = self.begin().await?;
+        lock_joinable_contest(
+            &mut tx,
+            contest_id,
+            internal_user_id,
+            invite_token,
+            &members,
+            now,
+        )
+        .await?;
+
+        sqlx::query(
+            r"
+            INSERT INTO internal_virtual_contest_teams
+            (id, internal_virtual_contest_id, name, internal_user_id)
+            VALUES ($1, $2, $3, $4)
+            ",
+        )
+        .bind(&uuid)
+        .bind(contest_id)
+        .bind(name)
+        .bind(internal_user_id)
+        .execute(&mut tx)
+        .await
+        .context("The team name is already used in the contest.")?;
+
+        let team_ids = vec![uuid.as_str(); members.len()];
+        let contest_ids = vec![contest_id; members.len()];
+        sqlx::query(
+            r"
+            INSERT INTO internal_virtual_contest_team_members
+            (team_id, internal_virtual_contest_id, atcoder_user_id)
+            VALUES (
+                UNNEST($1::VARCHAR(255)[]),
+                UNNEST($2::VARCHAR(255)[]),
+                UNNEST($3::VARCHAR(255)[])
+            )
+            ",
+        )
+        .bind(team_ids)
+        .bind(contest_ids)
+        .bind(members)
+        .execute(&mut tx)
+        .await
+        .context("A member already belongs to another team in the contest.")?;
+
+        tx.commit().await?;
+        Ok(uuid)
+    }
+
+    async fn delete_team(&self, contest_id: &str, team_id: &str) -> Result<()> {
+        sqlx::query(
+            r"
+            DELETE FROM internal_virtual_contest_teams
+            WHERE internal_virtual_contest_id = $1
+            AND id = $2
+            ",
+        )
+        .bind(contest_id)
+        .bind(team_id)
+        .execute(self)
+        .await?;
+        Ok(())
+    }
+}
+
+/// Locks the contest and checks that the user can join it at `now` with the given team members,
+/// which are AtCoder IDs in lowercase. Holding the lock until the end of the transaction keeps concurrent
+/// registrations from exceeding the capacity, missing a ban or entering a user twice.
+async fn lock_joinable_contest(
+    tx: &mut Transaction<'_, Postgres>,
+    contest_id: &str,
+    internal_user_id: &str,
+    invite_token: Option<&str>,
+    members: &[String],
+    now: i64,
+) -> Result<()> {
+    // The check runs as a separate statement so that it sees the registrations committed while
+    // waiting for the lock.
+    sqlx::query("SELECT id FROM internal_virtual_contests WHERE id = $1 FOR UPDATE")
+        .bind(contest_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+    let joinable = sqlx::query(
+        r"
+        SELECT id FROM internal_virtual_contests
+        WHERE id = $1
+        AND deleted_at IS NULL
+        AND (
+            is_public IS TRUE
+            OR invite_token = $3
+            OR internal_user_id = $2
+            OR EXISTS (
+                SELECT 1 FROM internal_virtual_contest_organizers
+                WHERE internal_virtual_contest_id = $1
+                AND internal_user_id = $2
+            )
+        )
+        AND NOT EXISTS (
+            SELECT 1 FROM internal_virtual_contest_bans
+            WHERE internal_virtual_contest_id = $1
+            AND internal_user_id = $2
+        )
+        AND NOT EXISTS (
+            SELECT 1 FROM internal_virtual_contest_bans AS a
+            JOIN internal_users AS b
+            ON a.internal_user_id = b.internal_user_id
+            WHERE a.internal_virtual_contest_id = $1
+            AND LOWER(b.atcoder_user_id) = ANY($4)
+        )
+        AND NOT EXISTS (
+            SELECT 1 FROM internal_virtual_contest_participants AS a
+            JOIN internal_users AS b
+            ON a.internal_user_id = b.internal_user_id
+            WHERE a.internal_virtual_contest_id = $1
+            AND LOWER(b.atcoder_user_id) = ANY($4)
+        )
+        AND (
+            registration_deadline_second IS NULL
+            OR registration_deadline_second >= $5
+        )
+        AND (
+            max_participants IS NULL
+            OR max_participants > (
+                SELECT COUNT(*) FROM internal_virtual_contest_participants
+                WHERE internal_virtual_contest_id = $1
+            ) + (
+                SELECT COUNT(*) FROM internal_virtual_contest_teams
+                WHERE internal_virtual_contest_id = $1
+            )
+        )
+        ",
+    )
+    .bind(contest_id)
+    .bind(internal_user_id)
+    .bind(invite_token)
+    .bind(members)
+    .bind(now)
+    .fetch_optional(&mut *tx)
+    .await?;
+    if joinable.is_none() {
+        return Err(NotJoinableError.into());
+    }
+    Ok(())
 }
@@ -0,0 +1,328 @@
+use crate::internal::virtual_contest_manager::{get_mode, VirtualContestMode};
+use crate::PgPool;
+use anyhow::{anyhow, bail, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use sqlx::postgres::PgRow;
+use sqlx::Row;
+use std::result::Result as StdResult;
+use std::str::FromStr;
+use uuid::Uuid;
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum Recurrence {
+    Daily,
+    Weekly,
+}
+
+impl Recurrence {
+    pub fn interval_second(self) -> i64 {
+        match self {
+            Recurrence::Daily => 24 * 3600,
+            Recurrence::Weekly => 7 * 24 * 3600,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Recurrence::Daily => "daily",
+            Recurrence::Weekly => "weekly",
+        }
+    }
+}
+
+impl FromStr for Recurrence {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "daily" => Ok(Recurrence::Daily),
+            "weekly" => Ok(Recurrence::Weekly),
+            _ => Err(anyhow!("Invalid recurrence: {}", s)),
+        }
+    }
+}
+
+/// Settings of the contests in a series, and the rule to select their problems.
+///
+/// Each contest uses `problem_count` problems whose point is between `min_point` and
+/// `max_point`, chosen from the ones which have not been used in the series yet.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct VirtualContestSeriesConfig {
+    pub title: String,
+    pub memo: String,
+    pub mode: Option<VirtualContestMode>,
+    pub is_public: bool,
+    pub penalty_second: i64,
+    pub recurrence: Recurrence,
+    pub first_start_epoch_second: i64,
+    pub duration_second: i64,
+    pub problem_count: i64,
+    pub min_point: Option<f64>,
+    pub max_point: Option<f64>,
+}
+
+#[derive(Serialize, Debug, PartialEq, Clone)]
+pub struct VirtualContestSeries {
+    pub id: String,
+    pub owner_user_id: String, // column name is `internal_user_id`
+    #[serde(flatten)]
+    pub config: VirtualContestSeriesConfig,
+}
+
+fn virtual_contest_series_mapper(row: PgRow) -> StdResult<VirtualContestSeries, sqlx::Error> {
+    let id: String = row.try_get("id")?;
+    let owner_user_id: String = row.try_get("internal_user_id")?;
+    let title: String = row.try_get("title")?;
+    let memo: String = row.try_get("memo")?;
+    let mode = get_mode(&row)?;
+    let is_public: bool = row.try_get("is_public")?;
+    let penalty_second: i64 = row.try_get("penalty_second")?;
+    let recurrence: String = row.try_get("recurrence")?;
+    let recurrence = recurrence
+        .parse::<Recurrence>()
+        .map_err(|e| sqlx::Error::Decode(e.into()))?;
+    let first_start_epoch_second: i64 = row.try_get("first_start_epoch_second")?;
+    let duration_second: i64 = row.try_get("duration_second")?;
+    let problem_count: i64 = row.try_get("problem_count")?;
+    let min_point: Option<f64> = row.try_get("min_point")?;
+    let max_point: Option<f64> = row.try_get("max_point")?;
+    Ok(VirtualContestSeries {
+        id,
+        owner_user_id,
+        config: VirtualContestSeriesConfig {
+            title,
+            memo,
+            mode,
+            is_public,
+            penalty_second,
+            recurrence,
+            first_start_epoch_second,
+            duration_second,
+            problem_count,
+            min_point,
+            max_point,
+        },
+    })
+}
+
+#[async_trait]
+pub trait VirtualContestSeriesManager {
+    async fn create_series(
+        &self,
+        internal_user_id: &str,
+        config: &VirtualContestSeriesConfig,
+    ) -> Result<String>;
+    async fn delete_series(&self, series_id: &str, internal_user_id: &str) -> Result<()>;
+    async fn get_own_series(&self, internal_user_id: &str) -> Result<Vec<VirtualContestSeries>>;
+    async fn get_all_series(&self) -> Result<Vec<VirtualContestSeries>>;
+
+    /// Returns the start times of the contests which have been created in the series.
+    async fn get_series_start_seconds(&self, series_id: &str) -> Result<Vec<i64>>;
+    /// Returns the problems which match the selection rule of the series and have not been used
+    /// in the series yet.
+    async fn get_series_candidate_problems(
+        &self,
+        series: &VirtualContestSeries,
+    ) -> Result<Vec<String>>;
+    /// Creates a contest of the series starting at `start_epoch_second` with the given problems.
+    async fn create_series_contest(
+        &self,
+        series: &VirtualContestSeries,
+        start_epoch_second: i64,
+        problem_ids: &[String],
+    ) -> Result<String>;
+}
+
+#[async_trait]
+impl VirtualContestSeriesManager for PgPool {
+    async fn create_series(
+        &self,
+        internal_user_id: &str,
+        config: &VirtualContestSeriesConfig,
+    ) -> Result<String> {
+        let uuid = Uuid::new_v4().to_string();
+        sqlx::query(
+            r"
+            INSERT INTO internal_virtual_contest_series
+            (
+                id,
+                title,
+                memo,
+                internal_user_id,
+                mode,
+                is_public,
+                penalty_second,
+                recurrence,
+                first_start_epoch_second,
+                duration_second,
+                problem_count,
+                min_point,
+                max_point
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+            ",
+        )
+        .bind(&uuid)
+        .bind(&config.title)
+        .bind(&config.memo)
+        .bind(internal_user_id)
+        .bind(config.mode.map(VirtualContestMode::as_str))
+        .bind(config.is_public)
+        .bind(config.penalty_second)
+        .bind(config.recurrence.as_str())
+        .bind(config.first_start_epoch_second)
+        .bind(config.duration_second)
+        .bind(config.problem_count)
+        .bind(config.min_point)
+        .bind(config.max_point)
+        .execute(self)
+        .await?;
+        Ok(uuid)
+    }
+
+    async fn delete_series(&self, series_id: &str, internal_user_id: &str) -> Result<()> {
+        let result = sqlx::query(
+            r"
+            DELETE FROM internal_virtual_contest_series
+            WHERE id = $1
+            AND internal_user_id = $2
+            ",
+        )
+        .bind(series_id)
+        .bind(internal_user_id)
+        .execute(self)
+        .await?;
+        if result.rows_affected() == 0 {
+            bail!("The target series does not exist or is not owned by the user.");
+        }
+        Ok(())
+    }
+
+    async fn get_own_series(&self, internal_user_id: &str) -> Result<Vec<VirtualContestSeries>> {
+        let series = sqlx::query(
+            r"
+            SELECT * FROM internal_virtual_contest_series
+            WHERE internal_user_id = $1
+            ORDER BY first_start_epoch_second ASC, id ASC
+            ",
+        )
+        .bind(internal_user_id)
+        .try_map(virtual_contest_series_mapper)
+        .fetch_all(self)
+        .await?;
+        Ok(series)
+    }
+
+    async fn get_all_series(&self) -> Result<Vec<VirtualContestSeries>> {
+        let series = sqlx::query(
+            r"
+            SELECT * FROM internal_virtual_contest_series
+            ORDER BY first_start_epoch_second ASC, id ASC
+            ",
+        )
+        .try_map(virtual_contest_series_mapper)
+        .fetch_all(self)
+        .await?;
+        Ok(series)
+    }
+
+    async fn get_series_start_seconds(&self, series_id: &str) -> Result<Vec<i64>> {
+        let start_seconds = sqlx::query(
+            r"
+            SELECT start_epoch_second FROM internal_virtual_contests
+            WHERE series_id = $1
+            ORDER BY start_epoch_second ASC
+            ",
+        )
+        .bind(series_id)
+        .try_map(|row: PgRow| row.try_get::<i64, _>("start_epoch_second"))
+        .fetch_all(self)
+        .await?;
+        Ok(start_seconds)
+    }
+
+    async fn get_series_candidate_problems(
+        &self,
+        series: &VirtualContestSeries,
+    ) -> Result<Vec<String>> {
+        let problem_ids = sqlx::query(
+            r"
+            SELECT a.id
+            FROM problems AS a
+            LEFT JOIN points AS b
+            ON a.id = b.problem_id
+            WHERE ($1::DOUBLE PRECISION IS NULL OR b.point >= $1)
+            AND ($2::DOUBLE PRECISION IS NULL OR b.point <= $2)
+            AND a.id NOT IN (
+                SELECT c.problem_id
+                FROM internal_virtual_contest_items AS c
+                JOIN internal_virtual_contests AS d
+                ON c.internal_virtual_contest_id = d.id
+                WHERE d.series_id = $3
+            )
+            ORDER BY a.id ASC
+            ",
+        )
+        .bind(series.config.min_point)
+        .bind(series.config.max_point)
+        .bind(&series.id)
+        .try_map(|row: PgRow| row.try_get::<String, _>("id"))
+        .fetch_all(self)
+        .await?;
+        Ok(problem_ids)
+    }
+
+    async fn create_series_contest(
+        &self,
+        series: &VirtualContestSeries,
+        start_epoch_second: i64,
+        problem_ids: &[String],
+    ) -> Result<String> {
+        let uuid = Uuid::new_v4().to_string();
+        let config = &series.config;
+        let mut tx = self.begin().await?;
+
+        sqlx::query(
+            r"
+            INSERT INTO internal_virtual_contests
+            (id, title, memo, internal_user_id, start_epoch_second, duration_second, mode, is_public, penalty_second, series_id)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            ",
+        )
+        .bind(&uuid)
+        .bind(&config.title)
+        .bind(&config.memo)
+        .bind(&series.owner_user_id)
+        .bind(start_epoch_second)
+        .bind(config.duration_second)
+        .bind(config.mode.map(VirtualContestMode::as_str))
+        .bind(config.is_public)
+        .bind(config.penalty_second)
+        .bind(&series.id)
+        .execute(&mut tx)
+        .await?;
+
+        let contest_ids = vec![uuid.as_str(); problem_ids.len()];
+        let orders = (0..problem_ids.len() as i64).collect::<Vec<_>>();
+        sqlx::query(
+            r"
+            INSERT INTO internal_virtual_contest_items
+            (internal_virtual_contest_id, problem_id, user_defined_order)
+            VALUES (
+                UNNEST($1::VARCHAR(255)[]),
+                UNNEST($2::VARCHAR(255)[]),
+                UNNEST($3::BIGINT[])
+            )
+            ",
+        )
+        .bind(contest_ids)
+        .bind(problem_ids)
+        .bind(orders)
+        .execute(&mut tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(uuid)
+    }
+}
@@ -15,6 +15,35 @@ pub trait LanguageCountClient {
         current_counts: &[UserLanguageCount],
     ) -> Result<()>;
     async fn load_language_count(&self) -> Result<Vec<UserLanguageCount>>;
+    async fn load_language_count_in_range(
+        &self,
+        simplified_language: &str,
+        offset: i64,
+        limit: i64,
+    ) -> Result<Vec<UserLanguageCount>>;
+    async fn get_users_language_count(&self, user_id: &str) -> Result<Vec<UserLanguageCount>>;
+    async fn get_language_count_rank(
+        &self,
+        simplified_language: &str,
+        problem_count: i32,
+    ) -> Result<i64>;
+    async fn get_language_count_position(
+        &self,
+        user_id: &str,
+        simplified_language: &str,
+        problem_count: i32,
+    ) -> Result<i64>;
+}
+
+fn user_language_count_mapper(row: PgRow) -> Result<UserLanguageCount, sqlx::Error> {
+    let user_id: String = row.try_get("user_id")?;
+    let simplified_language: String = row.try_get("simplified_language")?;
+    let problem_count: i32 = row.try_get("problem_count")?;
+    Ok(UserLanguageCount {
+        user_id,
+        simplified_language,
+        problem_count,
+    })
 }
 
 #[async_trait]
@@ -106,20 +135,104 @@ impl LanguageCountClient for PgPool {
             ORDER BY user_id
             ",
         )
-        .try_map(|row: PgRow| {
-            let user_id: String = row.try_get("user_id")?;
-            let simplified_language: String = row.try_get("simplified_language")?;
-            let problem_count: i32 = row.try_get("problem_count")?;
-            Ok(UserLanguageCount {
+        .try_map(user_language_count_mapper)
+        .fetch_all(self)
+        .await?;
+        Ok(count)
+    }
+
+    async fn load_language_count_in_range(
+        &self,
+        simplified_language: &str,
+        offset: i64,
+        limit: i64,
+    ) -> Result<Vec<UserLanguageCount>> {
+        let count = sqlx::query(
+            r"
+            SELECT
                 user_id,
                 simplified_language,
-                problem_count,
-            })
-        })
+                problem_count
+            FROM language_count
+            WHERE simplified_language = $1
+            ORDER BY problem_count DESC, user_id ASC
+            OFFSET $2 LIMIT $3
+            ",
+        )
+        .bind(simplified_language)
+        .bind(offset)
+        .bind(limit)
+        .try_map(user_language_count_mapper)
+        .fetch_all(self)
+        .await?;
+        Ok(count)
+    }
+
+    async fn get_users_language_count(&self, user_id: &str) -> Result<Vec<UserLanguageCount>> {
+        let count = sqlx::query(
+            r"
+            SELECT
+                user_id,
+                simplified_language,
+                problem_count
+            FROM language_count
+            WHERE user_id = $1
+            ORDER BY simplified_language
+            ",
+        )
+        .bind(user_id)
+        .try_map(user_language_count_mapper)
         .fetch_all(self)
         .await?;
         Ok(count)
     }
+
+    async fn get_language_count_rank(
+        &self,
+        simplified_language: &str,
+        problem_count: i32,
+    ) -> Result<i64> {
+        let rank = sqlx::query(
+            r"
+            SELECT COUNT(*) AS rank
+            FROM language_count
+            WHERE simplified_language = $1
+            AND problem_count > $2
+            ",
+        )
+        .bind(simplified_language)
+        .bind(problem_count)
+        .try_map(|row: PgRow| row.try_get::<i64, _>("rank"))
+        .fetch_one(self)
+        .await?;
+        Ok(rank)
+    }
+
+    async fn get_language_count_position(
+        &self,
+        user_id: &str,
+        simplified_language: &str,
+        problem_count: i32,
+    ) -> Result<i64> {
+        let position = sqlx::query(
+            r"
+            SELECT COUNT(*) AS position
+            FROM language_count
+            WHERE simplified_language = $1
+            AND (
+                problem_count > $2
+                OR (problem_count = $2 AND user_id < $3)
+            )
+            ",
+        )
+        .bind(simplified_language)
+        .bind(problem_count)
+        .bind(user_id)
+        .try_map(|row: PgRow| row.try_get::<i64, _>("position"))
+        .fetch_one(self)
+        .await?;
+        Ok(position)
+    }
 }
 
 fn simplify_language(lang: &str) -> String {
@@ -28,3 +28,13 @@ pub async fn initialize_pool<S: AsRef<str>>(database_url: S) -> Result<PgPool> {
         .await?;
     Ok(pool)
 }
+
+/// Returns whether the error was caused by a violation of a unique constraint.
+pub fn is_unique_violation(error: &anyhow::Error) -> bool {
+    error
+        .chain()
+        .any(|cause| match cause.downcast_ref::<sqlx::Error>() {
+            Some(sqlx::Error::Database(e)) => e.code().as_deref() == Some("23505"),
+            _ => false,
+        })
+}
@@ -67,6 +67,20 @@ impl FromRow<'_, PgRow> for Submission {
     }
 }
 
+#[derive(Debug, Default, Eq, PartialEq)]
+pub struct SubmissionSummary {
+    /// The largest `update_id` of the submissions, which increases when a submission is inserted
+    /// or rejudged.
+    pub max_update_id: i64,
+}
+
+impl FromRow<'_, PgRow> for SubmissionSummary {
+    fn from_row(row: &PgRow) -> sqlx::Result<Self> {
+        let max_update_id: i64 = row.try_get("max_update_id")?;
+        Ok(SubmissionSummary { max_update_id })
+    }
+}
+
 #[derive(Debug, Eq, PartialEq, Serialize)]
 pub struct UserLanguageCount {
     pub user_id: String,
@@ -101,3 +115,24 @@ pub struct UserStreak {
     pub user_id: String,
     pub streak: i64,
 }
+
+#[derive(PartialEq, Debug, Serialize)]
+pub struct UserCurrentStreak {
+    pub user_id: String,
+    pub streak: i64,
+    pub last_ac_date: String,
+}
+
+#[derive(PartialEq, Debug, Serialize)]
+pub struct DailyCount {
+    pub date: String,
+    pub count: i64,
+}
+
+#[derive(PartialEq, Debug, Serialize)]
+pub struct StreakStatistics {
+    pub max_streak: i64,
+    pub current_streak: i64,
+    pub last_ac_date: Option<String>,
+    pub daily_accepted_count: Vec<DailyCount>,
+}
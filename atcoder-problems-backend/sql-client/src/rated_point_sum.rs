@@ -1,4 +1,4 @@
-use crate::models::{ContestProblem, Submission};
+use crate::models::{ContestProblem, Submission, UserSum};
 use crate::{PgPool, FIRST_AGC_EPOCH_SECOND, MAX_INSERT_ROWS, UNRATED_STATE};
 use anyhow::Result;
 use async_trait::async_trait;
@@ -10,8 +10,10 @@ use std::collections::{BTreeMap, BTreeSet};
 #[async_trait]
 pub trait RatedPointSumClient {
     async fn update_rated_point_sum(&self, ac_submissions: &[Submission]) -> Result<()>;
+    async fn load_rated_point_sum_in_range(&self, offset: i64, limit: i64) -> Result<Vec<UserSum>>;
     async fn get_users_rated_point_sum(&self, user_id: &str) -> Option<f64>;
     async fn get_rated_point_sum_rank(&self, point: f64) -> Result<i64>;
+    async fn get_rated_point_sum_position(&self, user_id: &str, point: f64) -> Result<i64>;
 }
 
 #[async_trait]
@@ -88,6 +90,26 @@ impl RatedPointSumClient for PgPool {
         Ok(())
     }
 
+    async fn load_rated_point_sum_in_range(&self, offset: i64, limit: i64) -> Result<Vec<UserSum>> {
+        let sums = sqlx::query(
+            r"
+            SELECT user_id, point_sum FROM rated_point_sum
+            ORDER BY point_sum DESC, user_id ASC
+            OFFSET $1 LIMIT $2
+            ",
+        )
+        .bind(offset)
+        .bind(limit)
+        .try_map(|row: PgRow| {
+            let user_id: String = row.try_get("user_id")?;
+            let point_sum: f64 = row.try_get("point_sum")?;
+            Ok(UserSum { user_id, point_sum })
+        })
+        .fetch_all(self)
+        .await?;
+        Ok(sums)
+    }
+
     async fn get_users_rated_point_sum(&self, user_id: &str) -> Option<f64> {
         let sum = sqlx::query("SELECT point_sum FROM rated_point_sum WHERE user_id = $1")
             .bind(user_id)
@@ -106,4 +128,21 @@ impl RatedPointSumClient for PgPool {
             .await?;
         Ok(rank)
     }
+
+    async fn get_rated_point_sum_position(&self, user_id: &str, point: f64) -> Result<i64> {
+        let position = sqlx::query(
+            r"
+            SELECT COUNT(*) AS position
+            FROM rated_point_sum
+            WHERE point_sum > $1
+            OR (point_sum = $1 AND user_id < $2)
+            ",
+        )
+        .bind(point)
+        .bind(user_id)
+        .try_map(|row: PgRow| row.try_get::<i64, _>("position"))
+        .fetch_one(self)
+        .await?;
+        Ok(position)
+    }
 }
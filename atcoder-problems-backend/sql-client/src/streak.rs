@@ -1,10 +1,11 @@
-use crate::models::Submission;
+use crate::models::{DailyCount, StreakStatistics, Submission, UserCurrentStreak, UserStreak};
 use crate::{PgPool, MAX_INSERT_ROWS};
 use anyhow::Result;
 use async_trait::async_trait;
+use sqlx::postgres::PgRow;
+use sqlx::Row;
 
-use chrono::Duration;
-use chrono::{DateTime, Datelike, FixedOffset, TimeZone, Utc};
+use chrono::{DateTime, FixedOffset, NaiveDate, TimeZone, Utc};
 use std::cmp;
 use std::collections::BTreeMap;
 
@@ -38,29 +39,48 @@ impl StreakUpdater for PgPool {
             },
         );
 
-        let user_max_streak = first_ac_map
+        let user_streaks = first_ac_map
             .into_iter()
             .map(|(user_id, m)| {
-                let max_streak = get_max_streak(m.into_iter().map(|(_, utc)| utc).collect());
-                (user_id, max_streak)
+                let streaks = get_streaks(m.into_iter().map(|(_, utc)| utc).collect(), &jst());
+                (user_id, streaks)
             })
             .collect::<Vec<_>>();
 
-        for chunk in user_max_streak.chunks(MAX_INSERT_ROWS) {
-            let (user_ids, max_streaks): (Vec<&str>, Vec<i64>) = chunk.iter().copied().unzip();
+        for chunk in user_streaks.chunks(MAX_INSERT_ROWS) {
+            let user_ids = chunk
+                .iter()
+                .map(|(user_id, _)| *user_id)
+                .collect::<Vec<_>>();
+            let max_streaks = chunk.iter().map(|(_, s)| s.max_streak).collect::<Vec<_>>();
+            let current_streaks = chunk
+                .iter()
+                .map(|(_, s)| s.current_streak)
+                .collect::<Vec<_>>();
+            let last_ac_dates = chunk
+                .iter()
+                .map(|(_, s)| s.last_ac_date.as_str())
+                .collect::<Vec<_>>();
             sqlx::query(
                 r"
-                INSERT INTO max_streaks (user_id, streak)
+                INSERT INTO max_streaks (user_id, streak, current_streak, last_ac_date)
                 VALUES (
                     UNNEST($1::VARCHAR(255)[]),
-                    UNNEST($2::BIGINT[])
+                    UNNEST($2::BIGINT[]),
+                    UNNEST($3::BIGINT[]),
+                    UNNEST($4::TEXT[])::DATE
                 )
                 ON CONFLICT (user_id)
-                DO UPDATE SET streak = EXCLUDED.streak
+                DO UPDATE SET
+                    streak = EXCLUDED.streak,
+                    current_streak = EXCLUDED.current_streak,
+                    last_ac_date = EXCLUDED.last_ac_date
                 ",
             )
             .bind(user_ids)
             .bind(max_streaks)
+            .bind(current_streaks)
+            .bind(last_ac_dates)
             .execute(self)
             .await?;
         }
@@ -69,53 +89,325 @@ impl StreakUpdater for PgPool {
     }
 }
 
-fn get_max_streak<Tz: TimeZone>(mut v: Vec<DateTime<Tz>>) -> i64 {
-    v.sort();
-    let (_, max_streak) = (1..v.len()).fold((1, 1), |(current_streak, max_streak), i| {
-        if v[i - 1].is_same_day_in_jst(&v[i]) {
-            (current_streak, max_streak)
-        } else if (v[i - 1].clone() + Duration::days(1)).is_same_day_in_jst(&v[i]) {
-            (current_streak + 1, cmp::max(max_streak, current_streak + 1))
+#[async_trait]
+pub trait StreakClient {
+    async fn load_streak_count_in_range(&self, offset: i64, limit: i64) -> Result<Vec<UserStreak>>;
+    async fn get_users_streak_count(&self, user_id: &str) -> Option<i64>;
+    async fn get_streak_count_rank(&self, streak: i64) -> Result<i64>;
+    async fn get_streak_count_position(&self, user_id: &str, streak: i64) -> Result<i64>;
+
+    async fn load_current_streak_count_in_range(
+        &self,
+        since: &str,
+        offset: i64,
+        limit: i64,
+    ) -> Result<Vec<UserCurrentStreak>>;
+    async fn get_users_current_streak(&self, user_id: &str) -> Option<UserCurrentStreak>;
+    async fn get_current_streak_rank(&self, since: &str, streak: i64) -> Result<i64>;
+    async fn get_current_streak_position(
+        &self,
+        since: &str,
+        user_id: &str,
+        streak: i64,
+    ) -> Result<i64>;
+}
+
+fn user_current_streak_mapper(row: PgRow) -> Result<UserCurrentStreak, sqlx::Error> {
+    let user_id: String = row.try_get("user_id")?;
+    let streak: i64 = row.try_get("current_streak")?;
+    let last_ac_date: String = row.try_get("last_ac_date")?;
+    Ok(UserCurrentStreak {
+        user_id,
+        streak,
+        last_ac_date,
+    })
+}
+
+#[async_trait]
+impl StreakClient for PgPool {
+    async fn load_streak_count_in_range(&self, offset: i64, limit: i64) -> Result<Vec<UserStreak>> {
+        let streaks = sqlx::query(
+            r"
+            SELECT user_id, streak FROM max_streaks
+            ORDER BY streak DESC, user_id ASC
+            OFFSET $1 LIMIT $2
+            ",
+        )
+        .bind(offset)
+        .bind(limit)
+        .try_map(|row: PgRow| {
+            let user_id: String = row.try_get("user_id")?;
+            let streak: i64 = row.try_get("streak")?;
+            Ok(UserStreak { user_id, streak })
+        })
+        .fetch_all(self)
+        .await?;
+        Ok(streaks)
+    }
+
+    async fn get_users_streak_count(&self, user_id: &str) -> Option<i64> {
+        let streak = sqlx::query("SELECT streak FROM max_streaks WHERE user_id = $1")
+            .bind(user_id)
+            .try_map(|row: PgRow| row.try_get::<i64, _>("streak"))
+            .fetch_one(self)
+            .await
+            .ok()?;
+        Some(streak)
+    }
+
+    async fn get_streak_count_rank(&self, streak: i64) -> Result<i64> {
+        let rank = sqlx::query("SELECT COUNT(*) AS rank FROM max_streaks WHERE streak > $1")
+            .bind(streak)
+            .try_map(|row: PgRow| row.try_get::<i64, _>("rank"))
+            .fetch_one(self)
+            .await?;
+        Ok(rank)
+    }
+
+    async fn get_streak_count_position(&self, user_id: &str, streak: i64) -> Result<i64> {
+        let position = sqlx::query(
+            r"
+            SELECT COUNT(*) AS position
+            FROM max_streaks
+            WHERE streak > $1
+            OR (streak = $1 AND user_id < $2)
+            ",
+        )
+        .bind(streak)
+        .bind(user_id)
+        .try_map(|row: PgRow| row.try_get::<i64, _>("position"))
+        .fetch_one(self)
+        .await?;
+        Ok(position)
+    }
+
+    async fn load_current_streak_count_in_range(
+        &self,
+        since: &str,
+        offset: i64,
+        limit: i64,
+    ) -> Result<Vec<UserCurrentStreak>> {
+        let streaks = sqlx::query(
+            r"
+            SELECT user_id, current_streak, last_ac_date::TEXT AS last_ac_date
+            FROM max_streaks
+            WHERE last_ac_date >= $1::DATE
+            ORDER BY current_streak DESC, user_id ASC
+            OFFSET $2 LIMIT $3
+            ",
+        )
+        .bind(since)
+        .bind(offset)
+        .bind(limit)
+        .try_map(user_current_streak_mapper)
+        .fetch_all(self)
+        .await?;
+        Ok(streaks)
+    }
+
+    async fn get_users_current_streak(&self, user_id: &str) -> Option<UserCurrentStreak> {
+        sqlx::query(
+            r"
+            SELECT user_id, current_streak, last_ac_date::TEXT AS last_ac_date
+            FROM max_streaks
+            WHERE user_id = $1 AND last_ac_date IS NOT NULL
+            ",
+        )
+        .bind(user_id)
+        .try_map(user_current_streak_mapper)
+        .fetch_one(self)
+        .await
+        .ok()
+    }
+
+    async fn get_current_streak_rank(&self, since: &str, streak: i64) -> Result<i64> {
+        let rank = sqlx::query(
+            r"
+            SELECT COUNT(*) AS rank
+            FROM max_streaks
+            WHERE last_ac_date >= $1::DATE
+            AND current_streak > $2
+            ",
+        )
+        .bind(since)
+        .bind(streak)
+        .try_map(|row: PgRow| row.try_get::<i64, _>("rank"))
+        .fetch_one(self)
+        .await?;
+        Ok(rank)
+    }
+
+    async fn get_current_streak_position(
+        &self,
+        since: &str,
+        user_id: &str,
+        streak: i64,
+    ) -> Result<i64> {
+        let position = sqlx::query(
+            r"
+            SELECT COUNT(*) AS position
+            FROM max_streaks
+            WHERE last_ac_date >= $1::DATE
+            AND (current_streak > $2 OR (current_streak = $2 AND user_id < $3))
+            ",
+        )
+        .bind(since)
+        .bind(streak)
+        .bind(user_id)
+        .try_map(|row: PgRow| row.try_get::<i64, _>("position"))
+        .fetch_one(self)
+        .await?;
+        Ok(position)
+    }
+}
+
+/// Offset of JST from UTC in minutes, which decides the day boundaries of the public rankings.
+pub const JST_OFFSET_MINUTE: i32 = 9 * 60;
+
+pub fn jst() -> FixedOffset {
+    FixedOffset::east(JST_OFFSET_MINUTE * 60)
+}
+
+/// Returns the timezone `offset_minute` minutes east of UTC, or `None` if it is out of range.
+pub fn timezone_from_offset_minute(offset_minute: i32) -> Option<FixedOffset> {
+    offset_minute
+        .checked_mul(60)
+        .and_then(FixedOffset::east_opt)
+}
+
+/// Returns the earliest last AC date (`YYYY-MM-DD` in `tz`) of streaks which are still ongoing at
+/// `now`, i.e. the day before `now`.
+pub fn ongoing_streak_since<Tz: TimeZone>(now: &DateTime<Tz>, tz: &FixedOffset) -> String {
+    format_date(now.local_date(tz).pred())
+}
+
+impl UserCurrentStreak {
+    /// Returns the current streak at `now`, which is 0 if the streak has already ended.
+    pub fn streak_at<Tz: TimeZone>(&self, now: &DateTime<Tz>, tz: &FixedOffset) -> i64 {
+        if self.last_ac_date >= ongoing_streak_since(now, tz) {
+            self.streak
         } else {
-            (1, max_streak)
+            0
         }
-    });
-    max_streak
+    }
+}
+
+/// Computes the streaks and the daily counts of newly accepted problems of a user, deciding
+/// the day boundaries in `tz`.
+pub fn compute_streak_statistics<Tz: TimeZone>(
+    ac_submissions: &[Submission],
+    tz: &FixedOffset,
+    now: &DateTime<Tz>,
+) -> StreakStatistics {
+    let mut first_ac_map = BTreeMap::new();
+    for s in ac_submissions {
+        let epoch_second = first_ac_map
+            .entry(s.problem_id.as_str())
+            .or_insert(s.epoch_second);
+        *epoch_second = cmp::min(*epoch_second, s.epoch_second);
+    }
+    let first_ac_times = first_ac_map
+        .values()
+        .map(|&epoch_second| Utc.timestamp(epoch_second, 0))
+        .collect::<Vec<_>>();
+
+    let mut daily_count = BTreeMap::new();
+    for time in first_ac_times.iter() {
+        *daily_count.entry(time.local_date(tz)).or_insert(0) += 1;
+    }
+    let daily_accepted_count = daily_count
+        .into_iter()
+        .map(|(date, count)| DailyCount {
+            date: format_date(date),
+            count,
+        })
+        .collect::<Vec<_>>();
+
+    let streaks = get_streaks(first_ac_times, tz);
+    let last_ac_date = daily_accepted_count.last().map(|c| c.date.clone());
+    let current_streak = if last_ac_date >= Some(ongoing_streak_since(now, tz)) {
+        streaks.current_streak
+    } else {
+        0
+    };
+    StreakStatistics {
+        max_streak: streaks.max_streak,
+        current_streak,
+        last_ac_date,
+        daily_accepted_count,
+    }
+}
+
+struct Streaks {
+    max_streak: i64,
+    current_streak: i64,
+    last_ac_date: String,
 }
 
-trait AsJst {
-    fn as_jst(&self) -> DateTime<FixedOffset>;
-    fn is_same_day_in_jst<T: TimeZone>(&self, rhs: &DateTime<T>) -> bool {
-        let d1 = self.as_jst();
-        let d2 = rhs.as_jst();
-        d1.day() == d2.day() && d1.month() == d2.month() && d1.year() == d2.year()
+fn get_streaks<Tz: TimeZone>(v: Vec<DateTime<Tz>>, tz: &FixedOffset) -> Streaks {
+    let mut dates = v.iter().map(|t| t.local_date(tz)).collect::<Vec<_>>();
+    dates.sort();
+    dates.dedup();
+    let last_date = match dates.last() {
+        Some(&date) => date,
+        None => {
+            return Streaks {
+                max_streak: 0,
+                current_streak: 0,
+                last_ac_date: String::new(),
+            }
+        }
+    };
+    let (current_streak, max_streak) =
+        (1..dates.len()).fold((1, 1), |(current_streak, max_streak), i| {
+            if dates[i - 1].succ() == dates[i] {
+                (current_streak + 1, cmp::max(max_streak, current_streak + 1))
+            } else {
+                (1, max_streak)
+            }
+        });
+    Streaks {
+        max_streak,
+        current_streak,
+        last_ac_date: format_date(last_date),
     }
 }
 
-impl<Tz> AsJst for DateTime<Tz>
+fn format_date(date: NaiveDate) -> String {
+    date.format("%Y-%m-%d").to_string()
+}
+
+trait LocalDate {
+    fn local_date(&self, tz: &FixedOffset) -> NaiveDate;
+}
+
+impl<Tz> LocalDate for DateTime<Tz>
 where
     Tz: TimeZone,
 {
-    fn as_jst(&self) -> DateTime<FixedOffset> {
-        self.with_timezone(&FixedOffset::east(9 * 3600))
+    fn local_date(&self, tz: &FixedOffset) -> NaiveDate {
+        self.with_timezone(tz).naive_local().date()
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use chrono::Datelike;
+    use chrono::{Datelike, Duration};
 
     #[test]
     fn test_to_jst() {
         let dt_10_04 = Utc.timestamp(1570114800, 0); //2019-10-04T00:00:00+09:00
-        assert_eq!(dt_10_04.as_jst().day(), 04);
+        assert_eq!(dt_10_04.local_date(&jst()).day(), 04);
 
         let dt_10_03 = Utc.timestamp(1570114799, 0); //2019-10-03T23:59:59+09:00
-        assert_eq!(dt_10_03.as_jst().day(), 03);
+        assert_eq!(dt_10_03.local_date(&jst()).day(), 03);
 
         let tomorrow = dt_10_03 + Duration::days(1);
-        assert!((tomorrow).is_same_day_in_jst(&dt_10_04));
+        assert_eq!(tomorrow.local_date(&jst()), dt_10_04.local_date(&jst()));
+
+        let utc = FixedOffset::east(0);
+        assert_eq!(dt_10_04.local_date(&utc).day(), 3);
     }
 
     #[test]
@@ -137,7 +429,106 @@ mod tests {
         .into_iter()
         .map(|s| s.parse::<DateTime<Utc>>().unwrap())
         .collect::<Vec<_>>();
-        let streak = get_max_streak(v);
-        assert_eq!(streak, 4);
+        let streaks = get_streaks(v, &jst());
+        assert_eq!(streaks.max_streak, 4);
+        assert_eq!(streaks.current_streak, 4);
+        assert_eq!(streaks.last_ac_date, "2014-12-04");
+    }
+
+    #[test]
+    fn test_get_current_streak() {
+        let v = vec![
+            "2014-11-27T12:00:00+09:00",
+            "2014-11-28T12:00:00+09:00",
+            "2014-11-29T12:00:00+09:00",
+            "2014-12-01T23:59:59+09:00",
+            "2014-12-02T00:00:00+09:00",
+        ]
+        .into_iter()
+        .map(|s| s.parse::<DateTime<Utc>>().unwrap())
+        .collect::<Vec<_>>();
+        let streaks = get_streaks(v, &jst());
+        assert_eq!(streaks.max_streak, 3);
+        assert_eq!(streaks.current_streak, 2);
+        assert_eq!(streaks.last_ac_date, "2014-12-02");
+
+        let current = UserCurrentStreak {
+            user_id: "user".to_owned(),
+            streak: streaks.current_streak,
+            last_ac_date: streaks.last_ac_date,
+        };
+        let now = "2014-12-03T23:59:59+09:00"
+            .parse::<DateTime<Utc>>()
+            .unwrap();
+        assert_eq!(current.streak_at(&now, &jst()), 2);
+        let now = "2014-12-04T00:00:00+09:00"
+            .parse::<DateTime<Utc>>()
+            .unwrap();
+        assert_eq!(current.streak_at(&now, &jst()), 0);
+    }
+
+    #[test]
+    fn test_compute_streak_statistics() {
+        let submissions = [
+            ("p1", "2014-11-28T23:30:00+09:00"),
+            ("p2", "2014-11-29T00:30:00+09:00"),
+            ("p1", "2014-11-29T01:00:00+09:00"),
+            ("p3", "2014-11-30T08:30:00+09:00"),
+        ]
+        .iter()
+        .map(|&(problem_id, time)| Submission {
+            problem_id: problem_id.to_owned(),
+            epoch_second: time.parse::<DateTime<Utc>>().unwrap().timestamp(),
+            ..Default::default()
+        })
+        .collect::<Vec<_>>();
+        let now = "2014-12-01T12:00:00+09:00"
+            .parse::<DateTime<Utc>>()
+            .unwrap();
+
+        let statistics = compute_streak_statistics(&submissions, &jst(), &now);
+        assert_eq!(statistics.max_streak, 3);
+        assert_eq!(statistics.current_streak, 3);
+        assert_eq!(statistics.last_ac_date, Some("2014-11-30".to_owned()));
+        assert_eq!(
+            statistics.daily_accepted_count,
+            vec![
+                DailyCount {
+                    date: "2014-11-28".to_owned(),
+                    count: 1
+                },
+                DailyCount {
+                    date: "2014-11-29".to_owned(),
+                    count: 1
+                },
+                DailyCount {
+                    date: "2014-11-30".to_owned(),
+                    count: 1
+                },
+            ]
+        );
+
+        // In UTC, the first two problems are solved on the same day.
+        let utc = timezone_from_offset_minute(0).unwrap();
+        let statistics = compute_streak_statistics(&submissions, &utc, &now);
+        assert_eq!(statistics.max_streak, 2);
+        assert_eq!(statistics.current_streak, 0);
+        assert_eq!(statistics.last_ac_date, Some("2014-11-29".to_owned()));
+        assert_eq!(statistics.daily_accepted_count.len(), 2);
+        assert_eq!(statistics.daily_accepted_count[0].count, 2);
+
+        let statistics = compute_streak_statistics(&[], &jst(), &now);
+        assert_eq!(statistics.max_streak, 0);
+        assert_eq!(statistics.last_ac_date, None);
+    }
+
+    #[test]
+    fn test_timezone_from_offset_minute() {
+        assert_eq!(timezone_from_offset_minute(JST_OFFSET_MINUTE), Some(jst()));
+        assert_eq!(
+            timezone_from_offset_minute(-330),
+            Some(FixedOffset::west(330 * 60))
+        );
+        assert_eq!(timezone_from_offset_minute(24 * 60), None);
     }
 }
@@ -1,7 +1,8 @@
-use crate::models::Submission;
+use crate::models::{Submission, SubmissionSummary};
 use crate::PgPool;
 use anyhow::Result;
 use async_trait::async_trait;
+use serde::Deserialize;
 use sqlx::postgres::PgRow;
 use sqlx::Row;
 use std::collections::BTreeMap;
@@ -12,6 +13,12 @@ pub enum SubmissionRequest<'a> {
     UserAll {
         user_id: &'a str,
     },
+    UserPage {
+        user_id: &'a str,
+        from_second: i64,
+        from_id: i64,
+        count: i64,
+    },
     UsersAccepted {
         user_ids: &'a [&'a str],
     },
@@ -33,17 +40,61 @@ pub enum SubmissionRequest<'a> {
         ids: &'a [i64],
     },
     UsersProblemsTime {
+        /// Matched case-insensitively.
         user_ids: &'a [&'a str],
         problem_ids: &'a [&'a str],
         from_second: i64,
         to_second: i64,
     },
+    Filtered {
+        filter: SubmissionFilter<'a>,
+    },
+}
+
+/// Conditions of [SubmissionRequest::Filtered]. `None` means "no restriction".
+#[derive(Default)]
+pub struct SubmissionFilter<'a> {
+    /// Matched case-insensitively.
+    pub user_ids: Option<&'a [&'a str]>,
+    pub problem_ids: Option<&'a [&'a str]>,
+    pub contest_ids: Option<&'a [&'a str]>,
+    pub results: Option<&'a [&'a str]>,
+    /// Simplified language name, e.g. `C++` matches `C++ (GCC 9.2.1)`.
+    pub language: Option<&'a str>,
+    pub from_second: Option<i64>,
+    pub to_second: Option<i64>,
+    /// Ascending if not specified.
+    pub order: Option<SubmissionOrder>,
+    pub count: i64,
+}
+
+/// Order of submissions by `(epoch_second, id)`.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SubmissionOrder {
+    Asc,
+    Desc,
+}
+
+impl SubmissionOrder {
+    fn as_sql(&self) -> &'static str {
+        match self {
+            SubmissionOrder::Asc => "ASC",
+            SubmissionOrder::Desc => "DESC",
+        }
+    }
 }
 
 #[async_trait]
 pub trait SubmissionClient {
     async fn get_submissions<'a>(&self, request: SubmissionRequest<'a>) -> Result<Vec<Submission>>;
     async fn get_user_submission_count(&self, user_id: &str) -> Result<i64>;
+    async fn get_user_submission_summary(&self, user_id: &str) -> Result<SubmissionSummary>;
+    async fn get_time_submission_summary(
+        &self,
+        from_second: i64,
+        count: i64,
+    ) -> Result<SubmissionSummary>;
     async fn update_submissions(&self, values: &[Submission]) -> Result<usize>;
     async fn update_submission_count(&self) -> Result<()>;
     async fn update_user_submission_count(&self, user_id: &str) -> Result<()>;
@@ -60,6 +111,7 @@ pub trait SubmissionClient {
 #[async_trait]
 impl SubmissionClient for PgPool {
     async fn get_submissions<'a>(&self, request: SubmissionRequest<'a>) -> Result<Vec<Submission>> {
+        let filtered_query;
         let submissions = match request {
             SubmissionRequest::UserAll { user_id } => sqlx::query_as(
                 r"
@@ -69,6 +121,25 @@ impl SubmissionClient for PgPool {
             )
             .bind(user_id)
             .fetch_all(self),
+            SubmissionRequest::UserPage {
+                user_id,
+                from_second,
+                from_id,
+                count,
+            } => sqlx::query_as(
+                r"
+                    SELECT * FROM submissions
+                    WHERE LOWER(user_id) = LOWER($1)
+                    AND (epoch_second, id) > ($2, $3)
+                    ORDER BY epoch_second ASC, id ASC
+                    LIMIT $4
+                    ",
+            )
+            .bind(user_id)
+            .bind(from_second)
+            .bind(from_id)
+            .bind(count)
+            .fetch_all(self),
             SubmissionRequest::FromTime { from_second, count } => sqlx::query_as(
                 r"
                          SELECT * FROM submissions
@@ -145,19 +216,62 @@ impl SubmissionClient for PgPool {
             } => sqlx::query_as(
                 r"
                     SELECT * FROM submissions
-                    WHERE user_id = ANY($1)
+                    WHERE LOWER(user_id) = ANY($1)
                     AND problem_id = ANY($2)
                     AND epoch_second >= $3
                     AND epoch_second <= $4
                     LIMIT $5
                     ",
             )
-            .bind(user_ids)
+            .bind(
+                user_ids
+                    .iter()
+                    .map(|id| id.to_lowercase())
+                    .collect::<Vec<_>>(),
+            )
             .bind(problem_ids)
             .bind(from_second)
             .bind(to_second)
             .bind(SUBMISSION_LIMIT)
             .fetch_all(self),
+            SubmissionRequest::Filtered { filter } => {
+                // The language condition is the SQL version of `language_count::simplify_language`.
+                filtered_query = format!(
+                    r"
+                    SELECT * FROM submissions
+                    WHERE ($1::VARCHAR(255)[] IS NULL OR LOWER(user_id) = ANY($1))
+                    AND ($2::VARCHAR(255)[] IS NULL OR problem_id = ANY($2))
+                    AND ($3::VARCHAR(255)[] IS NULL OR contest_id = ANY($3))
+                    AND ($4::VARCHAR(255)[] IS NULL OR result = ANY($4))
+                    AND (
+                        $5::VARCHAR(255) IS NULL
+                        OR (
+                            CASE WHEN language LIKE 'Perl6%' THEN 'Raku'
+                            ELSE regexp_replace(language, '\d*\s*\(.*\)', '')
+                            END
+                        ) = $5
+                    )
+                    AND epoch_second >= $6
+                    AND epoch_second <= $7
+                    ORDER BY epoch_second {order}, id {order}
+                    LIMIT $8
+                    ",
+                    order = filter.order.unwrap_or(SubmissionOrder::Asc).as_sql()
+                );
+                let user_ids = filter
+                    .user_ids
+                    .map(|ids| ids.iter().map(|id| id.to_lowercase()).collect::<Vec<_>>());
+                sqlx::query_as(&filtered_query)
+                    .bind(user_ids)
+                    .bind(filter.problem_ids)
+                    .bind(filter.contest_ids)
+                    .bind(filter.results)
+                    .bind(filter.language)
+                    .bind(filter.from_second.unwrap_or(0))
+                    .bind(filter.to_second.unwrap_or(i64::MAX))
+                    .bind(filter.count)
+                    .fetch_all(self)
+            }
         }
         .await?;
         Ok(submissions)
@@ -177,6 +291,43 @@ impl SubmissionClient for PgPool {
         Ok(count)
     }
 
+    async fn get_user_submission_summary(&self, user_id: &str) -> Result<SubmissionSummary> {
+        // The value is looked up with an index so that conditional requests stay cheap.
+        let summary = sqlx::query_as(
+            r"
+            SELECT COALESCE(MAX(update_id), 0) AS max_update_id FROM submissions
+            WHERE LOWER(user_id) = LOWER($1)
+            ",
+        )
+        .bind(user_id)
+        .fetch_one(self)
+        .await?;
+        Ok(summary)
+    }
+
+    async fn get_time_submission_summary(
+        &self,
+        from_second: i64,
+        count: i64,
+    ) -> Result<SubmissionSummary> {
+        let summary = sqlx::query_as(
+            r"
+            SELECT COALESCE(MAX(update_id), 0) AS max_update_id
+            FROM (
+                SELECT update_id FROM submissions
+                WHERE epoch_second >= $1
+                ORDER BY epoch_second ASC
+                LIMIT $2
+            ) AS s
+            ",
+        )
+        .bind(from_second)
+        .bind(count)
+        .fetch_one(self)
+        .await?;
+        Ok(summary)
+    }
+
     async fn update_submissions(&self, values: &[Submission]) -> Result<usize> {
         let (
             ids,
@@ -272,7 +423,22 @@ impl SubmissionClient for PgPool {
                 user_id = EXCLUDED.user_id,
                 result = EXCLUDED.result,
                 point = EXCLUDED.point,
-                execution_time = EXCLUDED.execution_time
+                execution_time = EXCLUDED.execution_time,
+                update_id = CASE
+                    WHEN (
+                        submissions.user_id,
+                        submissions.result,
+                        submissions.point,
+                        submissions.execution_time
+                    ) IS DISTINCT FROM (
+                        EXCLUDED.user_id,
+                        EXCLUDED.result,
+                        EXCLUDED.point,
+                        EXCLUDED.execution_time
+                    )
+                    THEN nextval('submission_update_id')
+                    ELSE submissions.update_id
+                END
             ",
         )
         .bind(ids)
@@ -71,3 +71,51 @@ async fn test_accepted_count() {
         .await
         .is_none());
 }
+
+#[async_std::test]
+async fn test_accepted_count_ranking() {
+    let pool = utils::initialize_and_connect_to_test_sql().await;
+    sqlx::query(
+        r"
+        INSERT INTO accepted_count (user_id, problem_count)
+        VALUES ('user1', 10), ('user2', 20), ('user3', 10), ('user4', 5)
+        ",
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    let ranking = pool.load_accepted_count_in_range(1, 2).await.unwrap();
+    assert_eq!(
+        ranking,
+        vec![
+            UserProblemCount {
+                user_id: "user1".to_owned(),
+                problem_count: 10
+            },
+            UserProblemCount {
+                user_id: "user3".to_owned(),
+                problem_count: 10
+            }
+        ]
+    );
+    assert!(pool
+        .load_accepted_count_in_range(4, 10)
+        .await
+        .unwrap()
+        .is_empty());
+
+    assert_eq!(pool.get_accepted_count_rank(10).await.unwrap(), 1);
+    assert_eq!(
+        pool.get_accepted_count_position("user1", 10).await.unwrap(),
+        1
+    );
+    assert_eq!(
+        pool.get_accepted_count_position("user3", 10).await.unwrap(),
+        2
+    );
+    assert_eq!(
+        pool.get_accepted_count_position("user4", 5).await.unwrap(),
+        3
+    );
+}
@@ -132,3 +132,55 @@ async fn test_language_count() {
         ]
     );
 }
+
+#[async_std::test]
+async fn test_language_count_ranking() {
+    let pool = utils::initialize_and_connect_to_test_sql().await;
+    sqlx::query(
+        r"
+        INSERT INTO language_count (user_id, simplified_language, problem_count)
+        VALUES
+            ('user1', 'Rust', 10),
+            ('user1', 'C++', 30),
+            ('user2', 'Rust', 20),
+            ('user3', 'Rust', 10)
+        ",
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    let ranking = pool
+        .load_language_count_in_range("Rust", 1, 10)
+        .await
+        .unwrap();
+    assert_eq!(
+        ranking,
+        vec![
+            UserLanguageCount {
+                user_id: "user1".to_owned(),
+                simplified_language: "Rust".to_owned(),
+                problem_count: 10
+            },
+            UserLanguageCount {
+                user_id: "user3".to_owned(),
+                simplified_language: "Rust".to_owned(),
+                problem_count: 10
+            },
+        ]
+    );
+
+    let counts = pool.get_users_language_count("user1").await.unwrap();
+    assert_eq!(counts.len(), 2);
+    assert_eq!(counts[0].simplified_language, "C++");
+    assert_eq!(counts[1].simplified_language, "Rust");
+
+    assert_eq!(pool.get_language_count_rank("Rust", 10).await.unwrap(), 1);
+    assert_eq!(pool.get_language_count_rank("C++", 30).await.unwrap(), 0);
+    assert_eq!(
+        pool.get_language_count_position("user3", "Rust", 10)
+            .await
+            .unwrap(),
+        2
+    );
+}
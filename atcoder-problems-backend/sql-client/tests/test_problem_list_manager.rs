@@ -1,4 +1,8 @@
-use sql_client::internal::problem_list_manager::{ListItem, ProblemList, ProblemListManager};
+use sql_client::internal::problem_list_manager::{
+    ImportRejection, ItemProgress, ItemStatus, ListEditor, ListItem, ProblemList,
+    ProblemListManager, RejectedItem,
+};
+use sql_client::internal::DELETED_RETENTION_SECOND;
 
 mod utils;
 
@@ -25,6 +29,8 @@ async fn test_problem_list_manager() {
             internal_list_id: list_id.clone(),
             internal_list_name: list_name.to_string(),
             internal_user_id: internal_user_id.to_string(),
+            source_list_id: None,
+            fork_count: 0,
             items: vec![],
         },
         "`get_single_list` returned an unexpected value."
@@ -46,6 +52,7 @@ async fn test_problem_list_manager() {
         vec![ListItem {
             problem_id: problem_id.to_string(),
             memo: "".to_string(),
+            tags: vec![],
         }],
         "The item that has been added to the list is not found."
     );
@@ -59,6 +66,7 @@ async fn test_problem_list_manager() {
         vec![ListItem {
             problem_id: problem_id.to_string(),
             memo: "memo_updated".to_string(),
+            tags: vec![],
         }],
         "`memo` should be updated, but not."
     );
@@ -70,9 +78,444 @@ async fn test_problem_list_manager() {
         "The list still has its item unexpectedly."
     );
 
-    pool.delete_list(&list_id).await.unwrap();
+    pool.delete_list(&list_id, internal_user_id, 0)
+        .await
+        .unwrap();
     assert!(
         pool.get_list(internal_user_id).await.unwrap().is_empty(),
         "The list should be deleted, but still exists."
     );
 }
+
+#[async_std::test]
+async fn test_restore_list() {
+    let internal_user_id = "user_id";
+    let pool = utils::initialize_and_connect_to_test_sql().await;
+    utils::setup_internal_user(&pool, internal_user_id, "atcoder_id").await;
+    utils::setup_internal_user(&pool, "other_user_id", "other_atcoder_id").await;
+
+    let list_id = pool
+        .create_list(internal_user_id, "list_name")
+        .await
+        .unwrap();
+    pool.add_item(&list_id, "problem_id").await.unwrap();
+
+    pool.delete_list(&list_id, "other_user_id", 100)
+        .await
+        .unwrap_err();
+    pool.delete_list(&list_id, internal_user_id, 100)
+        .await
+        .unwrap();
+    pool.get_single_list(&list_id).await.unwrap_err();
+    pool.add_item(&list_id, "problem_id_2").await.unwrap_err();
+
+    let deleted = pool.get_deleted_lists(internal_user_id, 200).await.unwrap();
+    assert_eq!(deleted.len(), 1);
+    assert_eq!(deleted[0].internal_list_id, list_id);
+
+    pool.restore_list(&list_id, "other_user_id", 200)
+        .await
+        .unwrap_err();
+    pool.restore_list(&list_id, internal_user_id, 200)
+        .await
+        .unwrap();
+    let list = pool.get_single_list(&list_id).await.unwrap();
+    assert_eq!(list.items.len(), 1);
+    assert!(pool
+        .get_deleted_lists(internal_user_id, 200)
+        .await
+        .unwrap()
+        .is_empty());
+
+    pool.delete_list(&list_id, internal_user_id, 300)
+        .await
+        .unwrap();
+    let expired = 300 + DELETED_RETENTION_SECOND;
+    pool.restore_list(&list_id, internal_user_id, expired)
+        .await
+        .unwrap_err();
+    assert_eq!(pool.purge_deleted_lists(expired - 1).await.unwrap(), 0);
+    assert_eq!(pool.purge_deleted_lists(expired).await.unwrap(), 1);
+    pool.restore_list(&list_id, internal_user_id, 300)
+        .await
+        .unwrap_err();
+}
+
+#[async_std::test]
+async fn test_fork_list() {
+    let pool = utils::initialize_and_connect_to_test_sql().await;
+    utils::setup_internal_user(&pool, "user_id", "atcoder_id").await;
+    utils::setup_internal_user(&pool, "other_user_id", "other_atcoder_id").await;
+
+    let list_id = pool.create_list("user_id", "list_name").await.unwrap();
+    pool.add_item(&list_id, "problem_1").await.unwrap();
+    pool.add_item(&list_id, "problem_2").await.unwrap();
+    pool.update_item(&list_id, "problem_1", "memo_1")
+        .await
+        .unwrap();
+
+    let fork_id = pool.fork_list(&list_id, "other_user_id").await.unwrap();
+    let fork = pool.get_single_list(&fork_id).await.unwrap();
+    assert_eq!(fork.internal_user_id, "other_user_id");
+    assert_eq!(fork.internal_list_name, "list_name");
+    assert_eq!(fork.source_list_id, Some(list_id.clone()));
+    assert_eq!(fork.fork_count, 0);
+    let mut items = fork.items;
+    items.sort_by(|a, b| a.problem_id.cmp(&b.problem_id));
+    assert_eq!(
+        items,
+        vec![
+            ListItem {
+                problem_id: "problem_1".to_string(),
+                memo: "memo_1".to_string(),
+                tags: vec![],
+            },
+            ListItem {
+                problem_id: "problem_2".to_string(),
+                memo: "".to_string(),
+                tags: vec![],
+            },
+        ]
+    );
+
+    pool.add_item(&fork_id, "problem_3").await.unwrap();
+    let list = pool.get_single_list(&list_id).await.unwrap();
+    assert_eq!(
+        list.items.len(),
+        2,
+        "The fork should not change the source."
+    );
+    assert_eq!(list.fork_count, 1);
+
+    pool.fork_list(&list_id, "user_id").await.unwrap();
+    assert_eq!(pool.get_single_list(&list_id).await.unwrap().fork_count, 2);
+    pool.delete_list(&fork_id, "other_user_id", 0)
+        .await
+        .unwrap();
+    assert_eq!(pool.get_single_list(&list_id).await.unwrap().fork_count, 1);
+
+    pool.delete_list(&list_id, "user_id", 0).await.unwrap();
+    pool.fork_list(&list_id, "other_user_id").await.unwrap_err();
+    pool.fork_list("nonexistent", "other_user_id")
+        .await
+        .unwrap_err();
+}
+
+#[async_std::test]
+async fn test_ordered_and_tagged_items() {
+    let pool = utils::initialize_and_connect_to_test_sql().await;
+    utils::setup_internal_user(&pool, "user_id", "atcoder_id").await;
+
+    let list_id = pool.create_list("user_id", "list_name").await.unwrap();
+    for problem_id in ["problem_b", "problem_c", "problem_a"].iter() {
+        pool.add_item(&list_id, problem_id).await.unwrap();
+    }
+    let problem_ids = |list: &ProblemList| {
+        list.items
+            .iter()
+            .map(|item| item.problem_id.clone())
+            .collect::<Vec<_>>()
+    };
+    let list = pool.get_single_list(&list_id).await.unwrap();
+    assert_eq!(
+        problem_ids(&list),
+        vec!["problem_b", "problem_c", "problem_a"],
+        "Items should be in the order of addition."
+    );
+
+    pool.reorder_items(&list_id, &["problem_a", "problem_b", "problem_c"])
+        .await
+        .unwrap();
+    let list = pool.get_single_list(&list_id).await.unwrap();
+    assert_eq!(
+        problem_ids(&list),
+        vec!["problem_a", "problem_b", "problem_c"]
+    );
+    assert_eq!(
+        problem_ids(&pool.get_list("user_id").await.unwrap()[0]),
+        vec!["problem_a", "problem_b", "problem_c"]
+    );
+
+    pool.reorder_items(&list_id, &["problem_a", "problem_b"])
+        .await
+        .unwrap_err();
+    pool.reorder_items(
+        &list_id,
+        &["problem_a", "problem_a", "problem_b", "problem_c"],
+    )
+    .await
+    .unwrap_err();
+    pool.reorder_items(&list_id, &["problem_a", "problem_b", "problem_d"])
+        .await
+        .unwrap_err();
+
+    pool.update_item_tags(&list_id, "problem_b", &["dp", "graph", " dp "])
+        .await
+        .unwrap();
+    let list = pool.get_single_list(&list_id).await.unwrap();
+    assert_eq!(list.items[1].tags, vec!["dp", "graph"]);
+    assert!(list.items[0].tags.is_empty());
+    pool.update_item_tags(&list_id, "problem_b", &["math", " "])
+        .await
+        .unwrap_err();
+    pool.update_item_tags(&list_id, "problem_b", &["x".repeat(256).as_str()])
+        .await
+        .unwrap_err();
+    pool.update_item_tags(&list_id, "problem_b", &["x".repeat(255).as_str()])
+        .await
+        .unwrap();
+
+    pool.update_item_tags(&list_id, "problem_b", &["math"])
+        .await
+        .unwrap();
+    let list = pool.get_single_list(&list_id).await.unwrap();
+    assert_eq!(list.items[1].tags, vec!["math"]);
+    pool.update_item_tags(&list_id, "problem_d", &["math"])
+        .await
+        .unwrap_err();
+
+    let fork_id = pool.fork_list(&list_id, "user_id").await.unwrap();
+    let fork = pool.get_single_list(&fork_id).await.unwrap();
+    assert_eq!(fork.items, list.items);
+
+    pool.delete_item(&list_id, "problem_b").await.unwrap();
+    pool.add_item(&list_id, "problem_b").await.unwrap();
+    let list = pool.get_single_list(&list_id).await.unwrap();
+    assert_eq!(
+        problem_ids(&list),
+        vec!["problem_a", "problem_c", "problem_b"]
+    );
+    assert!(list.items[2].tags.is_empty());
+}
+
+#[async_std::test]
+async fn test_import_items() {
+    let pool = utils::initialize_and_connect_to_test_sql().await;
+    utils::setup_internal_user(&pool, "user_id", "atcoder_id").await;
+    sqlx::query(
+        r"
+        INSERT INTO problems (id, contest_id, title)
+        VALUES
+            ('problem_a', 'contest', 'A'),
+            ('problem_b', 'contest', 'B'),
+            ('problem_c', 'contest', 'C')
+        ",
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    let list_id = pool.create_list("user_id", "list_name").await.unwrap();
+    pool.add_item(&list_id, "problem_a").await.unwrap();
+
+    let long_memo = "x".repeat(256);
+    let rejected = pool
+        .import_items(
+            &list_id,
+            &[
+                ("problem_c", "memo_c"),
+                ("problem_a", "memo_a"),
+                ("problem_x", ""),
+                ("problem_b", &long_memo),
+                ("problem_b", "memo_b"),
+                ("problem_c", ""),
+            ],
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        rejected,
+        vec![
+            RejectedItem {
+                index: 1,
+                problem_id: "problem_a".to_string(),
+                reason: ImportRejection::Duplicate,
+            },
+            RejectedItem {
+                index: 2,
+                problem_id: "problem_x".to_string(),
+                reason: ImportRejection::UnknownProblem,
+            },
+            RejectedItem {
+                index: 3,
+                problem_id: "problem_b".to_string(),
+                reason: ImportRejection::TooLongMemo,
+            },
+            RejectedItem {
+                index: 5,
+                problem_id: "problem_c".to_string(),
+                reason: ImportRejection::Duplicate,
+            },
+        ]
+    );
+
+    let list = pool.get_single_list(&list_id).await.unwrap();
+    let items = list
+        .items
+        .iter()
+        .map(|item| (item.problem_id.as_str(), item.memo.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        items,
+        vec![
+            ("problem_a", ""),
+            ("problem_c", "memo_c"),
+            ("problem_b", "memo_b")
+        ]
+    );
+
+    assert!(pool.import_items(&list_id, &[]).await.unwrap().is_empty());
+    pool.delete_list(&list_id, "user_id", 0).await.unwrap();
+    pool.import_items(&list_id, &[("problem_a", "")])
+        .await
+        .unwrap_err();
+
+    let (list_id, rejected) = pool
+        .create_list_with_items(
+            "user_id",
+            "imported",
+            &[("problem_b", "memo_b"), ("problem_x", "")],
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        rejected,
+        vec![RejectedItem {
+            index: 1,
+            problem_id: "problem_x".to_string(),
+            reason: ImportRejection::UnknownProblem,
+        }]
+    );
+    let list = pool.get_single_list(&list_id).await.unwrap();
+    assert_eq!(list.internal_list_name, "imported");
+    assert_eq!(list.items.len(), 1);
+    assert_eq!(list.items[0].problem_id, "problem_b");
+    assert_eq!(list.items[0].memo, "memo_b");
+}
+
+#[async_std::test]
+async fn test_list_progress() {
+    let pool = utils::initialize_and_connect_to_test_sql().await;
+    utils::setup_internal_user(&pool, "user_id", "atcoder_id").await;
+    sqlx::query(
+        r"
+        INSERT INTO submissions
+            (id, epoch_second, problem_id, contest_id, user_id, language, point, length, result)
+        VALUES
+            (1, 100, 'problem_a', 'contest', 'atcoder_id', 'Rust', 0.0, 0, 'WA'),
+            (2, 200, 'problem_a', 'contest', 'atcoder_id', 'Rust', 100.0, 0, 'AC'),
+            (3, 300, 'problem_a', 'contest', 'atcoder_id', 'Rust', 100.0, 0, 'AC'),
+            (4, 100, 'problem_b', 'contest', 'atcoder_id', 'Rust', 0.0, 0, 'TLE'),
+            (5, 100, 'problem_c', 'contest', 'other_id', 'Rust', 100.0, 0, 'AC'),
+            (6, 100, 'problem_d', 'contest', 'atcoder_id', 'Rust', 100.0, 0, 'AC')
+        ",
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    let list_id = pool.create_list("user_id", "list_name").await.unwrap();
+    for problem_id in ["problem_c", "problem_a", "problem_b", "problem_d"].iter() {
+        pool.add_item(&list_id, problem_id).await.unwrap();
+    }
+    pool.add_item(&list_id, "problem_e").await.unwrap();
+    sqlx::query(
+        r"
+        INSERT INTO internal_progress_reset (internal_user_id, problem_id, reset_epoch_second)
+        VALUES ('user_id', 'problem_a', 250), ('user_id', 'problem_d', 100)
+        ",
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    let progress = |problem_id: &str, status, first_ac_epoch_second| ItemProgress {
+        problem_id: problem_id.to_string(),
+        status,
+        first_ac_epoch_second,
+    };
+    assert_eq!(
+        pool.get_list_progress(&list_id, "atcoder_id")
+            .await
+            .unwrap(),
+        vec![
+            progress("problem_c", ItemStatus::Untouched, None),
+            progress("problem_a", ItemStatus::Accepted, Some(300)),
+            progress("problem_b", ItemStatus::Tried, None),
+            progress("problem_d", ItemStatus::Untouched, None),
+            progress("problem_e", ItemStatus::Untouched, None),
+        ],
+        "The progress of the owner should honor the resets."
+    );
+    assert_eq!(
+        pool.get_list_progress(&list_id, "ATCODER_ID")
+            .await
+            .unwrap()[1],
+        progress("problem_a", ItemStatus::Accepted, Some(300))
+    );
+
+    utils::setup_internal_user(&pool, "other_user_id", "other_id").await;
+    let fork_id = pool.fork_list(&list_id, "other_user_id").await.unwrap();
+    let fork_progress = pool
+        .get_list_progress(&fork_id, "atcoder_id")
+        .await
+        .unwrap();
+    assert_eq!(
+        fork_progress[1],
+        progress("problem_a", ItemStatus::Accepted, Some(200)),
+        "The resets of other users should be ignored."
+    );
+    assert_eq!(
+        fork_progress[3],
+        progress("problem_d", ItemStatus::Accepted, Some(100))
+    );
+    assert_eq!(
+        pool.get_list_progress(&fork_id, "other_id").await.unwrap()[0],
+        progress("problem_c", ItemStatus::Accepted, Some(100))
+    );
+
+    pool.get_list_progress("nonexistent", "atcoder_id")
+        .await
+        .unwrap_err();
+}
+
+#[async_std::test]
+async fn test_list_editors() {
+    let pool = utils::initialize_and_connect_to_test_sql().await;
+    utils::setup_internal_user(&pool, "owner_id", "owner").await;
+    utils::setup_internal_user(&pool, "editor_id", "editor").await;
+
+    let list_id = pool.create_list("owner_id", "list_name").await.unwrap();
+    assert!(pool.get_list_editors(&list_id).await.unwrap().is_empty());
+    assert!(pool.get_shared_lists("editor_id").await.unwrap().is_empty());
+
+    pool.add_list_editor(&list_id, "editor_id").await.unwrap();
+    pool.add_list_editor(&list_id, "editor_id").await.unwrap();
+    assert_eq!(
+        pool.get_list_editors(&list_id).await.unwrap(),
+        vec![ListEditor {
+            internal_user_id: "editor_id".to_string(),
+            atcoder_user_id: Some("editor".to_string()),
+        }]
+    );
+    pool.add_item(&list_id, "problem_a").await.unwrap();
+    let shared = pool.get_shared_lists("editor_id").await.unwrap();
+    assert_eq!(shared.len(), 1);
+    assert_eq!(shared[0].internal_list_id, list_id);
+    assert_eq!(shared[0].internal_user_id, "owner_id");
+    assert_eq!(shared[0].items.len(), 1);
+    assert!(
+        pool.get_list("editor_id").await.unwrap().is_empty(),
+        "Shared lists should not be counted as the editor's own lists."
+    );
+
+    pool.delete_list(&list_id, "owner_id", 0).await.unwrap();
+    assert!(pool.get_shared_lists("editor_id").await.unwrap().is_empty());
+    pool.restore_list(&list_id, "owner_id", 0).await.unwrap();
+
+    pool.remove_list_editor(&list_id, "editor_id")
+        .await
+        .unwrap();
+    assert!(pool.get_list_editors(&list_id).await.unwrap().is_empty());
+    assert!(pool.get_shared_lists("editor_id").await.unwrap().is_empty());
+}
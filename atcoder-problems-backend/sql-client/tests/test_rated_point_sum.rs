@@ -199,3 +199,32 @@ async fn test_update_rated_point_sum() {
         .is_none());
 }
 
+#[async_std::test]
+async fn test_rated_point_sum_ranking() {
+    let pool = utils::initialize_and_connect_to_test_sql().await;
+    sqlx::query(
+        r"
+        INSERT INTO rated_point_sum (user_id, point_sum)
+        VALUES ('user1', 300.0), ('user2', 1200.0), ('user3', 300.0)
+        ",
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    let ranking = pool.load_rated_point_sum_in_range(0, 2).await.unwrap();
+    let user_ids = ranking
+        .iter()
+        .map(|s| s.user_id.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(user_ids, vec!["user2", "user1"]);
+    assert_eq!(ranking[0].point_sum, 1200.0);
+
+    assert_eq!(pool.get_rated_point_sum_rank(300.0).await.unwrap(), 1);
+    assert_eq!(
+        pool.get_rated_point_sum_position("user3", 300.0)
+            .await
+            .unwrap(),
+        2
+    );
+}
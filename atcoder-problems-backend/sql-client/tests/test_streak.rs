@@ -1,5 +1,5 @@
-use sql_client::models::UserStreak;
-use sql_client::streak::StreakUpdater;
+use sql_client::models::{UserCurrentStreak, UserStreak};
+use sql_client::streak::{StreakClient, StreakUpdater};
 use sql_client::submission_client::{SubmissionClient, SubmissionRequest};
 use sqlx::postgres::PgRow;
 use sqlx::Row;
@@ -40,5 +40,92 @@ async fn test_update_streak_ranking() {
 
     assert_eq!(v.len(), 1);
     assert_eq!(v[0].streak, 2);
+
+    assert_eq!(
+        pool.get_users_current_streak("user1").await,
+        Some(UserCurrentStreak {
+            user_id: "user1".to_owned(),
+            streak: 2,
+            last_ac_date: "2019-10-05".to_owned()
+        })
+    );
+    assert_eq!(pool.get_users_current_streak("user2").await, None);
+}
+
+#[async_std::test]
+async fn test_streak_ranking() {
+    let pool = utils::initialize_and_connect_to_test_sql().await;
+    sqlx::query(
+        r"
+        INSERT INTO max_streaks (user_id, streak)
+        VALUES ('user1', 3), ('user2', 7), ('user3', 3)
+        ",
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    let ranking = pool.load_streak_count_in_range(0, 10).await.unwrap();
+    assert_eq!(
+        ranking,
+        vec![
+            UserStreak {
+                user_id: "user2".to_owned(),
+                streak: 7
+            },
+            UserStreak {
+                user_id: "user1".to_owned(),
+                streak: 3
+            },
+            UserStreak {
+                user_id: "user3".to_owned(),
+                streak: 3
+            },
+        ]
+    );
+
+    assert_eq!(pool.get_users_streak_count("user1").await, Some(3));
+    assert_eq!(pool.get_users_streak_count("user9").await, None);
+    assert_eq!(pool.get_streak_count_rank(3).await.unwrap(), 1);
+    assert_eq!(pool.get_streak_count_position("user3", 3).await.unwrap(), 2);
 }
 
+#[async_std::test]
+async fn test_current_streak_ranking() {
+    let pool = utils::initialize_and_connect_to_test_sql().await;
+    sqlx::query(
+        r"
+        INSERT INTO max_streaks (user_id, streak, current_streak, last_ac_date)
+        VALUES
+            ('user1', 10, 3, '2020-01-10'),
+            ('user2', 10, 5, '2020-01-09'),
+            ('user3', 10, 3, '2020-01-10'),
+            ('user4', 10, 8, '2020-01-08')
+        ",
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    let ranking = pool
+        .load_current_streak_count_in_range("2020-01-09", 0, 10)
+        .await
+        .unwrap();
+    let user_ids = ranking
+        .iter()
+        .map(|s| s.user_id.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(user_ids, vec!["user2", "user1", "user3"]);
+    assert_eq!(ranking[0].last_ac_date, "2020-01-09");
+
+    assert_eq!(
+        pool.get_current_streak_rank("2020-01-09", 3).await.unwrap(),
+        1
+    );
+    assert_eq!(
+        pool.get_current_streak_position("2020-01-09", "user3", 3)
+            .await
+            .unwrap(),
+        2
+    );
+}
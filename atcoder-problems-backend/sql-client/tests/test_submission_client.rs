@@ -1,5 +1,7 @@
-use sql_client::models::Submission;
-use sql_client::submission_client::{SubmissionClient, SubmissionRequest};
+use sql_client::models::{Submission, SubmissionSummary};
+use sql_client::submission_client::{
+    SubmissionClient, SubmissionFilter, SubmissionOrder, SubmissionRequest,
+};
 
 mod utils;
 
@@ -97,6 +99,16 @@ async fn test_submission_client() {
     assert_eq!(pool.count_stored_submissions(&[1]).await.unwrap(), 1);
     assert_eq!(pool.count_stored_submissions(&[9]).await.unwrap(), 0);
 
+    // Every inserted submission takes a new `update_id`.
+    let user1 = pool.get_user_submission_summary("USER1").await.unwrap();
+    assert!(user1.max_update_id > 0);
+    let user2 = pool.get_user_submission_summary("user2").await.unwrap();
+    assert_ne!(user1, user2);
+    let summary = pool.get_user_submission_summary("user3").await.unwrap();
+    assert_eq!(summary, SubmissionSummary::default());
+    let summary = pool.get_time_submission_summary(200, 2).await.unwrap();
+    assert!(summary.max_update_id > 0);
+
     let request = SubmissionRequest::InvalidResult { from_second: 1 };
     let submissions = pool.get_submissions(request).await.unwrap();
     assert_eq!(submissions.len(), 2);
@@ -104,6 +116,162 @@ async fn test_submission_client() {
     let request = SubmissionRequest::InvalidResult { from_second: 2 };
     let submissions = pool.get_submissions(request).await.unwrap();
     assert_eq!(submissions.len(), 1);
+
+    // Only a rejudge which changes the result moves `max_update_id`.
+    let mut submission = pool
+        .get_submissions(SubmissionRequest::ByIds { ids: &[4] })
+        .await
+        .unwrap()
+        .remove(0);
+    pool.update_submissions(&[submission.clone()])
+        .await
+        .unwrap();
+    let summary = pool.get_user_submission_summary("user1").await.unwrap();
+    assert_eq!(summary, user1);
+    submission.result = "WA".to_owned();
+    pool.update_submissions(&[submission]).await.unwrap();
+    let summary = pool.get_user_submission_summary("user1").await.unwrap();
+    assert!(summary.max_update_id > user1.max_update_id);
+}
+
+#[async_std::test]
+async fn test_user_page() {
+    let pool = utils::initialize_and_connect_to_test_sql().await;
+    sqlx::query(
+        r"
+        INSERT INTO submissions
+            (id, epoch_second, problem_id, contest_id, user_id, language, point, length, result)
+        VALUES
+            (1, 100, 'problem1', 'contest1', 'user1', 'language1', 1.0, 1, 'AC'),
+            (2, 100, 'problem2', 'contest1', 'user1', 'language1', 1.0, 1, 'WA'),
+            (3, 200, 'problem1', 'contest1', 'user2', 'language1', 1.0, 1, 'AC'),
+            (4, 300, 'problem3', 'contest1', 'User1', 'language1', 1.0, 1, 'AC'),
+            (5, 50, 'problem3', 'contest1', 'user1', 'language1', 1.0, 1, 'AC');
+    ",
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    let request = SubmissionRequest::UserPage {
+        user_id: "user1",
+        from_second: 0,
+        from_id: i64::MIN,
+        count: 10,
+    };
+    let submissions = pool.get_submissions(request).await.unwrap();
+    let ids = submissions.iter().map(|s| s.id).collect::<Vec<_>>();
+    assert_eq!(ids, vec![5, 1, 2, 4]);
+
+    let request = SubmissionRequest::UserPage {
+        user_id: "user1",
+        from_second: 0,
+        from_id: i64::MIN,
+        count: 2,
+    };
+    let submissions = pool.get_submissions(request).await.unwrap();
+    let ids = submissions.iter().map(|s| s.id).collect::<Vec<_>>();
+    assert_eq!(ids, vec![5, 1]);
+
+    let request = SubmissionRequest::UserPage {
+        user_id: "user1",
+        from_second: 100,
+        from_id: 1,
+        count: 2,
+    };
+    let submissions = pool.get_submissions(request).await.unwrap();
+    let ids = submissions.iter().map(|s| s.id).collect::<Vec<_>>();
+    assert_eq!(ids, vec![2, 4]);
+
+    let request = SubmissionRequest::UserPage {
+        user_id: "user1",
+        from_second: 300,
+        from_id: 4,
+        count: 2,
+    };
+    let submissions = pool.get_submissions(request).await.unwrap();
+    assert!(submissions.is_empty());
+}
+
+#[async_std::test]
+async fn test_filtered() {
+    let pool = utils::initialize_and_connect_to_test_sql().await;
+    sqlx::query(
+        r"
+        INSERT INTO submissions
+            (id, epoch_second, problem_id, contest_id, user_id, language, point, length, result)
+        VALUES
+            (1, 100, 'abc100_d', 'abc100', 'user1', 'C++ (GCC 9.2.1)', 0.0, 1, 'WA'),
+            (2, 200, 'abc100_d', 'abc100', 'user1', 'C++14 (GCC 5.4.1)', 0.0, 1, 'WA'),
+            (3, 300, 'abc100_d', 'abc100', 'user1', 'C++ (GCC 9.2.1)', 400.0, 1, 'AC'),
+            (4, 400, 'abc101_d', 'abc101', 'user1', 'Python (3.8.2)', 0.0, 1, 'WA'),
+            (5, 500, 'abc101_d', 'abc101', 'user2', 'C++ (GCC 9.2.1)', 0.0, 1, 'WA'),
+            (6, 600, 'arc100_a', 'arc100', 'user1', 'C# (Mono 6.8.0.105)', 0.0, 1, 'WA'),
+            (7, 700, 'arc100_a', 'arc100', 'user1', 'Perl6 (rakudo-star 2016.01)', 0.0, 1, 'WA');
+    ",
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    let ids = |submissions: Vec<Submission>| submissions.iter().map(|s| s.id).collect::<Vec<_>>();
+
+    let request = SubmissionRequest::Filtered {
+        filter: SubmissionFilter {
+            user_ids: Some(&["User1"]),
+            problem_ids: Some(&["abc100_d", "abc101_d"]),
+            results: Some(&["WA"]),
+            language: Some("C++"),
+            count: 10,
+            ..Default::default()
+        },
+    };
+    let submissions = pool.get_submissions(request).await.unwrap();
+    assert_eq!(ids(submissions), vec![1, 2]);
+
+    let request = SubmissionRequest::Filtered {
+        filter: SubmissionFilter {
+            contest_ids: Some(&["abc100", "abc101"]),
+            order: Some(SubmissionOrder::Desc),
+            count: 2,
+            ..Default::default()
+        },
+    };
+    let submissions = pool.get_submissions(request).await.unwrap();
+    assert_eq!(ids(submissions), vec![5, 4]);
+
+    let request = SubmissionRequest::Filtered {
+        filter: SubmissionFilter {
+            user_ids: Some(&["user1"]),
+            from_second: Some(200),
+            to_second: Some(400),
+            count: 10,
+            ..Default::default()
+        },
+    };
+    let submissions = pool.get_submissions(request).await.unwrap();
+    assert_eq!(ids(submissions), vec![2, 3, 4]);
+
+    let request = SubmissionRequest::Filtered {
+        filter: SubmissionFilter {
+            user_ids: Some(&["user1"]),
+            language: Some("Raku"),
+            count: 10,
+            ..Default::default()
+        },
+    };
+    let submissions = pool.get_submissions(request).await.unwrap();
+    assert_eq!(ids(submissions), vec![7]);
+
+    let request = SubmissionRequest::Filtered {
+        filter: SubmissionFilter {
+            user_ids: Some(&[]),
+            count: 10,
+            ..Default::default()
+        },
+    };
+    let submissions = pool.get_submissions(request).await.unwrap();
+    assert!(submissions.is_empty());
 }
 
 #[async_std::test]
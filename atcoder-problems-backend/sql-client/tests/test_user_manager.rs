@@ -32,12 +32,13 @@ async fn test_user_manager() {
         InternalUserInfo {
             internal_user_id: internal_user_id.to_string(),
             atcoder_user_id: None,
+            timezone_offset: 540,
         },
         "`get_internal_user_info` for a user whose `atcoder_user_id` is not set returned an unexpected value."
     );
 
     let update_result = pool
-        .update_internal_user_info(internal_user_id, atcoder_user_id)
+        .update_internal_user_info(internal_user_id, atcoder_user_id, None)
         .await;
     assert!(
         update_result.is_ok(),
@@ -50,7 +51,53 @@ async fn test_user_manager() {
         InternalUserInfo {
             internal_user_id: internal_user_id.to_string(),
             atcoder_user_id: Some(atcoder_user_id.to_string()),
+            timezone_offset: 540,
         },
         "`get_internal_user_info` after `atcoder_user_id` was set returned an unexpected value."
     );
+
+    pool.update_internal_user_info(internal_user_id, atcoder_user_id, Some(-300))
+        .await
+        .unwrap();
+    let get_result = pool.get_internal_user_info(internal_user_id).await.unwrap();
+    assert_eq!(get_result.timezone_offset, -300);
+    assert_eq!(
+        get_result.atcoder_user_id,
+        Some(atcoder_user_id.to_string())
+    );
+    assert!(pool
+        .update_internal_user_info("unknown", atcoder_user_id, Some(-300))
+        .await
+        .is_err());
+    assert!(pool
+        .update_internal_user_info("unknown", atcoder_user_id, None)
+        .await
+        .is_err());
+}
+
+#[async_std::test]
+async fn test_calendar_token() {
+    let pool = utils::initialize_and_connect_to_test_sql().await;
+    pool.register_user("user_id").await.unwrap();
+
+    assert_eq!(pool.get_calendar_token("user_id").await.unwrap(), None);
+    let token = pool.generate_calendar_token("user_id").await.unwrap();
+    assert_eq!(
+        pool.get_calendar_token("user_id").await.unwrap(),
+        Some(token.clone())
+    );
+    assert_eq!(
+        pool.get_user_by_calendar_token(&token).await.unwrap(),
+        Some("user_id".to_owned())
+    );
+
+    let new_token = pool.generate_calendar_token("user_id").await.unwrap();
+    assert_ne!(token, new_token);
+    assert_eq!(pool.get_user_by_calendar_token(&token).await.unwrap(), None);
+    assert_eq!(
+        pool.get_user_by_calendar_token(&new_token).await.unwrap(),
+        Some("user_id".to_owned())
+    );
+
+    assert!(pool.generate_calendar_token("unknown").await.is_err());
 }
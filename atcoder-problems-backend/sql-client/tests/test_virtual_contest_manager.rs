@@ -1,6 +1,9 @@
 use sql_client::internal::virtual_contest_manager::{
-    VirtualContestInfo, VirtualContestItem, VirtualContestManager, MAX_PROBLEM_NUM_PER_CONTEST,
+    NotJoinableError, VirtualContestInfo, VirtualContestItem, VirtualContestManager,
+    VirtualContestMode, VirtualContestRegistration, VirtualContestSearch, VirtualContestState,
+    VirtualContestTeam, VirtualContestUser, MAX_PROBLEM_NUM_PER_CONTEST, MAX_TEAM_MEMBER_NUM,
 };
+use sql_client::internal::DELETED_RETENTION_SECOND;
 
 mod utils;
 
@@ -47,9 +50,10 @@ async fn test_virtual_contest_manager() {
     let memo = "memo";
     let start_epoch_second = 0;
     let duration_second = now_second.saturating_add(TIME_DELTA); // future
-    let mode = None;
+    let mode = Some(VirtualContestMode::Training);
     let is_public = true;
     let penalty_second = 42;
+    let freeze_second = 10;
     let contest_id = pool
         .create_contest(
             title,
@@ -57,9 +61,10 @@ async fn test_virtual_contest_manager() {
             user_id,
             start_epoch_second,
             duration_second,
-            mode.as_deref(),
+            mode,
             is_public,
             penalty_second,
+            freeze_second,
         )
         .await
         .unwrap();
@@ -70,9 +75,10 @@ async fn test_virtual_contest_manager() {
         owner_user_id: user_id.to_string(),
         start_epoch_second,
         duration_second,
-        mode: mode.clone(),
+        mode,
         is_public,
         penalty_second,
+        freeze_second,
     };
     let created_contests = vec![created_contest.clone()];
 
@@ -147,6 +153,29 @@ async fn test_virtual_contest_manager() {
         "Could not get the IDs of the running problems."
     );
 
+    // The contest is frozen for the last `freeze_second` seconds.
+    let frozen_second = now_second + TIME_DELTA - freeze_second;
+    let frozen_contests = pool
+        .get_frozen_contests(&["1", "2"], frozen_second)
+        .await
+        .unwrap();
+    assert_eq!(frozen_contests, created_contests);
+    let frozen_contests = pool
+        .get_frozen_contests(&["1", "2"], frozen_second - 1)
+        .await
+        .unwrap();
+    assert!(frozen_contests.is_empty());
+    let frozen_contests = pool
+        .get_frozen_contests(&["2"], frozen_second)
+        .await
+        .unwrap();
+    assert!(frozen_contests.is_empty());
+    let frozen_contests = pool
+        .get_frozen_contests(&["1"], now_second + TIME_DELTA)
+        .await
+        .unwrap();
+    assert!(frozen_contests.is_empty());
+
     let too_many_problems = (0..=MAX_PROBLEM_NUM_PER_CONTEST)
         .map(|i| VirtualContestItem {
             id: i.to_string(),
@@ -159,7 +188,9 @@ async fn test_virtual_contest_manager() {
         .await;
     assert!(update_result.is_err(), "`update_items` should fail because too many problems were passed, but actually it succeeded.");
 
-    pool.join_contest(&contest_id, user_id).await.unwrap();
+    pool.join_contest(&contest_id, user_id, None, now_second)
+        .await
+        .unwrap();
 
     let participated_contests = pool.get_participated_contests(user_id).await.unwrap();
     assert_eq!(
@@ -193,15 +224,41 @@ async fn test_virtual_contest_manager() {
     );
 
     let updated_duration_second = now_second.saturating_sub(TIME_DELTA); // past
+    let update_result = pool
+        .update_contest(
+            &contest_id,
+            "another title",
+            memo,
+            start_epoch_second,
+            updated_duration_second,
+            mode,
+            is_public,
+            penalty_second,
+            freeze_second,
+            "another_user_id",
+        )
+        .await;
+    assert!(
+        update_result.is_err(),
+        "`update_contest` should fail because the user does not own the contest, but actually it succeeded."
+    );
+    assert_eq!(
+        pool.get_single_contest_info(&contest_id).await.unwrap(),
+        created_contest,
+        "The contest should not be changed by a user who does not own it."
+    );
+
     pool.update_contest(
         &contest_id,
         title,
         memo,
         start_epoch_second,
         updated_duration_second,
-        mode.as_deref(),
+        mode,
         is_public,
         penalty_second,
+        freeze_second,
+        user_id,
     )
     .await
     .unwrap();
@@ -222,3 +279,700 @@ async fn test_virtual_contest_manager() {
         "`get_running_contest_problems` here should return an empty list, but got not empty."
     );
 }
+
+#[async_std::test]
+async fn test_contest_organizers() {
+    let pool = utils::initialize_and_connect_to_test_sql().await;
+    let owner_id = "owner_id";
+    let organizer_id = "organizer_id";
+    let stranger_id = "stranger_id";
+    utils::setup_internal_user(&pool, owner_id, "owner").await;
+    utils::setup_internal_user(&pool, organizer_id, "organizer").await;
+    utils::setup_internal_user(&pool, stranger_id, "stranger").await;
+
+    let contest_id = pool
+        .create_contest("title", "memo", owner_id, 0, 100, None, true, 0, 0)
+        .await
+        .unwrap();
+    assert!(pool
+        .get_contest_organizers(&contest_id)
+        .await
+        .unwrap()
+        .is_empty());
+
+    pool.add_contest_organizer(&contest_id, organizer_id)
+        .await
+        .unwrap();
+    pool.add_contest_organizer(&contest_id, organizer_id)
+        .await
+        .unwrap();
+    assert_eq!(
+        pool.get_contest_organizers(&contest_id).await.unwrap(),
+        vec![VirtualContestUser {
+            internal_user_id: organizer_id.to_owned(),
+            atcoder_user_id: Some("organizer".to_owned()),
+        }]
+    );
+
+    let own_contests = pool.get_own_contests(organizer_id).await.unwrap();
+    assert_eq!(own_contests.len(), 1);
+    assert_eq!(own_contests[0].id, contest_id);
+    assert_eq!(own_contests[0].owner_user_id, owner_id);
+    assert!(pool.get_own_contests(stranger_id).await.unwrap().is_empty());
+
+    pool.update_contest(
+        &contest_id,
+        "updated by organizer",
+        "memo",
+        0,
+        100,
+        None,
+        true,
+        0,
+        0,
+        organizer_id,
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        pool.get_single_contest_info(&contest_id)
+            .await
+            .unwrap()
+            .title,
+        "updated by organizer"
+    );
+    let problems = vec![VirtualContestItem {
+        id: "problem".to_owned(),
+        point: None,
+        order: None,
+    }];
+    pool.update_items(&contest_id, &problems, organizer_id)
+        .await
+        .unwrap();
+    assert!(pool
+        .update_items(&contest_id, &problems, stranger_id)
+        .await
+        .is_err());
+    assert!(pool
+        .update_contest(&contest_id, "", "", 0, 100, None, true, 0, 0, stranger_id)
+        .await
+        .is_err());
+
+    pool.remove_contest_organizer(&contest_id, organizer_id)
+        .await
+        .unwrap();
+    assert!(pool
+        .get_contest_organizers(&contest_id)
+        .await
+        .unwrap()
+        .is_empty());
+    assert!(pool
+        .update_items(&contest_id, &problems, organizer_id)
+        .await
+        .is_err());
+    assert!(pool
+        .get_own_contests(organizer_id)
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+#[async_std::test]
+async fn test_private_contest_invite() {
+    let pool = utils::initialize_and_connect_to_test_sql().await;
+    let owner_id = "owner_id";
+    let user_id = "user_id";
+    utils::setup_internal_user(&pool, owner_id, "owner").await;
+    utils::setup_internal_user(&pool, user_id, "user").await;
+
+    let contest_id = pool
+        .create_contest("title", "memo", owner_id, 0, 100, None, false, 0, 0)
+        .await
+        .unwrap();
+    assert_eq!(pool.get_invite_token(&contest_id).await.unwrap(), None);
+    assert!(pool
+        .join_contest(&contest_id, user_id, None, 0)
+        .await
+        .is_err());
+    assert!(pool
+        .join_contest(&contest_id, user_id, Some("wrong"), 0)
+        .await
+        .is_err());
+
+    let token = pool.generate_invite_token(&contest_id).await.unwrap();
+    assert_eq!(
+        pool.get_invite_token(&contest_id).await.unwrap(),
+        Some(token.clone())
+    );
+    pool.join_contest(&contest_id, user_id, Some(&token), 0)
+        .await
+        .unwrap();
+    pool.join_contest(&contest_id, owner_id, None, 0)
+        .await
+        .unwrap();
+    assert_eq!(
+        pool.get_single_contest_participants(&contest_id)
+            .await
+            .unwrap(),
+        vec!["owner".to_owned(), "user".to_owned()]
+    );
+
+    pool.leave_contest(&contest_id, user_id).await.unwrap();
+    let new_token = pool.generate_invite_token(&contest_id).await.unwrap();
+    assert_ne!(token, new_token);
+    assert!(pool
+        .join_contest(&contest_id, user_id, Some(&token), 0)
+        .await
+        .is_err());
+
+    pool.revoke_invite_token(&contest_id).await.unwrap();
+    assert_eq!(pool.get_invite_token(&contest_id).await.unwrap(), None);
+    assert!(pool
+        .join_contest(&contest_id, user_id, Some(&new_token), 0)
+        .await
+        .is_err());
+    assert!(pool.generate_invite_token("NON_EXISTING").await.is_err());
+}
+
+#[async_std::test]
+async fn test_participant_moderation() {
+    let pool = utils::initialize_and_connect_to_test_sql().await;
+    let owner_id = "owner_id";
+    let user1 = "user1_id";
+    let user2 = "user2_id";
+    utils::setup_internal_user(&pool, owner_id, "owner").await;
+    utils::setup_internal_user(&pool, user1, "user1").await;
+    utils::setup_internal_user(&pool, user2, "user2").await;
+
+    let contest_id = pool
+        .create_contest("title", "memo", owner_id, 0, 100, None, true, 0, 0)
+        .await
+        .unwrap();
+    assert_eq!(
+        pool.get_registration(&contest_id).await.unwrap(),
+        VirtualContestRegistration::default()
+    );
+
+    pool.join_contest(&contest_id, user1, None, 0)
+        .await
+        .unwrap();
+    pool.ban_user(&contest_id, user1).await.unwrap();
+    assert!(pool
+        .get_contest_participant_users(&contest_id)
+        .await
+        .unwrap()
+        .is_empty());
+    assert_eq!(
+        pool.get_banned_users(&contest_id).await.unwrap(),
+        vec![VirtualContestUser {
+            internal_user_id: user1.to_owned(),
+            atcoder_user_id: Some("user1".to_owned()),
+        }]
+    );
+    assert!(pool
+        .join_contest(&contest_id, user1, None, 0)
+        .await
+        .is_err());
+
+    pool.unban_user(&contest_id, user1).await.unwrap();
+    assert!(pool.get_banned_users(&contest_id).await.unwrap().is_empty());
+    pool.join_contest(&contest_id, user1, None, 0)
+        .await
+        .unwrap();
+
+    let registration = VirtualContestRegistration {
+        max_participants: Some(1),
+        registration_deadline_second: None,
+    };
+    pool.update_registration(&contest_id, &registration)
+        .await
+        .unwrap();
+    assert_eq!(
+        pool.get_registration(&contest_id).await.unwrap(),
+        registration
+    );
+    assert!(pool
+        .join_contest(&contest_id, user2, None, 0)
+        .await
+        .is_err());
+
+    pool.leave_contest(&contest_id, user1).await.unwrap();
+    let registration = VirtualContestRegistration {
+        max_participants: None,
+        registration_deadline_second: Some(100),
+    };
+    pool.update_registration(&contest_id, &registration)
+        .await
+        .unwrap();
+    assert!(pool
+        .join_contest(&contest_id, user2, None, 101)
+        .await
+        .is_err());
+    pool.join_contest(&contest_id, user2, None, 100)
+        .await
+        .unwrap();
+    pool.leave_contest(&contest_id, user2).await.unwrap();
+
+    let registration = VirtualContestRegistration {
+        max_participants: Some(2),
+        registration_deadline_second: Some(i64::MAX),
+    };
+    pool.update_registration(&contest_id, &registration)
+        .await
+        .unwrap();
+    pool.join_contest(&contest_id, user1, None, 0)
+        .await
+        .unwrap();
+    pool.join_contest(&contest_id, user2, None, 0)
+        .await
+        .unwrap();
+    let participants = pool
+        .get_contest_participant_users(&contest_id)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|u| u.internal_user_id)
+        .collect::<Vec<_>>();
+    assert_eq!(participants, vec![user1.to_owned(), user2.to_owned()]);
+}
+
+#[async_std::test]
+async fn test_clone_contest() {
+    let pool = utils::initialize_and_connect_to_test_sql().await;
+    let owner_id = "owner_id";
+    let user_id = "user_id";
+    utils::setup_internal_user(&pool, owner_id, "owner").await;
+    utils::setup_internal_user(&pool, user_id, "user").await;
+
+    let contest_id = pool
+        .create_contest(
+            "title",
+            "memo",
+            owner_id,
+            0,
+            100,
+            Some(VirtualContestMode::Lockout),
+            false,
+            300,
+            60,
+        )
+        .await
+        .unwrap();
+    let items = vec![
+        VirtualContestItem {
+            id: "problem_1".to_owned(),
+            point: Some(100),
+            order: Some(1),
+        },
+        VirtualContestItem {
+            id: "problem_2".to_owned(),
+            point: None,
+            order: Some(0),
+        },
+    ];
+    pool.update_items(&contest_id, &items, owner_id)
+        .await
+        .unwrap();
+    pool.join_contest(&contest_id, user_id, None, 0)
+        .await
+        .unwrap_err();
+    pool.join_contest(&contest_id, owner_id, None, 0)
+        .await
+        .unwrap();
+
+    let cloned_id = pool
+        .clone_contest(&contest_id, user_id, 1000)
+        .await
+        .unwrap();
+    assert_ne!(cloned_id, contest_id);
+    assert_eq!(
+        pool.get_single_contest_info(&cloned_id).await.unwrap(),
+        VirtualContestInfo {
+            id: cloned_id.clone(),
+            title: "title".to_owned(),
+            memo: "memo".to_owned(),
+            owner_user_id: user_id.to_owned(),
+            start_epoch_second: 1000,
+            duration_second: 100,
+            mode: Some(VirtualContestMode::Lockout),
+            is_public: false,
+            penalty_second: 300,
+            freeze_second: 60,
+        }
+    );
+    assert_eq!(
+        pool.get_single_contest_problems(&cloned_id).await.unwrap(),
+        pool.get_single_contest_problems(&contest_id).await.unwrap()
+    );
+    assert!(pool
+        .get_single_contest_participants(&cloned_id)
+        .await
+        .unwrap()
+        .is_empty());
+
+    assert!(pool
+        .clone_contest("NON_EXISTING", user_id, 1000)
+        .await
+        .is_err());
+}
+
+#[async_std::test]
+async fn test_search_contests() {
+    let pool = utils::initialize_and_connect_to_test_sql().await;
+    let owner_id = "owner_id";
+    let other_id = "other_id";
+    utils::setup_internal_user(&pool, owner_id, "owner").await;
+    utils::setup_internal_user(&pool, other_id, "other").await;
+
+    let finished = pool
+        .create_contest("ABC 100%", "", owner_id, 0, 100, None, true, 0, 0)
+        .await
+        .unwrap();
+    let running = pool
+        .create_contest("abc 200", "", other_id, 900, 200, None, true, 0, 0)
+        .await
+        .unwrap();
+    let upcoming = pool
+        .create_contest("ARC", "", owner_id, 2000, 100, None, true, 0, 0)
+        .await
+        .unwrap();
+    pool.create_contest("ABC private", "", owner_id, 0, 100, None, false, 0, 0)
+        .await
+        .unwrap();
+    pool.update_items(
+        &running,
+        &[VirtualContestItem {
+            id: "problem_1".to_owned(),
+            point: None,
+            order: None,
+        }],
+        other_id,
+    )
+    .await
+    .unwrap();
+
+    let search_ids = |search: VirtualContestSearch<'static>| {
+        let pool = pool.clone();
+        async move {
+            pool.search_contests(search, 1000)
+                .await
+                .unwrap()
+                .into_iter()
+                .map(|contest| contest.id)
+                .collect::<Vec<_>>()
+        }
+    };
+
+    assert_eq!(
+        search_ids(VirtualContestSearch {
+            count: 10,
+            ..Default::default()
+        })
+        .await,
+        vec![upcoming.clone(), running.clone(), finished.clone()]
+    );
+    assert_eq!(
+        search_ids(VirtualContestSearch {
+            offset: 1,
+            count: 1,
+            ..Default::default()
+        })
+        .await,
+        vec![running.clone()]
+    );
+    assert_eq!(
+        search_ids(VirtualContestSearch {
+            keyword: Some("abc"),
+            count: 10,
+            ..Default::default()
+        })
+        .await,
+        vec![running.clone(), finished.clone()]
+    );
+    assert_eq!(
+        search_ids(VirtualContestSearch {
+            keyword: Some("0%"),
+            count: 10,
+            ..Default::default()
+        })
+        .await,
+        vec![finished.clone()]
+    );
+    assert_eq!(
+        search_ids(VirtualContestSearch {
+            owner_user_id: Some(owner_id),
+            count: 10,
+            ..Default::default()
+        })
+        .await,
+        vec![upcoming.clone(), finished.clone()]
+    );
+    assert_eq!(
+        search_ids(VirtualContestSearch {
+            problem_id: Some("problem_1"),
+            count: 10,
+            ..Default::default()
+        })
+        .await,
+        vec![running.clone()]
+    );
+    for (state, expected) in vec![
+        (VirtualContestState::Upcoming, upcoming),
+        (VirtualContestState::Running, running),
+        (VirtualContestState::Finished, finished),
+    ] {
+        assert_eq!(
+            search_ids(VirtualContestSearch {
+                state: Some(state),
+                count: 10,
+                ..Default::default()
+            })
+            .await,
+            vec![expected]
+        );
+    }
+}
+
+#[async_std::test]
+async fn test_delete_contest() {
+    let pool = utils::initialize_and_connect_to_test_sql().await;
+    let owner_id = "owner_id";
+    let organizer_id = "organizer_id";
+    utils::setup_internal_user(&pool, owner_id, "owner").await;
+    utils::setup_internal_user(&pool, organizer_id, "organizer").await;
+
+    let contest_id = pool
+        .create_contest("title", "", owner_id, 0, 100, None, true, 0, 0)
+        .await
+        .unwrap();
+    pool.add_contest_organizer(&contest_id, organizer_id)
+        .await
+        .unwrap();
+    pool.join_contest(&contest_id, organizer_id, None, 0)
+        .await
+        .unwrap();
+
+    pool.delete_contest(&contest_id, organizer_id, 1000)
+        .await
+        .unwrap_err();
+    pool.delete_contest(&contest_id, owner_id, 1000)
+        .await
+        .unwrap();
+    pool.delete_contest(&contest_id, owner_id, 1000)
+        .await
+        .unwrap_err();
+
+    pool.get_single_contest_info(&contest_id).await.unwrap_err();
+    assert!(pool.get_own_contests(owner_id).await.unwrap().is_empty());
+    assert!(pool
+        .get_own_contests(organizer_id)
+        .await
+        .unwrap()
+        .is_empty());
+    assert!(pool
+        .get_participated_contests(organizer_id)
+        .await
+        .unwrap()
+        .is_empty());
+    assert!(pool.get_recent_contest_info().await.unwrap().is_empty());
+    pool.join_contest(&contest_id, owner_id, None, 0)
+        .await
+        .unwrap_err();
+    pool.clone_contest(&contest_id, owner_id, 0)
+        .await
+        .unwrap_err();
+
+    let deleted = pool.get_deleted_contests(owner_id, 2000).await.unwrap();
+    assert_eq!(deleted.len(), 1);
+    assert_eq!(deleted[0].id, contest_id);
+    assert!(pool
+        .get_deleted_contests(organizer_id, 2000)
+        .await
+        .unwrap()
+        .is_empty());
+
+    pool.restore_contest(&contest_id, organizer_id, 2000)
+        .await
+        .unwrap_err();
+    pool.restore_contest(&contest_id, owner_id, 2000)
+        .await
+        .unwrap();
+    pool.get_single_contest_info(&contest_id).await.unwrap();
+    assert_eq!(
+        pool.get_participated_contests(organizer_id)
+            .await
+            .unwrap()
+            .len(),
+        1
+    );
+
+    pool.delete_contest(&contest_id, owner_id, 3000)
+        .await
+        .unwrap();
+    let expired = 3000 + DELETED_RETENTION_SECOND;
+    pool.restore_contest(&contest_id, owner_id, expired)
+        .await
+        .unwrap_err();
+    assert_eq!(pool.purge_deleted_contests(expired - 1).await.unwrap(), 0);
+    assert_eq!(pool.purge_deleted_contests(expired).await.unwrap(), 1);
+    assert!(pool
+        .get_deleted_contests(owner_id, 3000)
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+#[async_std::test]
+async fn test_contest_teams() {
+    let pool = utils::initialize_and_connect_to_test_sql().await;
+    let owner_id = "owner_id";
+    let user_id = "user_id";
+    utils::setup_internal_user(&pool, owner_id, "owner").await;
+    utils::setup_internal_user(&pool, user_id, "user").await;
+
+    let contest_id = pool
+        .create_contest("title", "", owner_id, 0, 100, None, true, 0, 0)
+        .await
+        .unwrap();
+    assert!(pool
+        .get_contest_teams(&contest_id)
+        .await
+        .unwrap()
+        .is_empty());
+
+    let team_b = pool
+        .create_team(&contest_id, user_id, None, "team B", &["user", "bob"], 0)
+        .await
+        .unwrap();
+    let team_a = pool
+        .create_team(&contest_id, owner_id, None, "team A", &["Alice"], 0)
+        .await
+        .unwrap();
+    pool.create_team(&contest_id, user_id, None, "team A", &["carol"], 0)
+        .await
+        .unwrap_err();
+    pool.create_team(&contest_id, user_id, None, "team C", &["alice"], 0)
+        .await
+        .unwrap_err();
+    pool.create_team(&contest_id, user_id, None, "team C", &["BOB"], 0)
+        .await
+        .unwrap_err();
+    pool.create_team(&contest_id, user_id, None, "team C", &[], 0)
+        .await
+        .unwrap_err();
+    pool.create_team(
+        &contest_id,
+        user_id,
+        None,
+        "team C",
+        &vec!["member"; MAX_TEAM_MEMBER_NUM + 1],
+        0,
+    )
+    .await
+    .unwrap_err();
+
+    assert_eq!(
+        pool.get_contest_teams(&contest_id).await.unwrap(),
+        vec![
+            VirtualContestTeam {
+                id: team_a.clone(),
+                name: "team A".to_owned(),
+                internal_user_id: owner_id.to_owned(),
+                members: vec!["alice".to_owned()],
+            },
+            VirtualContestTeam {
+                id: team_b.clone(),
+                name: "team B".to_owned(),
+                internal_user_id: user_id.to_owned(),
+                members: vec!["bob".to_owned(), "user".to_owned()],
+            },
+        ]
+    );
+
+    pool.delete_team(&contest_id, &team_a).await.unwrap();
+    let teams = pool.get_contest_teams(&contest_id).await.unwrap();
+    assert_eq!(teams.len(), 1);
+    assert_eq!(teams[0].id, team_b);
+    pool.create_team(&contest_id, user_id, None, "team C", &["alice"], 0)
+        .await
+        .unwrap();
+
+    // A team takes a seat of the contest as well as an individual participant.
+    let registration = VirtualContestRegistration {
+        max_participants: Some(3),
+        registration_deadline_second: None,
+    };
+    pool.update_registration(&contest_id, &registration)
+        .await
+        .unwrap();
+    pool.join_contest(&contest_id, owner_id, None, 0)
+        .await
+        .unwrap();
+    let error = pool
+        .create_team(&contest_id, user_id, None, "team D", &["dave"], 0)
+        .await
+        .unwrap_err();
+    assert!(error.is::<NotJoinableError>());
+    let error = pool
+        .join_contest(&contest_id, user_id, None, 0)
+        .await
+        .unwrap_err();
+    assert!(error.is::<NotJoinableError>());
+
+    // Banning a user deletes the teams of the user, and the user cannot be a member of a new team.
+    let banned_id = "banned_id";
+    utils::setup_internal_user(&pool, banned_id, "Bob").await;
+    pool.ban_user(&contest_id, banned_id).await.unwrap();
+    let teams = pool.get_contest_teams(&contest_id).await.unwrap();
+    assert_eq!(teams.len(), 1);
+    assert_eq!(teams[0].name, "team C");
+    let error = pool
+        .create_team(&contest_id, user_id, None, "team B", &["bob", "dave"], 0)
+        .await
+        .unwrap_err();
+    assert!(error.is::<NotJoinableError>());
+    pool.create_team(&contest_id, user_id, None, "team D", &["dave"], 0)
+        .await
+        .unwrap();
+
+    // A user cannot take part both individually and as a member of a team.
+    let registration = VirtualContestRegistration {
+        max_participants: None,
+        registration_deadline_second: None,
+    };
+    pool.update_registration(&contest_id, &registration)
+        .await
+        .unwrap();
+    let error = pool
+        .create_team(&contest_id, user_id, None, "team E", &["OWNER"], 0)
+        .await
+        .unwrap_err();
+    assert!(error.is::<NotJoinableError>());
+    let dave_id = "dave_id";
+    utils::setup_internal_user(&pool, dave_id, "Dave").await;
+    let error = pool
+        .join_contest(&contest_id, dave_id, None, 0)
+        .await
+        .unwrap_err();
+    assert!(error.is::<NotJoinableError>());
+}
+
+#[async_std::test]
+async fn test_unknown_mode() {
+    let pool = utils::initialize_and_connect_to_test_sql().await;
+    let owner_id = "owner_id";
+    utils::setup_internal_user(&pool, owner_id, "owner").await;
+    let contest_id = pool
+        .create_contest("title", "", owner_id, 0, 100, None, true, 0, 0)
+        .await
+        .unwrap();
+    sqlx::query("UPDATE internal_virtual_contests SET mode = 'unknown' WHERE id = $1")
+        .bind(&contest_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let contests = pool.get_recent_contest_info().await.unwrap();
+    assert_eq!(contests.len(), 1);
+    assert_eq!(contests[0].mode, None);
+}
@@ -0,0 +1,125 @@
+use sql_client::internal::virtual_contest_manager::{VirtualContestItem, VirtualContestManager};
+use sql_client::internal::virtual_contest_series_manager::{
+    Recurrence, VirtualContestSeriesConfig, VirtualContestSeriesManager,
+};
+
+mod utils;
+
+fn config() -> VirtualContestSeriesConfig {
+    VirtualContestSeriesConfig {
+        title: "weekly".to_owned(),
+        memo: "memo".to_owned(),
+        mode: None,
+        is_public: true,
+        penalty_second: 300,
+        recurrence: Recurrence::Weekly,
+        first_start_epoch_second: 1000,
+        duration_second: 3600,
+        problem_count: 2,
+        min_point: Some(200.0),
+        max_point: Some(400.0),
+    }
+}
+
+#[async_std::test]
+async fn test_virtual_contest_series_manager() {
+    let pool = utils::initialize_and_connect_to_test_sql().await;
+    let owner_id = "owner_id";
+    let user_id = "user_id";
+    utils::setup_internal_user(&pool, owner_id, "owner").await;
+    utils::setup_internal_user(&pool, user_id, "user").await;
+
+    sqlx::query(
+        r"
+        INSERT INTO problems (id, contest_id, title)
+        VALUES
+            ('problem_a', 'contest', 'A'),
+            ('problem_b', 'contest', 'B'),
+            ('problem_c', 'contest', 'C'),
+            ('problem_d', 'contest', 'D'),
+            ('problem_e', 'contest', 'E')
+        ",
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+    sqlx::query(
+        r"
+        INSERT INTO points (problem_id, point)
+        VALUES
+            ('problem_a', 100),
+            ('problem_b', 200),
+            ('problem_c', 300),
+            ('problem_d', 400),
+            ('problem_e', 500)
+        ",
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    let series_id = pool.create_series(owner_id, &config()).await.unwrap();
+    let series = pool.get_own_series(owner_id).await.unwrap();
+    assert_eq!(series.len(), 1);
+    assert_eq!(series[0].id, series_id);
+    assert_eq!(series[0].owner_user_id, owner_id);
+    assert_eq!(series[0].config, config());
+    assert_eq!(pool.get_all_series().await.unwrap(), series);
+    assert!(pool.get_own_series(user_id).await.unwrap().is_empty());
+
+    let series = &series[0];
+    assert!(pool
+        .get_series_start_seconds(&series_id)
+        .await
+        .unwrap()
+        .is_empty());
+    assert_eq!(
+        pool.get_series_candidate_problems(series).await.unwrap(),
+        vec!["problem_b", "problem_c", "problem_d"]
+    );
+
+    let problem_ids = vec!["problem_d".to_owned(), "problem_b".to_owned()];
+    let contest_id = pool
+        .create_series_contest(series, 1000, &problem_ids)
+        .await
+        .unwrap();
+    let info = pool.get_single_contest_info(&contest_id).await.unwrap();
+    assert_eq!(info.title, "weekly");
+    assert_eq!(info.owner_user_id, owner_id);
+    assert_eq!(info.start_epoch_second, 1000);
+    assert_eq!(info.duration_second, 3600);
+    assert_eq!(info.penalty_second, 300);
+    assert_eq!(
+        pool.get_single_contest_problems(&contest_id).await.unwrap(),
+        vec![
+            VirtualContestItem {
+                id: "problem_d".to_owned(),
+                point: None,
+                order: Some(0),
+            },
+            VirtualContestItem {
+                id: "problem_b".to_owned(),
+                point: None,
+                order: Some(1),
+            },
+        ]
+    );
+    assert!(pool
+        .create_series_contest(series, 1000, &problem_ids)
+        .await
+        .is_err());
+
+    assert_eq!(
+        pool.get_series_start_seconds(&series_id).await.unwrap(),
+        vec![1000]
+    );
+    assert_eq!(
+        pool.get_series_candidate_problems(series).await.unwrap(),
+        vec!["problem_c"]
+    );
+
+    assert!(pool.delete_series(&series_id, user_id).await.is_err());
+    pool.delete_series(&series_id, owner_id).await.unwrap();
+    assert!(pool.get_all_series().await.unwrap().is_empty());
+    assert!(pool.get_single_contest_info(&contest_id).await.is_ok());
+}
@@ -0,0 +1,26 @@
+use chrono::Utc;
+use log::info;
+use sql_client::initialize_pool;
+use sql_client::internal::problem_list_manager::ProblemListManager;
+use sql_client::internal::virtual_contest_manager::VirtualContestManager;
+use std::env;
+use std::error::Error;
+
+#[async_std::main]
+async fn main() -> Result<(), Box<dyn Error>> {
+    simple_logger::init_with_level(log::Level::Info)?;
+    info!("Started!");
+
+    info!("Connecting to SQL ...");
+    let url = env::var("SQL_URL")?;
+    let conn = initialize_pool(&url).await?;
+
+    let now = Utc::now().timestamp();
+    let contest_count = conn.purge_deleted_contests(now).await?;
+    info!("Purged {} deleted contests.", contest_count);
+    let list_count = conn.purge_deleted_lists(now).await?;
+    info!("Purged {} deleted lists.", list_count);
+
+    info!("Finished");
+    Ok(())
+}
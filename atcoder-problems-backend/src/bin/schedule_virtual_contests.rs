@@ -0,0 +1,46 @@
+use anyhow::Result;
+use atcoder_problems_backend::scheduler::VirtualContestScheduler;
+use chrono::Utc;
+use rand::{thread_rng, Rng};
+use sql_client::initialize_pool;
+use std::time::{Duration, Instant};
+use std::{env, thread};
+
+const LOOP_INTERVAL_SECOND: u64 = 10 * 60;
+
+async fn schedule<R: Rng>(url: &str, rng: &mut R) -> Result<()> {
+    log::info!("Start scheduling...");
+    let pg_pool = initialize_pool(&url).await?;
+    let mut scheduler = VirtualContestScheduler::new(pg_pool, rng);
+    scheduler.schedule(Utc::now().timestamp()).await?;
+    log::info!("Finished scheduling");
+    Ok(())
+}
+
+#[async_std::main]
+async fn main() {
+    simple_logger::init_with_level(log::Level::Info).expect("Failed to initialize the logger.");
+    let url = env::var("SQL_URL").expect("SQL_URL must be set.");
+    log::info!("Started");
+
+    let mut rng = thread_rng();
+
+    loop {
+        log::info!("Start new loop...");
+        let now = Instant::now();
+
+        if let Err(e) = schedule(&url, &mut rng).await {
+            log::error!("{:?}", e);
+        }
+
+        let elapsed_secs = now.elapsed().as_secs();
+        log::info!("Elapsed {} sec.", elapsed_secs);
+        if elapsed_secs < LOOP_INTERVAL_SECOND {
+            let sleep_seconds = LOOP_INTERVAL_SECOND - elapsed_secs;
+            log::info!("Sleeping {} sec.", sleep_seconds);
+            thread::sleep(Duration::from_secs(sleep_seconds));
+        }
+
+        log::info!("Finished a loop");
+    }
+}
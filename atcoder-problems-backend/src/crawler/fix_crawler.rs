@@ -68,7 +68,7 @@ mod tests {
     use crate::crawler::utils::MockFetcher;
     use async_std::task::block_on;
     use async_trait::async_trait;
-    use sql_client::models::Submission;
+    use sql_client::models::{Submission, SubmissionSummary};
 
     const CURRENT_TIME: i64 = 100;
 
@@ -107,6 +107,12 @@ mod tests {
         async fn get_user_submission_count(&self, _: &str) -> Result<i64> {
             unimplemented!()
         }
+        async fn get_user_submission_summary(&self, _: &str) -> Result<SubmissionSummary> {
+            unimplemented!()
+        }
+        async fn get_time_submission_summary(&self, _: i64, _: i64) -> Result<SubmissionSummary> {
+            unimplemented!()
+        }
         async fn update_submissions(&self, _: &[Submission]) -> Result<usize> {
             Ok(0)
         }
@@ -55,7 +55,7 @@ mod tests {
     use crate::crawler::utils::MockFetcher;
     use async_std::task::block_on;
     use async_trait::async_trait;
-    use sql_client::models::{Contest, Problem, Submission};
+    use sql_client::models::{Contest, Problem, Submission, SubmissionSummary};
     use sql_client::submission_client::SubmissionRequest;
 
     #[test]
@@ -95,6 +95,16 @@ mod tests {
             async fn get_user_submission_count(&self, _: &str) -> Result<i64> {
                 unimplemented!()
             }
+            async fn get_user_submission_summary(&self, _: &str) -> Result<SubmissionSummary> {
+                unimplemented!()
+            }
+            async fn get_time_submission_summary(
+                &self,
+                _: i64,
+                _: i64,
+            ) -> Result<SubmissionSummary> {
+                unimplemented!()
+            }
 
             async fn update_submissions(&self, submissions: &[Submission]) -> Result<usize> {
                 assert_eq!(submissions.len(), 2);
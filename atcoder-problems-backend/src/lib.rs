@@ -1,4 +1,5 @@
 pub mod crawler;
 pub mod s3;
+pub mod scheduler;
 pub mod server;
 pub mod utils;
@@ -0,0 +1,204 @@
+use anyhow::{bail, Result};
+use rand::seq::SliceRandom;
+use rand::Rng;
+use sql_client::internal::virtual_contest_series_manager::{
+    VirtualContestSeries, VirtualContestSeriesManager,
+};
+
+/// Contests of a series are created this long before they start.
+pub const SCHEDULE_AHEAD_SECOND: i64 = 7 * 24 * 3600;
+
+pub struct VirtualContestScheduler<'a, P, R> {
+    db_pool: P,
+    rng: &'a mut R,
+}
+
+impl<'a, P, R> VirtualContestScheduler<'a, P, R>
+where
+    P: VirtualContestSeriesManager + Sync,
+    R: Rng,
+{
+    pub fn new(db_pool: P, rng: &'a mut R) -> Self {
+        Self { db_pool, rng }
+    }
+
+    /// Schedules the upcoming contests of every series. A failure of a series is logged and does
+    /// not stop the other series, and the run fails at the end if any series failed.
+    pub async fn schedule(&mut self, now: i64) -> Result<()> {
+        log::info!("Loading series ...");
+        let series_list = self.db_pool.get_all_series().await?;
+        log::info!("Loaded {} series", series_list.len());
+
+        let mut failed = 0;
+        for series in series_list.iter() {
+            if let Err(e) = self.schedule_series(series, now).await {
+                log::error!("Failed to schedule series {}: {:?}", series.id, e);
+                failed += 1;
+            }
+        }
+        if failed > 0 {
+            bail!(
+                "Failed to schedule {} of {} series",
+                failed,
+                series_list.len()
+            );
+        }
+        Ok(())
+    }
+
+    async fn schedule_series(&mut self, series: &VirtualContestSeries, now: i64) -> Result<()> {
+        let scheduled = self.db_pool.get_series_start_seconds(&series.id).await?;
+        for start_epoch_second in upcoming_start_seconds(series, now) {
+            if scheduled.contains(&start_epoch_second) {
+                continue;
+            }
+
+            let candidates = self.db_pool.get_series_candidate_problems(series).await?;
+            if candidates.is_empty() {
+                log::warn!("No problem is left for series {}", series.id);
+                break;
+            }
+            let problem_ids = candidates
+                .choose_multiple(self.rng, series.config.problem_count as usize)
+                .cloned()
+                .collect::<Vec<_>>();
+
+            log::info!(
+                "Creating a contest of series {} at {} ...",
+                series.id,
+                start_epoch_second
+            );
+            let contest_id = self
+                .db_pool
+                .create_series_contest(series, start_epoch_second, &problem_ids)
+                .await?;
+            log::info!("Created {}", contest_id);
+        }
+        Ok(())
+    }
+}
+
+/// Returns the start times of the contests in the series which start between `now` and
+/// `now + SCHEDULE_AHEAD_SECOND`.
+pub fn upcoming_start_seconds(series: &VirtualContestSeries, now: i64) -> Vec<i64> {
+    let first = series.config.first_start_epoch_second;
+    let interval = series.config.recurrence.interval_second();
+    let skipped = if now > first {
+        (now - first + interval - 1) / interval
+    } else {
+        0
+    };
+    (skipped..)
+        .map(|i| first + i * interval)
+        .take_while(|&start| start <= now + SCHEDULE_AHEAD_SECOND)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_std::task::block_on;
+    use async_trait::async_trait;
+    use sql_client::internal::virtual_contest_series_manager::{
+        Recurrence, VirtualContestSeriesConfig,
+    };
+    use std::sync::Mutex;
+
+    fn series(recurrence: Recurrence, first_start_epoch_second: i64) -> VirtualContestSeries {
+        VirtualContestSeries {
+            id: "series".to_owned(),
+            owner_user_id: "user".to_owned(),
+            config: VirtualContestSeriesConfig {
+                title: String::new(),
+                memo: String::new(),
+                mode: None,
+                is_public: true,
+                penalty_second: 0,
+                recurrence,
+                first_start_epoch_second,
+                duration_second: 3600,
+                problem_count: 3,
+                min_point: None,
+                max_point: None,
+            },
+        }
+    }
+
+    #[test]
+    fn test_upcoming_start_seconds() {
+        let day = 24 * 3600;
+
+        let weekly = series(Recurrence::Weekly, 1000);
+        assert_eq!(upcoming_start_seconds(&weekly, 0), vec![1000]);
+        assert_eq!(
+            upcoming_start_seconds(&weekly, 1000),
+            vec![1000, 1000 + 7 * day]
+        );
+        assert_eq!(upcoming_start_seconds(&weekly, 1001), vec![1000 + 7 * day]);
+        assert!(upcoming_start_seconds(&weekly, 1000 - 7 * day - 1).is_empty());
+
+        let daily = series(Recurrence::Daily, 0);
+        let starts = upcoming_start_seconds(&daily, 10 * day + 1);
+        assert_eq!(starts.len(), 7);
+        assert_eq!(starts[0], 11 * day);
+        assert_eq!(starts[6], 17 * day);
+    }
+
+    struct MockDB {
+        created: Mutex<Vec<(String, i64)>>,
+    }
+
+    #[async_trait]
+    impl VirtualContestSeriesManager for MockDB {
+        async fn create_series(&self, _: &str, _: &VirtualContestSeriesConfig) -> Result<String> {
+            unimplemented!()
+        }
+        async fn delete_series(&self, _: &str, _: &str) -> Result<()> {
+            unimplemented!()
+        }
+        async fn get_own_series(&self, _: &str) -> Result<Vec<VirtualContestSeries>> {
+            unimplemented!()
+        }
+        async fn get_all_series(&self) -> Result<Vec<VirtualContestSeries>> {
+            let mut broken = series(Recurrence::Weekly, 1000);
+            broken.id = "broken".to_owned();
+            Ok(vec![broken, series(Recurrence::Weekly, 1000)])
+        }
+        async fn get_series_start_seconds(&self, series_id: &str) -> Result<Vec<i64>> {
+            if series_id == "broken" {
+                bail!("The series is broken.");
+            }
+            Ok(vec![])
+        }
+        async fn get_series_candidate_problems(
+            &self,
+            _: &VirtualContestSeries,
+        ) -> Result<Vec<String>> {
+            Ok(vec!["problem".to_owned()])
+        }
+        async fn create_series_contest(
+            &self,
+            series: &VirtualContestSeries,
+            start_epoch_second: i64,
+            _: &[String],
+        ) -> Result<String> {
+            let mut created = self.created.lock().unwrap();
+            created.push((series.id.clone(), start_epoch_second));
+            Ok("contest".to_owned())
+        }
+    }
+
+    #[test]
+    fn test_schedule_after_failure() {
+        let db = MockDB {
+            created: Mutex::new(Vec::new()),
+        };
+        let mut rng = rand::thread_rng();
+        let mut scheduler = VirtualContestScheduler::new(db, &mut rng);
+        assert!(block_on(scheduler.schedule(0)).is_err());
+        assert_eq!(
+            *scheduler.db_pool.created.lock().unwrap(),
+            vec![("series".to_owned(), 1000)]
+        );
+    }
+}
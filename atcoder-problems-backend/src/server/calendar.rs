@@ -0,0 +1,196 @@
+use crate::server::utils::RequestUnpack;
+use crate::server::{AppData, Authentication, CommonResponse};
+
+use chrono::{TimeZone, Utc};
+use sql_client::internal::user_manager::UserManager;
+use sql_client::internal::virtual_contest_manager::{VirtualContestInfo, VirtualContestManager};
+use std::collections::BTreeMap;
+use tide::{Request, Response, Result, StatusCode};
+
+const CONTEST_URL_PREFIX: &str = "https://kenkoooo.com/atcoder/#/contest/show/";
+const MAX_LINE_OCTETS: usize = 75;
+
+/// Formats the contests as an iCalendar (RFC 5545) feed.
+pub(crate) fn format_calendar(name: &str, contests: &[VirtualContestInfo], now: i64) -> String {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_owned(),
+        "VERSION:2.0".to_owned(),
+        "PRODID:-//AtCoder Problems//Virtual Contests//EN".to_owned(),
+        "CALSCALE:GREGORIAN".to_owned(),
+        format!("X-WR-CALNAME:{}", escape_text(name)),
+    ];
+    for contest in contests {
+        let url = format!("{}{}", CONTEST_URL_PREFIX, contest.id);
+        lines.push("BEGIN:VEVENT".to_owned());
+        lines.push(format!("UID:{}@kenkoooo.com", contest.id));
+        lines.push(format!("DTSTAMP:{}", format_time(now)));
+        lines.push(format!(
+            "DTSTART:{}",
+            format_time(contest.start_epoch_second)
+        ));
+        lines.push(format!(
+            "DTEND:{}",
+            format_time(contest.start_epoch_second + contest.duration_second)
+        ));
+        lines.push(format!("SUMMARY:{}", escape_text(&contest.title)));
+        if !contest.memo.is_empty() {
+            lines.push(format!("DESCRIPTION:{}", escape_text(&contest.memo)));
+        }
+        lines.push(format!("URL:{}", url));
+        lines.push("END:VEVENT".to_owned());
+    }
+    lines.push("END:VCALENDAR".to_owned());
+
+    lines
+        .iter()
+        .map(|line| fold_line(line) + "\r\n")
+        .collect::<String>()
+}
+
+fn format_time(epoch_second: i64) -> String {
+    Utc.timestamp(epoch_second, 0)
+        .format("%Y%m%dT%H%M%SZ")
+        .to_string()
+}
+
+fn escape_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+/// Splits a line longer than 75 octets into lines which start with a space.
+fn fold_line(line: &str) -> String {
+    let mut folded = String::new();
+    let mut octets = 0;
+    for c in line.chars() {
+        if octets + c.len_utf8() > MAX_LINE_OCTETS {
+            folded.push_str("\r\n ");
+            octets = 1;
+        }
+        folded.push(c);
+        octets += c.len_utf8();
+    }
+    folded
+}
+
+fn calendar_response(calendar: String) -> Response {
+    let mut response = Response::new(StatusCode::Ok);
+    response.set_content_type("text/calendar; charset=utf-8");
+    response.set_body(calendar);
+    response
+}
+
+pub(crate) async fn get_recent_calendar<A>(request: Request<AppData<A>>) -> Result<Response> {
+    let conn = request.state().pg_pool.clone();
+    let contests = conn.get_recent_contest_info().await?;
+    let calendar = format_calendar(
+        "AtCoder Problems Virtual Contests",
+        &contests,
+        Utc::now().timestamp(),
+    );
+    Ok(calendar_response(calendar).make_cors())
+}
+
+pub(crate) async fn get_user_calendar<A>(request: Request<AppData<A>>) -> Result<Response> {
+    let conn = request.state().pg_pool.clone();
+    let token = request.param("token")?;
+    let user_id = conn
+        .get_user_by_calendar_token(token)
+        .await?
+        .ok_or_else(|| tide::Error::from_str(StatusCode::NotFound, "Invalid calendar token."))?;
+
+    let own_contests = conn.get_own_contests(&user_id).await?;
+    let participated_contests = conn.get_participated_contests(&user_id).await?;
+    let contests = own_contests
+        .into_iter()
+        .chain(participated_contests)
+        .map(|contest| (contest.id.clone(), contest))
+        .collect::<BTreeMap<_, _>>();
+    let mut contests = contests.values().cloned().collect::<Vec<_>>();
+    contests.sort_by_key(|contest| contest.start_epoch_second);
+
+    let calendar = format_calendar("My Virtual Contests", &contests, Utc::now().timestamp());
+    Ok(calendar_response(calendar))
+}
+
+pub(crate) async fn get_calendar_token<A>(request: Request<AppData<A>>) -> Result<Response>
+where
+    A: Authentication + Clone + Send + Sync + 'static,
+{
+    let user_id = request.get_authorized_id().await?;
+    let conn = request.state().pg_pool.clone();
+    let token = conn.get_calendar_token(&user_id).await?;
+    let body = serde_json::json!({ "calendar_token": token });
+    let response = Response::json(&body)?;
+    Ok(response)
+}
+
+pub(crate) async fn generate_calendar_token<A>(request: Request<AppData<A>>) -> Result<Response>
+where
+    A: Authentication + Clone + Send + Sync + 'static,
+{
+    let user_id = request.get_authorized_id().await?;
+    let conn = request.state().pg_pool.clone();
+    let token = conn.generate_calendar_token(&user_id).await?;
+    let body = serde_json::json!({ "calendar_token": token });
+    let response = Response::json(&body)?;
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_calendar() {
+        let contests = vec![VirtualContestInfo {
+            id: "contest_id".to_owned(),
+            title: "Weekly, #1; easy".to_owned(),
+            memo: "line1\nline2".to_owned(),
+            owner_user_id: "owner".to_owned(),
+            start_epoch_second: 1_600_000_000,
+            duration_second: 3600,
+            mode: None,
+            is_public: true,
+            penalty_second: 0,
+            freeze_second: 0,
+        }];
+        let calendar = format_calendar("name", &contests, 1_500_000_000);
+        assert_eq!(
+            calendar,
+            [
+                "BEGIN:VCALENDAR",
+                "VERSION:2.0",
+                "PRODID:-//AtCoder Problems//Virtual Contests//EN",
+                "CALSCALE:GREGORIAN",
+                "X-WR-CALNAME:name",
+                "BEGIN:VEVENT",
+                "UID:contest_id@kenkoooo.com",
+                "DTSTAMP:20170714T024000Z",
+                "DTSTART:20200913T122640Z",
+                "DTEND:20200913T132640Z",
+                "SUMMARY:Weekly\\, #1\\; easy",
+                "DESCRIPTION:line1\\nline2",
+                "URL:https://kenkoooo.com/atcoder/#/contest/show/contest_id",
+                "END:VEVENT",
+                "END:VCALENDAR",
+                "",
+            ]
+            .join("\r\n")
+        );
+    }
+
+    #[test]
+    fn test_fold_line() {
+        let line = "あ".repeat(30);
+        let folded = fold_line(&line);
+        let lines = folded.split("\r\n").collect::<Vec<_>>();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0], "あ".repeat(25));
+        assert_eq!(lines[1], format!(" {}", "あ".repeat(5)));
+        assert_eq!(fold_line("short"), "short");
+    }
+}
@@ -0,0 +1,133 @@
+/// Formats the records as CSV (RFC 4180), quoting the fields only when needed.
+pub(crate) fn format_csv<R, F>(records: R) -> String
+where
+    R: IntoIterator,
+    R::Item: IntoIterator<Item = F>,
+    F: AsRef<str>,
+{
+    records
+        .into_iter()
+        .map(|record| {
+            let fields = record
+                .into_iter()
+                .map(|field| escape_field(field.as_ref()))
+                .collect::<Vec<_>>();
+            fields.join(",") + "\r\n"
+        })
+        .collect()
+}
+
+/// Prefixes the field with `'` if it starts with a character which spreadsheet applications take
+/// as the beginning of a formula, so that opening the file does not evaluate user input.
+pub(crate) fn neutralize_formula(field: &str) -> String {
+    if field.starts_with(&['=', '+', '-', '@', '\t', '\r'][..]) {
+        format!("'{}", field)
+    } else {
+        field.to_owned()
+    }
+}
+
+fn escape_field(field: &str) -> String {
+    if field.contains(&[',', '"', '\r', '\n'][..]) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_owned()
+    }
+}
+
+/// Parses CSV (RFC 4180) into records. Both CRLF and LF are accepted as line breaks, and empty
+/// lines are skipped. Returns `None` if a quoted field is malformed.
+pub(crate) fn parse_csv(text: &str) -> Option<Vec<Vec<String>>> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = text.trim_start_matches('\u{feff}').chars().peekable();
+    while let Some(c) = chars.next() {
+        if quoted {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => match chars.peek() {
+                    None | Some(',') | Some('\r') | Some('\n') => quoted = false,
+                    Some(_) => return None,
+                },
+                c => field.push(c),
+            }
+            continue;
+        }
+        match c {
+            '"' if field.is_empty() => quoted = true,
+            ',' => record.push(std::mem::take(&mut field)),
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\r' | '\n' => {
+                record.push(std::mem::take(&mut field));
+                if record.len() > 1 || !record[0].is_empty() {
+                    records.push(std::mem::take(&mut record));
+                } else {
+                    record.clear();
+                }
+            }
+            c => field.push(c),
+        }
+    }
+    if quoted {
+        return None;
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+    Some(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_csv() {
+        let records = vec![
+            vec!["problem_id", "memo"],
+            vec!["abc001_a", "plain"],
+            vec!["abc001_b", "a, \"quoted\"\nmemo"],
+        ];
+        assert_eq!(
+            format_csv(records),
+            "problem_id,memo\r\nabc001_a,plain\r\nabc001_b,\"a, \"\"quoted\"\"\nmemo\"\r\n"
+        );
+    }
+
+    #[test]
+    fn test_neutralize_formula() {
+        assert_eq!(neutralize_formula("abc001_a"), "abc001_a");
+        assert_eq!(neutralize_formula("a=b"), "a=b");
+        for field in ["=1+1", "+1", "-1", "@SUM(A1)", "\tx", "\rx"].iter() {
+            assert_eq!(neutralize_formula(field), format!("'{}", field));
+        }
+    }
+
+    #[test]
+    fn test_parse_csv() {
+        let text =
+            "problem_id,memo\r\nabc001_a,plain\n\nabc001_b,\"a, \"\"quoted\"\"\nmemo\"\nabc001_c,";
+        assert_eq!(
+            parse_csv(text).unwrap(),
+            vec![
+                vec!["problem_id", "memo"],
+                vec!["abc001_a", "plain"],
+                vec!["abc001_b", "a, \"quoted\"\nmemo"],
+                vec!["abc001_c", ""],
+            ]
+        );
+        assert_eq!(
+            parse_csv(&format_csv(vec![vec!["x,\"y\"", ""]])).unwrap(),
+            vec![vec!["x,\"y\"", ""]]
+        );
+        assert!(parse_csv("a,\"b").is_none());
+        assert!(parse_csv("a,\"b\"c").is_none());
+        assert!(parse_csv("").unwrap().is_empty());
+    }
+}
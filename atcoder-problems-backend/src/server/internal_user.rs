@@ -1,8 +1,10 @@
+use crate::server::user_info::get_streak_statistics;
 use crate::server::utils::RequestUnpack;
 use crate::server::{AppData, Authentication, CommonResponse};
 use serde::Deserialize;
 use sql_client::internal::user_manager::UserManager;
-use tide::{Request, Response, Result};
+use sql_client::streak::timezone_from_offset_minute;
+use tide::{Request, Response, Result, StatusCode};
 
 pub(crate) async fn update<A: Authentication + Clone + Send + Sync + 'static>(
     request: Request<AppData<A>>,
@@ -10,12 +12,22 @@ pub(crate) async fn update<A: Authentication + Clone + Send + Sync + 'static>(
     #[derive(Deserialize)]
     struct Q {
         atcoder_user_id: String,
+        timezone_offset: Option<i32>,
     }
     let user_id = request.get_authorized_id().await?;
     let conn = request.state().pg_pool.clone();
     let body = request.parse_body::<Q>().await?;
-    conn.update_internal_user_info(&user_id, &body.atcoder_user_id)
-        .await?;
+    if let Some(timezone_offset) = body.timezone_offset {
+        if timezone_from_offset_minute(timezone_offset).is_none() {
+            return Err(tide::Error::from_str(
+                StatusCode::BadRequest,
+                "Invalid timezone offset.",
+            ));
+        }
+    }
+    conn.update_internal_user_info(&user_id, &body.atcoder_user_id, body.timezone_offset)
+        .await
+        .map_err(|_| tide::Error::from_str(StatusCode::NotFound, "The user does not exist."))?;
     Ok(Response::empty_json())
 }
 
@@ -27,3 +39,25 @@ pub(crate) async fn get<A: Authentication + Clone + Send + Sync + 'static>(
     let info = conn.get_internal_user_info(&user_id).await?;
     Ok(Response::json(&info)?)
 }
+
+pub(crate) async fn get_streak<A: Authentication + Clone + Send + Sync + 'static>(
+    request: Request<AppData<A>>,
+) -> Result<Response> {
+    #[derive(Deserialize)]
+    struct Q {
+        user: Option<String>,
+        timezone_offset: Option<i32>,
+    }
+    let user_id = request.get_authorized_id().await?;
+    let conn = request.state().pg_pool.clone();
+    let query = request.query::<Q>()?;
+    let info = conn.get_internal_user_info(&user_id).await?;
+    let atcoder_user_id = query
+        .user
+        .or(info.atcoder_user_id)
+        .ok_or_else(|| tide::Error::from_str(StatusCode::BadRequest, "AtCoder ID is not set."))?;
+    let timezone_offset = query.timezone_offset.unwrap_or(info.timezone_offset);
+    let statistics = get_streak_statistics(&conn, atcoder_user_id, timezone_offset).await?;
+    let response = Response::json(&statistics)?;
+    Ok(response)
+}
@@ -1,28 +1,38 @@
 use crate::server::time_submissions::get_time_submissions;
 use crate::server::user_info::get_user_info;
 use crate::server::user_submissions::{
-    get_recent_submissions, get_user_submissions, get_users_time_submissions,
+    get_filtered_submissions, get_recent_submissions, get_user_submission_page,
+    get_user_submissions, get_users_time_submissions,
 };
 pub(crate) mod auth;
 use crate::server::middleware::LogMiddleware;
 use crate::server::problem_list::{
-    add_item, create_list, delete_item, delete_list, get_own_lists, get_single_list, update_item,
-    update_list,
+    add_editor, add_item, create_list, delete_item, delete_list, export_list, fork_list,
+    get_deleted_lists, get_editors, get_list_progress, get_own_lists, get_shared_lists,
+    get_single_list, import_list, remove_editor, reorder_items, restore_list, update_item,
+    update_item_tags, update_list,
 };
 use auth::get_token;
 pub use auth::{Authentication, GitHubAuthentication, GitHubUserResponse};
+use sql_client::models::SubmissionSummary;
 use sql_client::PgPool;
+use tide::http::conditional::ETag;
 use tide::{Result, StatusCode};
 
+pub(crate) mod calendar;
+pub(crate) mod csv;
 pub(crate) mod internal_user;
 pub(crate) mod middleware;
 pub(crate) mod problem_list;
 pub(crate) mod progress_reset;
+pub(crate) mod ranking;
+pub(crate) mod standings;
 pub(crate) mod time_submissions;
 pub(crate) mod user_info;
 pub(crate) mod user_submissions;
 pub(crate) mod utils;
 pub(crate) mod virtual_contest;
+pub(crate) mod virtual_contest_series;
 
 pub async fn run_server<A>(pg_pool: PgPool, authentication: A, port: u16) -> Result<()>
 where
@@ -37,15 +47,31 @@ where
         api.at("/list").nest({
             let mut api = tide::with_state(app_data.clone());
             api.at("/my").get_ah(get_own_lists);
+            api.at("/shared").get_ah(get_shared_lists);
             api.at("/get/:list_id").get_ah(get_single_list);
+            api.at("/progress/:list_id").get_ah(get_list_progress);
             api.at("/create").post_ah(create_list);
+            api.at("/fork").post_ah(fork_list);
+            api.at("/export/:list_id").get_ah(export_list);
+            api.at("/import").post_ah(import_list);
             api.at("/delete").post_ah(delete_list);
+            api.at("/restore").post_ah(restore_list);
+            api.at("/deleted").get_ah(get_deleted_lists);
             api.at("/update").post_ah(update_list);
             api.at("/item").nest({
                 let mut api = tide::with_state(app_data.clone());
                 api.at("/add").post_ah(add_item);
                 api.at("/update").post_ah(update_item);
                 api.at("/delete").post_ah(delete_item);
+                api.at("/reorder").post_ah(reorder_items);
+                api.at("/tags").post_ah(update_item_tags);
+                api
+            });
+            api.at("/editor").nest({
+                let mut api = tide::with_state(app_data.clone());
+                api.at("/list/:list_id").get_ah(get_editors);
+                api.at("/add").post_ah(add_editor);
+                api.at("/remove").post_ah(remove_editor);
                 api
             });
             api
@@ -55,16 +81,73 @@ where
             let mut api = tide::with_state(app_data.clone());
             api.at("/create").post_ah(virtual_contest::create_contest);
             api.at("/update").post_ah(virtual_contest::update_contest);
+            api.at("/clone").post_ah(virtual_contest::clone_contest);
+            api.at("/delete").post_ah(virtual_contest::delete_contest);
+            api.at("/restore").post_ah(virtual_contest::restore_contest);
+            api.at("/deleted")
+                .get_ah(virtual_contest::get_deleted_contests);
             api.at("/item/update")
                 .post_ah(virtual_contest::update_items);
             api.at("/get/:contest_id")
                 .get_ah(virtual_contest::get_single_contest);
+            api.at("/standings/:contest_id")
+                .get_ah(standings::get_standings);
+            api.at("/standings/export/:contest_id")
+                .get_ah(standings::export_standings_file);
+            api.at("/organizer/list/:contest_id")
+                .get_ah(virtual_contest::get_organizers);
+            api.at("/organizer/add")
+                .post_ah(virtual_contest::add_organizer);
+            api.at("/organizer/remove")
+                .post_ah(virtual_contest::remove_organizer);
+            api.at("/invite/get/:contest_id")
+                .get_ah(virtual_contest::get_invite_token);
+            api.at("/invite/generate")
+                .post_ah(virtual_contest::generate_invite_token);
+            api.at("/invite/revoke")
+                .post_ah(virtual_contest::revoke_invite_token);
+            api.at("/participant/list/:contest_id")
+                .get_ah(virtual_contest::get_participants);
+            api.at("/participant/remove")
+                .post_ah(virtual_contest::remove_participant);
+            api.at("/participant/ban")
+                .post_ah(virtual_contest::ban_participant);
+            api.at("/participant/unban")
+                .post_ah(virtual_contest::unban_participant);
+            api.at("/team/list/:contest_id")
+                .get_ah(virtual_contest::get_teams);
+            api.at("/team/create").post_ah(virtual_contest::create_team);
+            api.at("/team/delete").post_ah(virtual_contest::delete_team);
+            api.at("/registration/get/:contest_id")
+                .get_ah(virtual_contest::get_registration);
+            api.at("/registration/update")
+                .post_ah(virtual_contest::update_registration);
             api.at("/join").post_ah(virtual_contest::join_contest);
             api.at("/leave").post_ah(virtual_contest::leave_contest);
             api.at("/my").get_ah(virtual_contest::get_my_contests);
             api.at("/joined").get_ah(virtual_contest::get_participated);
             api.at("/recent")
                 .get_ah(virtual_contest::get_recent_contests);
+            api.at("/search").get_ah(virtual_contest::search_contests);
+            api.at("/series").nest({
+                let mut api = tide::with_state(app_data.clone());
+                api.at("/create")
+                    .post_ah(virtual_contest_series::create_series);
+                api.at("/delete")
+                    .post_ah(virtual_contest_series::delete_series);
+                api.at("/my").get_ah(virtual_contest_series::get_my_series);
+                api
+            });
+            api
+        });
+
+        api.at("/calendar").nest({
+            let mut api = tide::with_state(app_data.clone());
+            api.at("/recent").get_ah(calendar::get_recent_calendar);
+            api.at("/user/:token").get_ah(calendar::get_user_calendar);
+            api.at("/token").get_ah(calendar::get_calendar_token);
+            api.at("/token/generate")
+                .post_ah(calendar::generate_calendar_token);
             api
         });
 
@@ -72,6 +155,7 @@ where
             let mut api = tide::with_state(app_data.clone());
             api.at("/get").get_ah(internal_user::get);
             api.at("/update").post_ah(internal_user::update);
+            api.at("/streak").get_ah(internal_user::get_streak);
             api
         });
 
@@ -100,6 +184,33 @@ where
             api.at("/from/:from").get_ah(get_time_submissions);
             api.at("/recent").get_ah(get_recent_submissions);
             api.at("/users_and_time").get_ah(get_users_time_submissions);
+            api.at("/user/streak").get_ah(user_info::get_user_streak);
+            api.at("/ranking").nest({
+                let mut api = tide::with_state(app_data.clone());
+                api.at("/ac").get_ah(ranking::get_ac_ranking);
+                api.at("/ac/around")
+                    .get_ah(ranking::get_ac_ranking_around_user);
+                api.at("/sum").get_ah(ranking::get_rated_point_sum_ranking);
+                api.at("/sum/around")
+                    .get_ah(ranking::get_rated_point_sum_ranking_around_user);
+                api.at("/streak").get_ah(ranking::get_streak_ranking);
+                api.at("/streak/around")
+                    .get_ah(ranking::get_streak_ranking_around_user);
+                api.at("/current_streak")
+                    .get_ah(ranking::get_current_streak_ranking);
+                api.at("/current_streak/around")
+                    .get_ah(ranking::get_current_streak_ranking_around_user);
+                api.at("/lang").get_ah(ranking::get_language_ranking);
+                api.at("/lang/around")
+                    .get_ah(ranking::get_language_ranking_around_user);
+                api
+            });
+            api
+        });
+        api.at("/v4").nest({
+            let mut api = tide::with_state(app_data.clone());
+            api.at("/user/submissions").get_ah(get_user_submission_page);
+            api.at("/submissions").get_ah(get_filtered_submissions);
             api
         });
         api
@@ -115,7 +226,9 @@ pub(crate) trait CommonResponse {
     where
         Self: Sized;
     fn empty_json() -> Self;
+    fn not_modified() -> Self;
     fn make_cors(self) -> Self;
+    fn with_validators(self, summary: &SubmissionSummary) -> Self;
 }
 
 impl CommonResponse for tide::Response {
@@ -138,11 +251,26 @@ impl CommonResponse for tide::Response {
             .body("{}")
             .build()
     }
+    fn not_modified() -> Self {
+        Self::new(StatusCode::NotModified)
+    }
     fn make_cors(self) -> Self {
         let mut response = self;
         response.insert_header(tide::http::headers::ACCESS_CONTROL_ALLOW_ORIGIN, "*");
         response
     }
+    fn with_validators(self, summary: &SubmissionSummary) -> Self {
+        let mut response = self;
+        submission_etag(summary).apply(&mut response);
+        response
+    }
+}
+
+/// Every insert and rejudge takes a new `update_id`, so the largest one changes whenever the
+/// submissions do. No `Last-Modified` is sent because the submission times do not move on a
+/// rejudge.
+pub(crate) fn submission_etag(summary: &SubmissionSummary) -> ETag {
+    ETag::new(summary.max_update_id.to_string())
 }
 
 pub(crate) struct AppData<A> {
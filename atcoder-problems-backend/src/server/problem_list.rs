@@ -1,8 +1,61 @@
+use crate::server::csv::{format_csv, parse_csv};
 use crate::server::utils::RequestUnpack;
 use crate::server::{AppData, Authentication, CommonResponse};
-use serde::Deserialize;
-use sql_client::internal::problem_list_manager::ProblemListManager;
-use tide::{Request, Response, Result};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sql_client::internal::problem_list_manager::{
+    ImportRejection, ProblemList, ProblemListManager,
+};
+use sql_client::internal::user_manager::UserManager;
+use sql_client::PgPool;
+use tide::{Request, Response, Result, StatusCode};
+
+#[derive(Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+enum Format {
+    Csv,
+    Json,
+}
+
+fn forbidden(message: &'static str) -> tide::Error {
+    tide::Error::from_str(StatusCode::Forbidden, message)
+}
+
+async fn get_existing_list(conn: &PgPool, internal_list_id: &str) -> Result<ProblemList> {
+    conn.get_single_list(internal_list_id)
+        .await
+        .map_err(|_| tide::Error::from_str(StatusCode::NotFound, "The list does not exist."))
+}
+
+/// Returns the list if `user_id` is allowed to edit its items, i.e. `404` if the list does not
+/// exist and `403` if the user is neither the owner nor an editor.
+async fn get_editable_list(
+    conn: &PgPool,
+    internal_list_id: &str,
+    user_id: &str,
+) -> Result<ProblemList> {
+    let list = get_existing_list(conn, internal_list_id).await?;
+    if list.internal_user_id != user_id {
+        let editors = conn.get_list_editors(internal_list_id).await?;
+        if !editors.iter().any(|e| e.internal_user_id == user_id) {
+            return Err(forbidden("Only the editors can edit the list."));
+        }
+    }
+    Ok(list)
+}
+
+/// Returns the list if `user_id` is the owner of it.
+async fn get_owned_list(
+    conn: &PgPool,
+    internal_list_id: &str,
+    user_id: &str,
+) -> Result<ProblemList> {
+    let list = get_existing_list(conn, internal_list_id).await?;
+    if list.internal_user_id != user_id {
+        return Err(forbidden("Only the owner can do this."));
+    }
+    Ok(list)
+}
 
 pub(crate) async fn get_own_lists<A>(request: Request<AppData<A>>) -> Result<Response>
 where
@@ -15,6 +68,17 @@ where
     Ok(response)
 }
 
+pub(crate) async fn get_shared_lists<A>(request: Request<AppData<A>>) -> Result<Response>
+where
+    A: Authentication + Clone + Send + Sync + 'static,
+{
+    let user_id = request.get_authorized_id().await?;
+    let conn = request.state().pg_pool.clone();
+    let list = conn.get_shared_lists(&user_id).await?;
+    let response = Response::json(&list)?;
+    Ok(response)
+}
+
 pub(crate) async fn get_single_list<A>(request: Request<AppData<A>>) -> Result<Response>
 where
     A: Authentication + Clone + Send + Sync + 'static,
@@ -45,6 +109,25 @@ where
     Ok(response)
 }
 
+pub(crate) async fn fork_list<A>(request: Request<AppData<A>>) -> Result<Response>
+where
+    A: Authentication + Clone + Send + Sync + 'static,
+{
+    #[derive(Deserialize)]
+    struct Q {
+        internal_list_id: String,
+    }
+    let internal_user_id = request.get_authorized_id().await?;
+    let conn = request.state().pg_pool.clone();
+    let query = request.parse_body::<Q>().await?;
+    let internal_list_id = conn
+        .fork_list(&query.internal_list_id, &internal_user_id)
+        .await?;
+    let body = serde_json::json!({ "internal_list_id": internal_list_id });
+    let response = Response::json(&body)?;
+    Ok(response)
+}
+
 pub(crate) async fn delete_list<A>(request: Request<AppData<A>>) -> Result<Response>
 where
     A: Authentication + Clone + Send + Sync + 'static,
@@ -53,14 +136,60 @@ where
     struct Q {
         internal_list_id: String,
     }
-    request.get_authorized_id().await?;
+    let internal_user_id = request.get_authorized_id().await?;
+    let conn = request.state().pg_pool.clone();
+    let query = request.parse_body::<Q>().await?;
+    conn.delete_list(
+        &query.internal_list_id,
+        &internal_user_id,
+        Utc::now().timestamp(),
+    )
+    .await
+    .map_err(|_| tide::Error::from_str(StatusCode::NotFound, "The list does not exist."))?;
+    let response = Response::empty_json();
+    Ok(response)
+}
+
+pub(crate) async fn restore_list<A>(request: Request<AppData<A>>) -> Result<Response>
+where
+    A: Authentication + Clone + Send + Sync + 'static,
+{
+    #[derive(Deserialize)]
+    struct Q {
+        internal_list_id: String,
+    }
+    let internal_user_id = request.get_authorized_id().await?;
     let conn = request.state().pg_pool.clone();
     let query = request.parse_body::<Q>().await?;
-    conn.delete_list(&query.internal_list_id).await?;
+    conn.restore_list(
+        &query.internal_list_id,
+        &internal_user_id,
+        Utc::now().timestamp(),
+    )
+    .await
+    .map_err(|_| {
+        tide::Error::from_str(
+            StatusCode::NotFound,
+            "The list is not a restorable list of yours.",
+        )
+    })?;
     let response = Response::empty_json();
     Ok(response)
 }
 
+pub(crate) async fn get_deleted_lists<A>(request: Request<AppData<A>>) -> Result<Response>
+where
+    A: Authentication + Clone + Send + Sync + 'static,
+{
+    let user_id = request.get_authorized_id().await?;
+    let conn = request.state().pg_pool.clone();
+    let list = conn
+        .get_deleted_lists(&user_id, Utc::now().timestamp())
+        .await?;
+    let response = Response::json(&list)?;
+    Ok(response)
+}
+
 pub(crate) async fn update_list<A>(request: Request<AppData<A>>) -> Result<Response>
 where
     A: Authentication + Clone + Send + Sync + 'static,
@@ -70,9 +199,10 @@ where
         internal_list_id: String,
         name: String,
     }
-    request.get_authorized_id().await?;
+    let internal_user_id = request.get_authorized_id().await?;
     let conn = request.state().pg_pool.clone();
     let query = request.parse_body::<Q>().await?;
+    get_owned_list(&conn, &query.internal_list_id, &internal_user_id).await?;
     conn.update_list(&query.internal_list_id, &query.name)
         .await?;
     let response = Response::empty_json();
@@ -88,9 +218,10 @@ where
         internal_list_id: String,
         problem_id: String,
     }
-    request.get_authorized_id().await?;
+    let internal_user_id = request.get_authorized_id().await?;
     let conn = request.state().pg_pool.clone();
     let query = request.parse_body::<Q>().await?;
+    get_editable_list(&conn, &query.internal_list_id, &internal_user_id).await?;
     conn.add_item(&query.internal_list_id, &query.problem_id)
         .await?;
     let response = Response::empty_json();
@@ -107,9 +238,10 @@ where
         problem_id: String,
         memo: String,
     }
-    request.get_authorized_id().await?;
+    let internal_user_id = request.get_authorized_id().await?;
     let conn = request.state().pg_pool.clone();
     let query = request.parse_body::<Q>().await?;
+    get_editable_list(&conn, &query.internal_list_id, &internal_user_id).await?;
     conn.update_item(&query.internal_list_id, &query.problem_id, &query.memo)
         .await?;
     let response = Response::empty_json();
@@ -125,11 +257,281 @@ where
         internal_list_id: String,
         problem_id: String,
     }
-    request.get_authorized_id().await?;
+    let internal_user_id = request.get_authorized_id().await?;
     let conn = request.state().pg_pool.clone();
     let query = request.parse_body::<Q>().await?;
+    get_editable_list(&conn, &query.internal_list_id, &internal_user_id).await?;
     conn.delete_item(&query.internal_list_id, &query.problem_id)
         .await?;
     let response = Response::empty_json();
     Ok(response)
 }
+
+pub(crate) async fn reorder_items<A>(request: Request<AppData<A>>) -> Result<Response>
+where
+    A: Authentication + Clone + Send + Sync + 'static,
+{
+    #[derive(Deserialize)]
+    struct Q {
+        internal_list_id: String,
+        problem_ids: Vec<String>,
+    }
+    let internal_user_id = request.get_authorized_id().await?;
+    let conn = request.state().pg_pool.clone();
+    let query = request.parse_body::<Q>().await?;
+    get_editable_list(&conn, &query.internal_list_id, &internal_user_id).await?;
+    let problem_ids = query
+        .problem_ids
+        .iter()
+        .map(|s| s.as_str())
+        .collect::<Vec<_>>();
+    conn.reorder_items(&query.internal_list_id, &problem_ids)
+        .await
+        .map_err(|_| {
+            tide::Error::from_str(
+                StatusCode::BadRequest,
+                "The problems do not match the items of the list.",
+            )
+        })?;
+    let response = Response::empty_json();
+    Ok(response)
+}
+
+pub(crate) async fn update_item_tags<A>(request: Request<AppData<A>>) -> Result<Response>
+where
+    A: Authentication + Clone + Send + Sync + 'static,
+{
+    #[derive(Deserialize)]
+    struct Q {
+        internal_list_id: String,
+        problem_id: String,
+        tags: Vec<String>,
+    }
+    let internal_user_id = request.get_authorized_id().await?;
+    let conn = request.state().pg_pool.clone();
+    let query = request.parse_body::<Q>().await?;
+    get_editable_list(&conn, &query.internal_list_id, &internal_user_id).await?;
+    let tags = query.tags.iter().map(|s| s.as_str()).collect::<Vec<_>>();
+    conn.update_item_tags(&query.internal_list_id, &query.problem_id, &tags)
+        .await
+        .map_err(|_| {
+            tide::Error::from_str(
+                StatusCode::BadRequest,
+                "The item does not exist or the tags are invalid.",
+            )
+        })?;
+    let response = Response::empty_json();
+    Ok(response)
+}
+
+pub(crate) async fn get_list_progress<A>(request: Request<AppData<A>>) -> Result<Response>
+where
+    A: Authentication + Clone + Send + Sync + 'static,
+{
+    #[derive(Deserialize)]
+    struct Query {
+        user: String,
+    }
+    let list_id = request.param("list_id")?;
+    let query = request.query::<Query>()?;
+    let conn = request.state().pg_pool.clone();
+    let progress = conn
+        .get_list_progress(list_id, &query.user)
+        .await
+        .map_err(|_| tide::Error::from_str(StatusCode::NotFound, "The list does not exist."))?;
+    let response = Response::json(&progress)?;
+    Ok(response)
+}
+
+pub(crate) async fn export_list<A>(request: Request<AppData<A>>) -> Result<Response>
+where
+    A: Authentication + Clone + Send + Sync + 'static,
+{
+    #[derive(Deserialize)]
+    struct Query {
+        format: Option<Format>,
+    }
+    let list_id = request.param("list_id")?;
+    let query = request.query::<Query>()?;
+    let conn = request.state().pg_pool.clone();
+    let list = conn.get_single_list(list_id).await?;
+
+    let response = match query.format.unwrap_or(Format::Json) {
+        Format::Csv => {
+            let mut records = vec![vec!["problem_id", "memo"]];
+            records.extend(
+                list.items
+                    .iter()
+                    .map(|item| vec![item.problem_id.as_str(), item.memo.as_str()]),
+            );
+            let mut response = Response::new(StatusCode::Ok);
+            response.set_content_type("text/csv; charset=utf-8");
+            response.insert_header(
+                "Content-Disposition",
+                format!(
+                    "attachment; filename=\"list-{}.csv\"",
+                    list.internal_list_id
+                ),
+            );
+            response.set_body(format_csv(records));
+            response
+        }
+        Format::Json => Response::json(&list)?,
+    };
+    Ok(response)
+}
+
+/// Imports the items in the body into the list of `internal_list_id`, or into a new list named
+/// `list_name` if it is not given. The body is either a CSV with a header which has `problem_id`
+/// and optionally `memo` columns, or a JSON object which has `items` like the exported one.
+pub(crate) async fn import_list<A>(mut request: Request<AppData<A>>) -> Result<Response>
+where
+    A: Authentication + Clone + Send + Sync + 'static,
+{
+    #[derive(Deserialize)]
+    struct Query {
+        format: Option<Format>,
+        internal_list_id: Option<String>,
+        list_name: Option<String>,
+    }
+    #[derive(Deserialize)]
+    struct ImportedItem {
+        problem_id: String,
+        #[serde(default)]
+        memo: String,
+    }
+    #[derive(Deserialize)]
+    struct ImportedList {
+        items: Vec<ImportedItem>,
+    }
+    #[derive(Serialize)]
+    struct RejectedRow {
+        /// 1-indexed position of the item in the body, not counting the CSV header.
+        row: usize,
+        problem_id: String,
+        reason: ImportRejection,
+    }
+
+    let internal_user_id = request.get_authorized_id().await?;
+    let query = request.query::<Query>()?;
+    let conn = request.state().pg_pool.clone();
+    let body = request.body_string().await?;
+    let items = match query.format.unwrap_or(Format::Json) {
+        Format::Csv => parse_csv_items(&body).ok_or_else(|| {
+            tide::Error::from_str(
+                StatusCode::BadRequest,
+                "The CSV must have a header with `problem_id`.",
+            )
+        })?,
+        Format::Json => serde_json::from_str::<ImportedList>(&body)
+            .map_err(|_| tide::Error::from_str(StatusCode::BadRequest, "Invalid JSON."))?
+            .items
+            .into_iter()
+            .map(|item| (item.problem_id.trim().to_owned(), item.memo))
+            .collect(),
+    };
+    let items = items
+        .iter()
+        .map(|(problem_id, memo)| (problem_id.as_str(), memo.as_str()))
+        .collect::<Vec<_>>();
+
+    let (internal_list_id, rejected) = match query.internal_list_id {
+        Some(internal_list_id) => {
+            get_editable_list(&conn, &internal_list_id, &internal_user_id).await?;
+            let rejected = conn.import_items(&internal_list_id, &items).await?;
+            (internal_list_id, rejected)
+        }
+        None => {
+            let list_name = query.list_name.unwrap_or_default();
+            conn.create_list_with_items(&internal_user_id, &list_name, &items)
+                .await?
+        }
+    };
+    let body = serde_json::json!({
+        "internal_list_id": internal_list_id,
+        "imported": items.len() - rejected.len(),
+        "rejected": rejected
+            .into_iter()
+            .map(|item| RejectedRow {
+                row: item.index + 1,
+                problem_id: item.problem_id,
+                reason: item.reason,
+            })
+            .collect::<Vec<_>>(),
+    });
+    let response = Response::json(&body)?;
+    Ok(response)
+}
+
+/// Reads the pairs of a problem id and a memo from a CSV with a header.
+fn parse_csv_items(text: &str) -> Option<Vec<(String, String)>> {
+    let records = parse_csv(text)?;
+    let (header, records) = records.split_first()?;
+    let column = |name: &str| header.iter().position(|field| field.trim() == name);
+    let problem_id_column = column("problem_id")?;
+    let memo_column = column("memo");
+    let items = records
+        .iter()
+        .map(|record| {
+            let field = |i: usize| record.get(i).map(|s| s.trim()).unwrap_or_default();
+            let problem_id = field(problem_id_column).to_owned();
+            let memo = memo_column.map(|i| field(i).to_owned()).unwrap_or_default();
+            (problem_id, memo)
+        })
+        .collect();
+    Some(items)
+}
+
+pub(crate) async fn get_editors<A>(request: Request<AppData<A>>) -> Result<Response> {
+    let conn = request.state().pg_pool.clone();
+    let list_id = request.param("list_id")?;
+    get_existing_list(&conn, list_id).await?;
+    let editors = conn.get_list_editors(list_id).await?;
+    let response = Response::json(&editors)?;
+    Ok(response)
+}
+
+pub(crate) async fn add_editor<A>(request: Request<AppData<A>>) -> Result<Response>
+where
+    A: Authentication + Clone + Send + Sync + 'static,
+{
+    #[derive(Deserialize)]
+    struct Q {
+        internal_list_id: String,
+        user_id: String,
+    }
+    let internal_user_id = request.get_authorized_id().await?;
+    let conn = request.state().pg_pool.clone();
+    let query = request.parse_body::<Q>().await?;
+    get_owned_list(&conn, &query.internal_list_id, &internal_user_id).await?;
+    conn.get_internal_user_info(&query.user_id)
+        .await
+        .map_err(|_| tide::Error::from_str(StatusCode::NotFound, "The user does not exist."))?;
+    conn.add_list_editor(&query.internal_list_id, &query.user_id)
+        .await?;
+    let response = Response::empty_json();
+    Ok(response)
+}
+
+pub(crate) async fn remove_editor<A>(request: Request<AppData<A>>) -> Result<Response>
+where
+    A: Authentication + Clone + Send + Sync + 'static,
+{
+    #[derive(Deserialize)]
+    struct Q {
+        internal_list_id: String,
+        user_id: String,
+    }
+    let internal_user_id = request.get_authorized_id().await?;
+    let conn = request.state().pg_pool.clone();
+    let query = request.parse_body::<Q>().await?;
+    let list = get_existing_list(&conn, &query.internal_list_id).await?;
+    // Editors can step down by themselves.
+    if list.internal_user_id != internal_user_id && query.user_id != internal_user_id {
+        return Err(forbidden("Only the owner can remove other editors."));
+    }
+    conn.remove_list_editor(&query.internal_list_id, &query.user_id)
+        .await?;
+    let response = Response::empty_json();
+    Ok(response)
+}
@@ -0,0 +1,263 @@
+use crate::server::{AppData, CommonResponse};
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sql_client::accepted_count::AcceptedCountClient;
+use sql_client::language_count::LanguageCountClient;
+use sql_client::rated_point_sum::RatedPointSumClient;
+use sql_client::streak::{jst, ongoing_streak_since, StreakClient};
+use tide::{Request, Response, Result, StatusCode};
+
+const DEFAULT_RANKING_LIMIT: i64 = 100;
+const MAX_RANKING_LIMIT: i64 = 1000;
+
+#[derive(Deserialize)]
+struct RangeQuery {
+    offset: Option<i64>,
+    limit: Option<i64>,
+}
+
+impl RangeQuery {
+    fn offset(&self) -> i64 {
+        self.offset.unwrap_or(0).max(0)
+    }
+    fn limit(&self) -> i64 {
+        clamp_limit(self.limit)
+    }
+}
+
+#[derive(Deserialize)]
+struct UserQuery {
+    user: String,
+    limit: Option<i64>,
+}
+
+impl UserQuery {
+    fn limit(&self) -> i64 {
+        clamp_limit(self.limit)
+    }
+}
+
+/// A page of a ranking which contains the requested user.
+#[derive(Serialize)]
+struct RankingAroundUser<T> {
+    /// The number of users who are strictly ranked above the requested user.
+    rank: i64,
+    offset: i64,
+    entries: Vec<T>,
+}
+
+fn clamp_limit(limit: Option<i64>) -> i64 {
+    limit
+        .unwrap_or(DEFAULT_RANKING_LIMIT)
+        .clamp(1, MAX_RANKING_LIMIT)
+}
+
+/// Returns the offset of the page of `limit` entries centered at `position`.
+fn centered_offset(position: i64, limit: i64) -> i64 {
+    (position - limit / 2).max(0)
+}
+
+fn user_not_found(user_id: &str) -> tide::Error {
+    tide::Error::from_str(
+        StatusCode::NotFound,
+        format!("{} is not in the ranking.", user_id),
+    )
+}
+
+pub(crate) async fn get_ac_ranking<A>(request: Request<AppData<A>>) -> Result<Response> {
+    let conn = request.state().pg_pool.clone();
+    let query = request.query::<RangeQuery>()?;
+    let ranking = conn
+        .load_accepted_count_in_range(query.offset(), query.limit())
+        .await?;
+    let response = Response::json(&ranking)?.make_cors();
+    Ok(response)
+}
+
+pub(crate) async fn get_ac_ranking_around_user<A>(
+    request: Request<AppData<A>>,
+) -> Result<Response> {
+    let conn = request.state().pg_pool.clone();
+    let query = request.query::<UserQuery>()?;
+    let count = conn
+        .get_users_accepted_count(&query.user)
+        .await
+        .ok_or_else(|| user_not_found(&query.user))?;
+    let rank = conn.get_accepted_count_rank(count).await?;
+    let position = conn.get_accepted_count_position(&query.user, count).await?;
+    let offset = centered_offset(position, query.limit());
+    let entries = conn
+        .load_accepted_count_in_range(offset, query.limit())
+        .await?;
+    let response = Response::json(&RankingAroundUser {
+        rank,
+        offset,
+        entries,
+    })?
+    .make_cors();
+    Ok(response)
+}
+
+pub(crate) async fn get_rated_point_sum_ranking<A>(
+    request: Request<AppData<A>>,
+) -> Result<Response> {
+    let conn = request.state().pg_pool.clone();
+    let query = request.query::<RangeQuery>()?;
+    let ranking = conn
+        .load_rated_point_sum_in_range(query.offset(), query.limit())
+        .await?;
+    let response = Response::json(&ranking)?.make_cors();
+    Ok(response)
+}
+
+pub(crate) async fn get_rated_point_sum_ranking_around_user<A>(
+    request: Request<AppData<A>>,
+) -> Result<Response> {
+    let conn = request.state().pg_pool.clone();
+    let query = request.query::<UserQuery>()?;
+    let point_sum = conn
+        .get_users_rated_point_sum(&query.user)
+        .await
+        .ok_or_else(|| user_not_found(&query.user))?;
+    let rank = conn.get_rated_point_sum_rank(point_sum).await?;
+    let position = conn
+        .get_rated_point_sum_position(&query.user, point_sum)
+        .await?;
+    let offset = centered_offset(position, query.limit());
+    let entries = conn
+        .load_rated_point_sum_in_range(offset, query.limit())
+        .await?;
+    let response = Response::json(&RankingAroundUser {
+        rank,
+        offset,
+        entries,
+    })?
+    .make_cors();
+    Ok(response)
+}
+
+pub(crate) async fn get_streak_ranking<A>(request: Request<AppData<A>>) -> Result<Response> {
+    let conn = request.state().pg_pool.clone();
+    let query = request.query::<RangeQuery>()?;
+    let ranking = conn
+        .load_streak_count_in_range(query.offset(), query.limit())
+        .await?;
+    let response = Response::json(&ranking)?.make_cors();
+    Ok(response)
+}
+
+pub(crate) async fn get_streak_ranking_around_user<A>(
+    request: Request<AppData<A>>,
+) -> Result<Response> {
+    let conn = request.state().pg_pool.clone();
+    let query = request.query::<UserQuery>()?;
+    let streak = conn
+        .get_users_streak_count(&query.user)
+        .await
+        .ok_or_else(|| user_not_found(&query.user))?;
+    let rank = conn.get_streak_count_rank(streak).await?;
+    let position = conn.get_streak_count_position(&query.user, streak).await?;
+    let offset = centered_offset(position, query.limit());
+    let entries = conn
+        .load_streak_count_in_range(offset, query.limit())
+        .await?;
+    let response = Response::json(&RankingAroundUser {
+        rank,
+        offset,
+        entries,
+    })?
+    .make_cors();
+    Ok(response)
+}
+
+pub(crate) async fn get_current_streak_ranking<A>(
+    request: Request<AppData<A>>,
+) -> Result<Response> {
+    let conn = request.state().pg_pool.clone();
+    let query = request.query::<RangeQuery>()?;
+    let since = ongoing_streak_since(&Utc::now(), &jst());
+    let ranking = conn
+        .load_current_streak_count_in_range(&since, query.offset(), query.limit())
+        .await?;
+    let response = Response::json(&ranking)?.make_cors();
+    Ok(response)
+}
+
+pub(crate) async fn get_current_streak_ranking_around_user<A>(
+    request: Request<AppData<A>>,
+) -> Result<Response> {
+    let conn = request.state().pg_pool.clone();
+    let query = request.query::<UserQuery>()?;
+    let since = ongoing_streak_since(&Utc::now(), &jst());
+    let streak = conn
+        .get_users_current_streak(&query.user)
+        .await
+        .filter(|s| s.last_ac_date >= since)
+        .ok_or_else(|| user_not_found(&query.user))?
+        .streak;
+    let rank = conn.get_current_streak_rank(&since, streak).await?;
+    let position = conn
+        .get_current_streak_position(&since, &query.user, streak)
+        .await?;
+    let offset = centered_offset(position, query.limit());
+    let entries = conn
+        .load_current_streak_count_in_range(&since, offset, query.limit())
+        .await?;
+    let response = Response::json(&RankingAroundUser {
+        rank,
+        offset,
+        entries,
+    })?
+    .make_cors();
+    Ok(response)
+}
+
+pub(crate) async fn get_language_ranking<A>(request: Request<AppData<A>>) -> Result<Response> {
+    #[derive(Deserialize)]
+    struct Query {
+        language: String,
+    }
+    let conn = request.state().pg_pool.clone();
+    let language = request.query::<Query>()?.language;
+    let query = request.query::<RangeQuery>()?;
+    let ranking = conn
+        .load_language_count_in_range(&language, query.offset(), query.limit())
+        .await?;
+    let response = Response::json(&ranking)?.make_cors();
+    Ok(response)
+}
+
+pub(crate) async fn get_language_ranking_around_user<A>(
+    request: Request<AppData<A>>,
+) -> Result<Response> {
+    #[derive(Deserialize)]
+    struct Query {
+        language: String,
+    }
+    let conn = request.state().pg_pool.clone();
+    let language = request.query::<Query>()?.language;
+    let query = request.query::<UserQuery>()?;
+    let count = conn
+        .get_users_language_count(&query.user)
+        .await?
+        .into_iter()
+        .find(|c| c.simplified_language == language)
+        .map(|c| c.problem_count)
+        .ok_or_else(|| user_not_found(&query.user))?;
+    let rank = conn.get_language_count_rank(&language, count).await?;
+    let position = conn
+        .get_language_count_position(&query.user, &language, count)
+        .await?;
+    let offset = centered_offset(position, query.limit());
+    let entries = conn
+        .load_language_count_in_range(&language, offset, query.limit())
+        .await?;
+    let response = Response::json(&RankingAroundUser {
+        rank,
+        offset,
+        entries,
+    })?
+    .make_cors();
+    Ok(response)
+}
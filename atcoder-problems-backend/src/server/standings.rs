@@ -0,0 +1,782 @@
+use crate::server::csv::{format_csv, neutralize_formula};
+use crate::server::utils::RequestUnpack;
+use crate::server::virtual_contest::{get_existing_contest, is_organizer};
+use crate::server::{AppData, Authentication, CommonResponse};
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sql_client::internal::virtual_contest_manager::{
+    VirtualContestInfo, VirtualContestItem, VirtualContestManager, VirtualContestMode,
+    VirtualContestTeam,
+};
+use sql_client::models::Submission;
+use sql_client::submission_client::{SubmissionClient, SubmissionRequest};
+use sql_client::PgPool;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet};
+use tide::{Request, Response, Result, StatusCode};
+
+#[derive(Serialize, Debug, PartialEq, Clone)]
+pub(crate) struct ProblemResult {
+    pub(crate) problem_id: String,
+    pub(crate) point: f64,
+    pub(crate) accepted: bool,
+    pub(crate) trials: i64,
+    /// The number of submissions before the one which gave the current point.
+    pub(crate) penalties: i64,
+    /// Seconds from the start of the contest to the submission which gave the current point.
+    pub(crate) elapsed_second: i64,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+pub(crate) struct ParticipantStanding {
+    pub(crate) rank: usize,
+    /// The AtCoder ID of the participant, or the name of the team.
+    pub(crate) user_id: String,
+    /// AtCoder IDs of the members if the participant is a team.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) team_members: Option<Vec<String>>,
+    pub(crate) point: f64,
+    pub(crate) penalties: i64,
+    pub(crate) elapsed_second: i64,
+    /// `elapsed_second` plus `penalties` times the penalty of the contest. Penalties are not
+    /// counted in the lockout mode.
+    pub(crate) time_second: i64,
+    pub(crate) problems: Vec<ProblemResult>,
+}
+
+/// Computes the standings in the same way as the virtual contest page of the frontend.
+///
+/// In the lockout mode, only the first participant who gets AC on a problem gets its point, and
+/// the submissions to the problem after that are ignored. In the training mode, every problem is
+/// worth 1 point.
+///
+/// A team is ranked as one participant, whose result of each problem is the best one among the
+/// submissions of its members.
+///
+/// `submissions` should contain only the submissions made by the participants and the team
+/// members to the problems of the contest during the contest.
+pub(crate) fn compute_standings(
+    info: &VirtualContestInfo,
+    items: &[VirtualContestItem],
+    participants: &[String],
+    teams: &[VirtualContestTeam],
+    mut submissions: Vec<Submission>,
+) -> Vec<ParticipantStanding> {
+    let lockout = info.mode == Some(VirtualContestMode::Lockout);
+    // Neither lockout nor training contests charge penalties for wrong submissions.
+    let penalty_second = match info.mode {
+        Some(VirtualContestMode::Lockout) | Some(VirtualContestMode::Training) => 0,
+        _ => info.penalty_second,
+    };
+    let point_override = items
+        .iter()
+        .map(|item| match info.mode {
+            Some(VirtualContestMode::Training) => (item.id.as_str(), Some(1)),
+            _ => (item.id.as_str(), item.point),
+        })
+        .collect::<BTreeMap<_, _>>();
+    submissions.sort_by_key(|s| s.id);
+    let mut locked_problems = BTreeSet::new();
+
+    let entries = participants
+        .iter()
+        .map(|user_id| (user_id.as_str(), None))
+        .chain(
+            teams
+                .iter()
+                .map(|team| (team.name.as_str(), Some(&team.members))),
+        )
+        .collect::<Vec<_>>();
+    // Each submission counts for a single entry, so that a user cannot score twice. Registration
+    // keeps individual participants and team members apart, and if they still overlap, the first
+    // entry wins. The users are matched in lowercase because AtCoder IDs are case-insensitive.
+    let mut user_entries = BTreeMap::new();
+    for (i, (user_id, members)) in entries.iter().enumerate() {
+        let member_ids = match members {
+            Some(members) => members.iter().map(|m| m.as_str()).collect(),
+            None => vec![*user_id],
+        };
+        for member_id in member_ids {
+            user_entries.entry(member_id.to_lowercase()).or_insert(i);
+        }
+    }
+
+    let mut results = vec![BTreeMap::new(); entries.len()];
+    for submission in submissions.iter() {
+        let i = match user_entries.get(&submission.user_id.to_lowercase()) {
+            Some(&i) => i,
+            None => continue,
+        };
+        let point_override = match point_override.get(submission.problem_id.as_str()) {
+            Some(&point_override) => point_override,
+            None => continue,
+        };
+        let accepted = submission.result == "AC";
+        if lockout {
+            if locked_problems.contains(submission.problem_id.as_str()) {
+                continue;
+            }
+            if accepted {
+                locked_problems.insert(submission.problem_id.as_str());
+            }
+        }
+        let point = match point_override {
+            Some(point) if accepted => point as f64,
+            None if accepted || !lockout => submission.point,
+            _ => 0.0,
+        };
+        let elapsed_second = submission.epoch_second - info.start_epoch_second;
+        let result = results[i]
+            .entry(submission.problem_id.as_str())
+            .or_insert_with(|| ProblemResult {
+                problem_id: submission.problem_id.clone(),
+                point,
+                accepted,
+                trials: 0,
+                penalties: 0,
+                elapsed_second,
+            });
+        if result.point < point {
+            result.point = point;
+            result.accepted |= accepted;
+            result.penalties = result.trials;
+            result.elapsed_second = elapsed_second;
+        }
+        result.trials += 1;
+    }
+
+    let mut standings = entries
+        .into_iter()
+        .zip(results)
+        .map(|((user_id, team_members), problems)| {
+            let problems = problems.values().cloned().collect::<Vec<_>>();
+            let point = problems.iter().map(|r| r.point).sum::<f64>();
+            let penalties = problems.iter().map(|r| r.penalties).sum::<i64>();
+            let elapsed_second = problems
+                .iter()
+                .filter(|r| r.point > 0.0)
+                .map(|r| r.elapsed_second)
+                .max()
+                .unwrap_or(0);
+            ParticipantStanding {
+                rank: 0,
+                user_id: user_id.to_owned(),
+                team_members: team_members.cloned(),
+                point,
+                penalties,
+                elapsed_second,
+                time_second: elapsed_second + penalties * penalty_second,
+                problems,
+            }
+        })
+        .collect::<Vec<_>>();
+    standings.sort_by(|a, b| compare_standings(a, b).then_with(|| a.user_id.cmp(&b.user_id)));
+
+    for i in 0..standings.len() {
+        standings[i].rank =
+            if i > 0 && compare_standings(&standings[i - 1], &standings[i]) == Ordering::Equal {
+                standings[i - 1].rank
+            } else {
+                i + 1
+            };
+    }
+    standings
+}
+
+fn compare_standings(a: &ParticipantStanding, b: &ParticipantStanding) -> Ordering {
+    b.point
+        .partial_cmp(&a.point)
+        .unwrap_or(Ordering::Equal)
+        .then_with(|| a.time_second.cmp(&b.time_second))
+        .then_with(|| a.penalties.cmp(&b.penalties))
+}
+
+/// Returns the time from which the submissions are hidden from `user_id` because the standings
+/// are frozen, or `None` if the user can see all the submissions.
+///
+/// The organizers can always see all the submissions, and so can everyone after the contest.
+pub(crate) async fn get_freeze_start(
+    conn: &PgPool,
+    info: &VirtualContestInfo,
+    user_id: Option<&str>,
+    now: i64,
+) -> Result<Option<i64>> {
+    let end_second = info.start_epoch_second + info.duration_second;
+    if info.freeze_second <= 0 || now >= end_second {
+        return Ok(None);
+    }
+    if let Some(user_id) = user_id {
+        if is_organizer(conn, info, user_id).await? {
+            return Ok(None);
+        }
+    }
+    Ok(Some(end_second - info.freeze_second))
+}
+
+/// The submissions hidden by the freeze of a contest, i.e. the submissions of the participants to
+/// the problems of the contest from the start of the freeze.
+pub(crate) struct FrozenSubmissions {
+    freeze_start: i64,
+    problem_ids: BTreeSet<String>,
+    /// AtCoder IDs of the participants and the team members in lowercase.
+    user_ids: BTreeSet<String>,
+}
+
+impl FrozenSubmissions {
+    pub(crate) fn contains(&self, submission: &Submission) -> bool {
+        submission.epoch_second >= self.freeze_start
+            && self.problem_ids.contains(&submission.problem_id)
+            && self.user_ids.contains(&submission.user_id.to_lowercase())
+    }
+}
+
+/// Returns the submissions hidden from `user_id` by the freezes of `contests`.
+pub(crate) async fn get_frozen_submissions(
+    conn: &PgPool,
+    contests: &[VirtualContestInfo],
+    user_id: Option<&str>,
+    now: i64,
+) -> Result<Vec<FrozenSubmissions>> {
+    let mut frozen = Vec::new();
+    for info in contests {
+        let freeze_start = match get_freeze_start(conn, info, user_id, now).await? {
+            Some(freeze_start) => freeze_start,
+            None => continue,
+        };
+        let problem_ids = conn
+            .get_single_contest_problems(&info.id)
+            .await?
+            .into_iter()
+            .map(|item| item.id)
+            .collect();
+        let participants = conn.get_single_contest_participants(&info.id).await?;
+        let teams = conn.get_contest_teams(&info.id).await?;
+        let user_ids = participants
+            .iter()
+            .chain(teams.iter().flat_map(|team| team.members.iter()))
+            .map(|user_id| user_id.to_lowercase())
+            .collect();
+        frozen.push(FrozenSubmissions {
+            freeze_start,
+            problem_ids,
+            user_ids,
+        });
+    }
+    Ok(frozen)
+}
+
+/// A row of the exported standings, whose problems are aligned with the items of the contest.
+/// A problem is `None` if the participant has not submitted to it.
+#[derive(Serialize, Debug, PartialEq)]
+pub(crate) struct ExportedStanding<'a> {
+    pub(crate) rank: usize,
+    pub(crate) user_id: &'a str,
+    pub(crate) team_members: Option<&'a [String]>,
+    pub(crate) problems: Vec<Option<&'a ProblemResult>>,
+    pub(crate) point: f64,
+    pub(crate) penalties: i64,
+    pub(crate) time_second: i64,
+}
+
+pub(crate) fn export_standings<'a>(
+    items: &[VirtualContestItem],
+    standings: &'a [ParticipantStanding],
+) -> Vec<ExportedStanding<'a>> {
+    standings
+        .iter()
+        .map(|standing| {
+            let problems = items
+                .iter()
+                .map(|item| standing.problems.iter().find(|r| r.problem_id == item.id))
+                .collect();
+            ExportedStanding {
+                rank: standing.rank,
+                user_id: &standing.user_id,
+                team_members: standing.team_members.as_deref(),
+                problems,
+                point: standing.point,
+                penalties: standing.penalties,
+                time_second: standing.time_second,
+            }
+        })
+        .collect()
+}
+
+/// Formats the exported standings as CSV (RFC 4180), which has the score, the time and the
+/// penalties of each problem between the user and the totals. The members of a team are separated
+/// by spaces. The fields given by users are neutralized against formula injection.
+pub(crate) fn format_standings_csv(
+    items: &[VirtualContestItem],
+    rows: &[ExportedStanding],
+) -> String {
+    let mut header = vec![
+        "rank".to_owned(),
+        "user_id".to_owned(),
+        "team_members".to_owned(),
+    ];
+    for item in items {
+        header.push(neutralize_formula(&format!("{} point", item.id)));
+        header.push(neutralize_formula(&format!("{} elapsed_second", item.id)));
+        header.push(neutralize_formula(&format!("{} penalties", item.id)));
+    }
+    header.extend(
+        ["point", "penalties", "time_second"]
+            .iter()
+            .map(|s| s.to_string()),
+    );
+
+    let mut lines = vec![header];
+    for row in rows {
+        let mut line = vec![
+            row.rank.to_string(),
+            neutralize_formula(row.user_id),
+            neutralize_formula(&row.team_members.unwrap_or_default().join(" ")),
+        ];
+        for problem in row.problems.iter() {
+            match problem {
+                Some(result) => {
+                    line.push(result.point.to_string());
+                    line.push(result.elapsed_second.to_string());
+                    line.push(result.penalties.to_string());
+                }
+                None => line.extend(vec![String::new(); 3]),
+            }
+        }
+        line.push(row.point.to_string());
+        line.push(row.penalties.to_string());
+        line.push(row.time_second.to_string());
+        lines.push(line);
+    }
+
+    format_csv(lines)
+}
+
+/// Loads the standings of the contest as `user_id` can see them, and returns whether they are
+/// frozen together.
+async fn load_standings(
+    conn: &PgPool,
+    info: &VirtualContestInfo,
+    items: &[VirtualContestItem],
+    user_id: Option<&str>,
+) -> Result<(bool, Vec<ParticipantStanding>)> {
+    let participants = conn.get_single_contest_participants(&info.id).await?;
+    let teams = conn.get_contest_teams(&info.id).await?;
+    let freeze_start = get_freeze_start(conn, info, user_id, Utc::now().timestamp()).await?;
+
+    let user_ids = participants
+        .iter()
+        .chain(teams.iter().flat_map(|team| team.members.iter()))
+        .map(|s| s.as_str())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect::<Vec<_>>();
+    let problem_ids = items.iter().map(|s| s.id.as_str()).collect::<Vec<_>>();
+    let submissions = conn
+        .get_submissions(SubmissionRequest::UsersProblemsTime {
+            user_ids: &user_ids,
+            problem_ids: &problem_ids,
+            from_second: info.start_epoch_second,
+            to_second: match freeze_start {
+                Some(freeze_start) => freeze_start - 1,
+                None => info.start_epoch_second + info.duration_second,
+            },
+        })
+        .await?;
+
+    let standings = compute_standings(info, items, &participants, &teams, submissions);
+    Ok((freeze_start.is_some(), standings))
+}
+
+pub(crate) async fn get_standings<A>(request: Request<AppData<A>>) -> Result<Response>
+where
+    A: Authentication + Clone + Send + Sync + 'static,
+{
+    #[derive(Serialize)]
+    struct Standings {
+        contest_id: String,
+        penalty_second: i64,
+        frozen: bool,
+        standings: Vec<ParticipantStanding>,
+    }
+
+    let conn = request.state().pg_pool.clone();
+    let contest_id = request.param("contest_id")?;
+    let user_id = request.get_authorized_id().await.ok();
+
+    let info = get_existing_contest(&conn, contest_id).await?;
+    let items = conn.get_single_contest_problems(contest_id).await?;
+    let (frozen, standings) = load_standings(&conn, &info, &items, user_id.as_deref()).await?;
+    let response = Response::json(&Standings {
+        contest_id: info.id,
+        penalty_second: info.penalty_second,
+        frozen,
+        standings,
+    })?;
+    Ok(response)
+}
+
+pub(crate) async fn export_standings_file<A>(request: Request<AppData<A>>) -> Result<Response>
+where
+    A: Authentication + Clone + Send + Sync + 'static,
+{
+    #[derive(Deserialize, Clone, Copy, PartialEq, Eq)]
+    #[serde(rename_all = "lowercase")]
+    enum Format {
+        Csv,
+        Json,
+    }
+    #[derive(Deserialize)]
+    struct Query {
+        format: Option<Format>,
+    }
+    #[derive(Serialize)]
+    struct ExportedStandings<'a> {
+        contest_id: &'a str,
+        title: &'a str,
+        problem_ids: Vec<&'a str>,
+        frozen: bool,
+        standings: Vec<ExportedStanding<'a>>,
+    }
+
+    let conn = request.state().pg_pool.clone();
+    let contest_id = request.param("contest_id")?;
+    let query = request.query::<Query>()?;
+    let user_id = request.get_authorized_id().await.ok();
+
+    let info = get_existing_contest(&conn, contest_id).await?;
+    let items = conn.get_single_contest_problems(contest_id).await?;
+    let (frozen, standings) = load_standings(&conn, &info, &items, user_id.as_deref()).await?;
+    let rows = export_standings(&items, &standings);
+
+    let response = match query.format.unwrap_or(Format::Json) {
+        Format::Csv => {
+            let mut response = Response::new(StatusCode::Ok);
+            response.set_content_type("text/csv; charset=utf-8");
+            response.insert_header(
+                "Content-Disposition",
+                format!("attachment; filename=\"standings-{}.csv\"", info.id),
+            );
+            response.set_body(format_standings_csv(&items, &rows));
+            response
+        }
+        Format::Json => Response::json(&ExportedStandings {
+            contest_id: &info.id,
+            title: &info.title,
+            problem_ids: items.iter().map(|item| item.id.as_str()).collect(),
+            frozen,
+            standings: rows,
+        })?,
+    };
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn submission(id: i64, user_id: &str, problem_id: &str, result: &str, time: i64) -> Submission {
+        Submission {
+            id,
+            user_id: user_id.to_owned(),
+            problem_id: problem_id.to_owned(),
+            result: result.to_owned(),
+            epoch_second: time,
+            point: if result == "AC" { 100.0 } else { 0.0 },
+            ..Default::default()
+        }
+    }
+
+    /// A public contest from 0 to 1000 without a freeze.
+    fn contest_info(mode: Option<VirtualContestMode>, penalty_second: i64) -> VirtualContestInfo {
+        VirtualContestInfo {
+            id: "contest".to_owned(),
+            title: String::new(),
+            memo: String::new(),
+            owner_user_id: String::new(),
+            start_epoch_second: 0,
+            duration_second: 1000,
+            mode,
+            is_public: true,
+            penalty_second,
+            freeze_second: 0,
+        }
+    }
+
+    #[test]
+    fn test_compute_standings() {
+        let info = VirtualContestInfo {
+            start_epoch_second: 1000,
+            ..contest_info(None, 300)
+        };
+        let items = vec![
+            VirtualContestItem {
+                id: "p1".to_owned(),
+                point: None,
+                order: None,
+            },
+            VirtualContestItem {
+                id: "p2".to_owned(),
+                point: Some(500),
+                order: None,
+            },
+        ];
+        let participants = vec![
+            "u1".to_owned(),
+            "u2".to_owned(),
+            "u3".to_owned(),
+            "u4".to_owned(),
+        ];
+        let submissions = vec![
+            submission(1, "u1", "p1", "WA", 1010),
+            submission(2, "u1", "p1", "AC", 1020),
+            submission(3, "u1", "p1", "AC", 1030),
+            submission(4, "u1", "p2", "AC", 1100),
+            submission(5, "u2", "p2", "AC", 1400),
+            submission(6, "u2", "p1", "AC", 1200),
+            submission(7, "u3", "p1", "AC", 1700),
+            submission(8, "u2", "p3", "AC", 1000),
+            submission(9, "u5", "p1", "AC", 1000),
+        ];
+
+        let standings = compute_standings(&info, &items, &participants, &[], submissions);
+        let summary = standings
+            .iter()
+            .map(|s| {
+                (
+                    s.rank,
+                    s.user_id.as_str(),
+                    s.point,
+                    s.penalties,
+                    s.time_second,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            vec![
+                (1, "u2", 600.0, 0, 400),
+                (2, "u1", 600.0, 1, 400),
+                (3, "u3", 100.0, 0, 700),
+                (4, "u4", 0.0, 0, 0),
+            ]
+        );
+        assert_eq!(
+            standings[1].problems[0],
+            ProblemResult {
+                problem_id: "p1".to_owned(),
+                point: 100.0,
+                accepted: true,
+                trials: 3,
+                penalties: 1,
+                elapsed_second: 20,
+            }
+        );
+    }
+
+    #[test]
+    fn test_export_standings_csv() {
+        let info = contest_info(None, 300);
+        let items = vec![
+            VirtualContestItem {
+                id: "p1".to_owned(),
+                point: Some(100),
+                order: None,
+            },
+            VirtualContestItem {
+                id: "p,2".to_owned(),
+                point: Some(200),
+                order: None,
+            },
+        ];
+        let participants = vec!["u1".to_owned(), "u2".to_owned()];
+        let teams = vec![VirtualContestTeam {
+            id: "team_id".to_owned(),
+            name: "=HYPERLINK(\"x\")".to_owned(),
+            internal_user_id: String::new(),
+            members: vec!["u3".to_owned(), "u4".to_owned()],
+        }];
+        let submissions = vec![
+            submission(1, "u1", "p,2", "WA", 10),
+            submission(2, "u1", "p,2", "AC", 20),
+            submission(3, "u2", "p1", "AC", 30),
+        ];
+        let standings = compute_standings(&info, &items, &participants, &teams, submissions);
+        let rows = export_standings(&items, &standings);
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[0].user_id, "u1");
+        assert!(rows[0].team_members.is_none());
+        assert!(rows[0].problems[0].is_none());
+        assert_eq!(rows[0].problems[1].unwrap().problem_id, "p,2");
+        assert_eq!(
+            rows[2].team_members,
+            Some(&["u3".to_owned(), "u4".to_owned()][..])
+        );
+
+        assert_eq!(
+            format_standings_csv(&items, &rows),
+            "rank,user_id,team_members,p1 point,p1 elapsed_second,p1 penalties,\
+             \"p,2 point\",\"p,2 elapsed_second\",\"p,2 penalties\",point,penalties,time_second\r\n\
+             1,u1,,,,,200,20,1,200,1,320\r\n\
+             2,u2,,100,30,0,,,,100,0,30\r\n\
+             3,\"'=HYPERLINK(\"\"x\"\")\",u3 u4,,,,,,,0,0,0\r\n"
+        );
+    }
+
+    #[test]
+    fn test_tied_rank() {
+        let info = contest_info(None, 0);
+        let items = vec![VirtualContestItem {
+            id: "p1".to_owned(),
+            point: Some(1),
+            order: None,
+        }];
+        let participants = vec!["u1".to_owned(), "u2".to_owned(), "u3".to_owned()];
+        let submissions = vec![
+            submission(1, "u2", "p1", "AC", 10),
+            submission(2, "u1", "p1", "AC", 10),
+        ];
+        let standings = compute_standings(&info, &items, &participants, &[], submissions);
+        let ranks = standings
+            .iter()
+            .map(|s| (s.rank, s.user_id.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(ranks, vec![(1, "u1"), (1, "u2"), (3, "u3")]);
+    }
+
+    #[test]
+    fn test_mode_standings() {
+        let mut info = contest_info(Some(VirtualContestMode::Lockout), 300);
+        let items = vec![
+            VirtualContestItem {
+                id: "p1".to_owned(),
+                point: None,
+                order: None,
+            },
+            VirtualContestItem {
+                id: "p2".to_owned(),
+                point: Some(500),
+                order: None,
+            },
+        ];
+        let participants = vec!["u1".to_owned(), "u2".to_owned()];
+        let submissions = vec![
+            submission(1, "u1", "p1", "WA", 10),
+            submission(2, "u2", "p1", "AC", 20),
+            submission(3, "u1", "p1", "AC", 30),
+            submission(4, "u1", "p2", "WA", 40),
+            submission(5, "u1", "p2", "AC", 50),
+            submission(6, "u2", "p2", "AC", 60),
+        ];
+
+        let standings = compute_standings(&info, &items, &participants, &[], submissions.clone());
+        let summary = standings
+            .iter()
+            .map(|s| (s.rank, s.user_id.as_str(), s.point, s.time_second))
+            .collect::<Vec<_>>();
+        assert_eq!(summary, vec![(1, "u1", 500.0, 50), (2, "u2", 100.0, 20)]);
+        assert!(!standings[0].problems[0].accepted);
+
+        info.mode = Some(VirtualContestMode::Training);
+        let standings = compute_standings(&info, &items, &participants, &[], submissions.clone());
+        let summary = standings
+            .iter()
+            .map(|s| (s.rank, s.user_id.as_str(), s.point, s.time_second))
+            .collect::<Vec<_>>();
+        assert_eq!(summary, vec![(1, "u1", 2.0, 50), (2, "u2", 2.0, 60)]);
+        assert_eq!(standings[0].penalties, 2);
+
+        info.mode = None;
+        let standings = compute_standings(&info, &items, &participants, &[], submissions);
+        let summary = standings
+            .iter()
+            .map(|s| (s.rank, s.user_id.as_str(), s.point, s.time_second))
+            .collect::<Vec<_>>();
+        assert_eq!(summary, vec![(1, "u2", 600.0, 60), (2, "u1", 600.0, 650)]);
+    }
+
+    #[test]
+    fn test_team_standings() {
+        let info = contest_info(None, 100);
+        let items = vec![
+            VirtualContestItem {
+                id: "p1".to_owned(),
+                point: None,
+                order: None,
+            },
+            VirtualContestItem {
+                id: "p2".to_owned(),
+                point: None,
+                order: None,
+            },
+        ];
+        let participants = vec!["u1".to_owned()];
+        let teams = vec![VirtualContestTeam {
+            id: "team_id".to_owned(),
+            name: "team".to_owned(),
+            internal_user_id: String::new(),
+            members: vec!["u1".to_owned(), "u2".to_owned()],
+        }];
+        let submissions = vec![
+            submission(1, "u2", "p1", "WA", 10),
+            submission(2, "u1", "p1", "AC", 20),
+            submission(3, "u2", "p1", "AC", 30),
+            submission(4, "u2", "p2", "AC", 40),
+        ];
+
+        let standings = compute_standings(&info, &items, &participants, &teams, submissions);
+        let summary = standings
+            .iter()
+            .map(|s| {
+                (
+                    s.rank,
+                    s.user_id.as_str(),
+                    s.team_members.clone(),
+                    s.point,
+                    s.penalties,
+                    s.time_second,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            vec![
+                (
+                    1,
+                    "team",
+                    Some(vec!["u1".to_owned(), "u2".to_owned()]),
+                    200.0,
+                    1,
+                    140
+                ),
+                (2, "u1", None, 100.0, 0, 20),
+            ]
+        );
+        // The submission by u1 counts only for the individual entry.
+        assert_eq!(standings[0].problems[0].trials, 2);
+    }
+
+    #[test]
+    fn test_team_standings_case_insensitive() {
+        let info = contest_info(None, 0);
+        let items = vec![VirtualContestItem {
+            id: "p1".to_owned(),
+            point: None,
+            order: None,
+        }];
+        let teams = vec![VirtualContestTeam {
+            id: "team_id".to_owned(),
+            name: "team".to_owned(),
+            internal_user_id: String::new(),
+            members: vec!["member3".to_owned()],
+        }];
+        let submissions = vec![submission(1, "Member3", "p1", "AC", 10)];
+
+        let standings = compute_standings(&info, &items, &[], &teams, submissions);
+        let summary = standings
+            .iter()
+            .map(|s| (s.user_id.as_str(), s.point))
+            .collect::<Vec<_>>();
+        assert_eq!(summary, vec![("team", 100.0)]);
+    }
+}
@@ -1,17 +1,32 @@
+use crate::server::utils::is_not_modified;
 use crate::server::{AppData, CommonResponse};
 use sql_client::submission_client::{SubmissionClient, SubmissionRequest};
 use tide::{Request, Response, Result};
 
+const TIME_SUBMISSION_LIMIT: i64 = 1000;
+
 pub(crate) async fn get_time_submissions<A>(request: Request<AppData<A>>) -> Result<Response> {
     let from = request.param("from")?;
     let from_epoch_second = from.parse::<i64>()?;
     let conn = request.state().pg_pool.clone();
+    let summary = conn
+        .get_time_submission_summary(from_epoch_second, TIME_SUBMISSION_LIMIT)
+        .await?;
+    if is_not_modified(&request, &summary) {
+        let response = Response::not_modified()
+            .with_validators(&summary)
+            .make_cors();
+        return Ok(response);
+    }
+
     let submissions: Vec<_> = conn
         .get_submissions(SubmissionRequest::FromTime {
             from_second: from_epoch_second,
-            count: 1000,
+            count: TIME_SUBMISSION_LIMIT,
         })
         .await?;
-    let response = Response::json(&submissions)?.make_cors();
+    let response = Response::json(&submissions)?
+        .with_validators(&summary)
+        .make_cors();
     Ok(response)
 }
@@ -1,9 +1,16 @@
 use crate::server::{AppData, CommonResponse};
 
+use chrono::Utc;
 use serde::{Deserialize, Serialize};
 use sql_client::accepted_count::AcceptedCountClient;
+use sql_client::models::StreakStatistics;
 use sql_client::rated_point_sum::RatedPointSumClient;
-use tide::{Request, Response, Result};
+use sql_client::streak::{
+    compute_streak_statistics, jst, timezone_from_offset_minute, StreakClient, JST_OFFSET_MINUTE,
+};
+use sql_client::submission_client::{SubmissionClient, SubmissionRequest};
+use sql_client::PgPool;
+use tide::{Request, Response, Result, StatusCode};
 
 #[derive(Deserialize)]
 struct Query {
@@ -16,6 +23,8 @@ struct UserInfo {
     accepted_count_rank: i64,
     rated_point_sum: f64,
     rated_point_sum_rank: i64,
+    current_streak: i64,
+    last_ac_date: Option<String>,
 }
 
 pub(crate) async fn get_user_info<A>(request: Request<AppData<A>>) -> Result<Response> {
@@ -29,6 +38,11 @@ pub(crate) async fn get_user_info<A>(request: Request<AppData<A>>) -> Result<Res
         .await
         .unwrap_or(0.0);
     let rated_point_sum_rank = conn.get_rated_point_sum_rank(rated_point_sum).await?;
+    let current_streak = conn.get_users_current_streak(&user_id).await;
+    let last_ac_date = current_streak.as_ref().map(|s| s.last_ac_date.clone());
+    let current_streak = current_streak
+        .map(|s| s.streak_at(&Utc::now(), &jst()))
+        .unwrap_or(0);
 
     let user_info = UserInfo {
         user_id,
@@ -36,7 +50,53 @@ pub(crate) async fn get_user_info<A>(request: Request<AppData<A>>) -> Result<Res
         accepted_count_rank,
         rated_point_sum,
         rated_point_sum_rank,
+        current_streak,
+        last_ac_date,
     };
     let response = Response::json(&user_info)?.make_cors();
     Ok(response)
 }
+
+#[derive(Serialize)]
+pub(crate) struct UserStreakStatistics {
+    user_id: String,
+    timezone_offset: i32,
+    #[serde(flatten)]
+    statistics: StreakStatistics,
+}
+
+/// Computes the streak statistics of `user_id`, deciding the day boundaries by `timezone_offset`
+/// minutes east of UTC.
+pub(crate) async fn get_streak_statistics(
+    conn: &PgPool,
+    user_id: String,
+    timezone_offset: i32,
+) -> Result<UserStreakStatistics> {
+    let tz = timezone_from_offset_minute(timezone_offset)
+        .ok_or_else(|| tide::Error::from_str(StatusCode::BadRequest, "Invalid timezone offset."))?;
+    let submissions = conn
+        .get_submissions(SubmissionRequest::UsersAccepted {
+            user_ids: &[user_id.as_str()],
+        })
+        .await?;
+    let statistics = compute_streak_statistics(&submissions, &tz, &Utc::now());
+    Ok(UserStreakStatistics {
+        user_id,
+        timezone_offset,
+        statistics,
+    })
+}
+
+pub(crate) async fn get_user_streak<A>(request: Request<AppData<A>>) -> Result<Response> {
+    #[derive(Deserialize)]
+    struct Q {
+        user: String,
+        timezone_offset: Option<i32>,
+    }
+    let conn = request.state().pg_pool.clone();
+    let query = request.query::<Q>()?;
+    let timezone_offset = query.timezone_offset.unwrap_or(JST_OFFSET_MINUTE);
+    let statistics = get_streak_statistics(&conn, query.user, timezone_offset).await?;
+    let response = Response::json(&statistics)?.make_cors();
+    Ok(response)
+}
@@ -1,8 +1,18 @@
-use crate::server::{AppData, CommonResponse};
-use serde::Deserialize;
-use sql_client::submission_client::{SubmissionClient, SubmissionRequest};
+use crate::server::standings::get_frozen_submissions;
+use crate::server::utils::{is_not_modified, RequestUnpack};
+use crate::server::{AppData, Authentication, CommonResponse};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sql_client::internal::virtual_contest_manager::VirtualContestManager;
+use sql_client::models::Submission;
+use sql_client::submission_client::{
+    SubmissionClient, SubmissionFilter, SubmissionOrder, SubmissionRequest,
+};
 use tide::http::headers::CACHE_CONTROL;
-use tide::{Request, Response, Result};
+use tide::{Request, Response, Result, StatusCode};
+
+const DEFAULT_SUBMISSION_LIMIT: i64 = 500;
+const MAX_SUBMISSION_LIMIT: i64 = 1000;
 
 pub(crate) async fn get_user_submissions<A>(request: Request<AppData<A>>) -> Result<Response> {
     #[derive(Deserialize, Debug)]
@@ -12,14 +22,75 @@ pub(crate) async fn get_user_submissions<A>(request: Request<AppData<A>>) -> Res
     let conn = request.state().pg_pool.clone();
     let query = request.query::<Query>()?;
     let user_id = &query.user;
+    let summary = conn.get_user_submission_summary(user_id).await?;
+    if is_not_modified(&request, &summary) {
+        let mut response = Response::not_modified()
+            .with_validators(&summary)
+            .make_cors();
+        response.insert_header(CACHE_CONTROL, "max-age=300");
+        return Ok(response);
+    }
+
     let submissions = conn
         .get_submissions(SubmissionRequest::UserAll { user_id })
         .await?;
-    let mut response = Response::json(&submissions)?.make_cors();
+    let mut response = Response::json(&submissions)?
+        .with_validators(&summary)
+        .make_cors();
     response.insert_header(CACHE_CONTROL, "max-age=300");
     Ok(response)
 }
 
+pub(crate) async fn get_user_submission_page<A>(request: Request<AppData<A>>) -> Result<Response> {
+    #[derive(Deserialize, Debug)]
+    struct Query {
+        user: String,
+        from_second: Option<i64>,
+        from_id: Option<i64>,
+        limit: Option<i64>,
+    }
+    #[derive(Serialize)]
+    struct Cursor {
+        from_second: i64,
+        from_id: i64,
+    }
+    #[derive(Serialize)]
+    struct Page {
+        submissions: Vec<Submission>,
+        next: Option<Cursor>,
+    }
+
+    let conn = request.state().pg_pool.clone();
+    let query = request.query::<Query>()?;
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_SUBMISSION_LIMIT)
+        .clamp(1, MAX_SUBMISSION_LIMIT);
+
+    // Fetch one extra row to know whether the next page exists.
+    let mut submissions = conn
+        .get_submissions(SubmissionRequest::UserPage {
+            user_id: &query.user,
+            from_second: query.from_second.unwrap_or(0),
+            from_id: query.from_id.unwrap_or(i64::MIN),
+            count: limit + 1,
+        })
+        .await?;
+    let next = if submissions.len() as i64 > limit {
+        submissions.truncate(limit as usize);
+        submissions.last().map(|s| Cursor {
+            from_second: s.epoch_second,
+            from_id: s.id,
+        })
+    } else {
+        None
+    };
+
+    let page = Page { submissions, next };
+    let response = Response::json(&page)?.make_cors();
+    Ok(response)
+}
+
 pub(crate) async fn get_recent_submissions<A>(request: Request<AppData<A>>) -> Result<Response> {
     let conn = request.state().pg_pool.clone();
     let submissions = conn
@@ -29,9 +100,17 @@ pub(crate) async fn get_recent_submissions<A>(request: Request<AppData<A>>) -> R
     Ok(response)
 }
 
-pub(crate) async fn get_users_time_submissions<A>(
-    request: Request<AppData<A>>,
-) -> Result<Response> {
+/// Returns the submissions of the users to the problems in the time range.
+///
+/// The submissions of the participants of a running virtual contest to its problems during its
+/// freeze are hidden from everyone but the organizers of the contest. This is the endpoint the
+/// standings of virtual contests are built from, and the freeze only affects the standings: the
+/// other submission endpoints, e.g. `/results` and `/v3/from`, return the frozen submissions as
+/// they are on AtCoder.
+pub(crate) async fn get_users_time_submissions<A>(request: Request<AppData<A>>) -> Result<Response>
+where
+    A: Authentication + Clone + Send + Sync + 'static,
+{
     #[derive(Deserialize, Debug)]
     struct Query {
         users: String,
@@ -48,7 +127,8 @@ pub(crate) async fn get_users_time_submissions<A>(
         .split(',')
         .map(|s| s.trim())
         .collect::<Vec<_>>();
-    let submissions = conn
+
+    let mut submissions = conn
         .get_submissions(SubmissionRequest::UsersProblemsTime {
             user_ids: &user_ids,
             problem_ids: &problem_ids,
@@ -56,6 +136,80 @@ pub(crate) async fn get_users_time_submissions<A>(
             to_second: query.to,
         })
         .await?;
+
+    let now = Utc::now().timestamp();
+    let frozen_contests = conn.get_frozen_contests(&problem_ids, now).await?;
+    if !frozen_contests.is_empty() {
+        let user_id = request.get_authorized_id().await.ok();
+        let frozen =
+            get_frozen_submissions(&conn, &frozen_contests, user_id.as_deref(), now).await?;
+        submissions.retain(|submission| !frozen.iter().any(|f| f.contains(submission)));
+    }
     let response = Response::json(&submissions)?;
     Ok(response)
 }
+
+pub(crate) async fn get_filtered_submissions<A>(request: Request<AppData<A>>) -> Result<Response> {
+    #[derive(Deserialize, Debug)]
+    struct Query {
+        users: Option<String>,
+        problems: Option<String>,
+        contests: Option<String>,
+        results: Option<String>,
+        language: Option<String>,
+        from: Option<i64>,
+        to: Option<i64>,
+        order: Option<SubmissionOrder>,
+        limit: Option<i64>,
+    }
+
+    /// An empty list, e.g. `users=`, means no restriction as well as a missing one.
+    fn split(values: &Option<String>) -> Option<Vec<&str>> {
+        let values = values
+            .as_ref()?
+            .split(',')
+            .map(|s| s.trim())
+            .filter(|s| !s.is_empty())
+            .collect::<Vec<_>>();
+        if values.is_empty() {
+            None
+        } else {
+            Some(values)
+        }
+    }
+
+    let conn = request.state().pg_pool.clone();
+    let query = request.query::<Query>()?;
+    let user_ids = split(&query.users);
+    let problem_ids = split(&query.problems);
+    let contest_ids = split(&query.contests);
+    let results = split(&query.results);
+    if user_ids.is_none() && problem_ids.is_none() && contest_ids.is_none() {
+        return Err(tide::Error::from_str(
+            StatusCode::BadRequest,
+            "At least one of users, problems or contests is required.",
+        ));
+    }
+
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_SUBMISSION_LIMIT)
+        .clamp(1, MAX_SUBMISSION_LIMIT);
+    let submissions = conn
+        .get_submissions(SubmissionRequest::Filtered {
+            filter: SubmissionFilter {
+                user_ids: user_ids.as_deref(),
+                problem_ids: problem_ids.as_deref(),
+                contest_ids: contest_ids.as_deref(),
+                results: results.as_deref(),
+                language: query.language.as_deref(),
+                from_second: query.from,
+                to_second: query.to,
+                order: query.order,
+                count: limit,
+            },
+        })
+        .await?;
+    let response = Response::json(&submissions)?.make_cors();
+    Ok(response)
+}
@@ -1,7 +1,9 @@
-use crate::server::{AppData, Authentication};
+use crate::server::{submission_etag, AppData, Authentication};
 use anyhow::Context;
 use async_trait::async_trait;
 use serde::de::DeserializeOwned;
+use sql_client::models::SubmissionSummary;
+use tide::http::conditional::{ETag, IfNoneMatch};
 use tide::{Request, Result};
 
 #[async_trait]
@@ -33,3 +35,28 @@ impl<A: Authentication + Clone + Send + Sync + 'static> RequestUnpack for Reques
         Ok(body)
     }
 }
+
+/// Returns `true` if the `If-None-Match` sent by the client still matches the submissions,
+/// i.e. the server can answer `304 Not Modified` instead of the full body.
+pub(crate) fn is_not_modified<State>(
+    request: &Request<State>,
+    summary: &SubmissionSummary,
+) -> bool {
+    match IfNoneMatch::from_headers(request) {
+        Ok(Some(if_none_match)) => {
+            let etag = submission_etag(summary);
+            if_none_match.wildcard()
+                || if_none_match
+                    .iter()
+                    .any(|e| opaque_tag(e) == opaque_tag(&etag))
+        }
+        _ => false,
+    }
+}
+
+/// Weak comparison ignores the `W/` prefix.
+fn opaque_tag(etag: &ETag) -> &str {
+    match etag {
+        ETag::Strong(s) | ETag::Weak(s) => s,
+    }
+}
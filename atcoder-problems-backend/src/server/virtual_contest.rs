@@ -1,11 +1,100 @@
 use crate::server::utils::RequestUnpack;
 use crate::server::{AppData, Authentication, CommonResponse};
 
+use chrono::Utc;
 use serde::{Deserialize, Serialize};
+use sql_client::internal::user_manager::UserManager;
 use sql_client::internal::virtual_contest_manager::{
-    VirtualContestInfo, VirtualContestItem, VirtualContestManager,
+    NotJoinableError, VirtualContestInfo, VirtualContestItem, VirtualContestManager,
+    VirtualContestMode, VirtualContestRegistration, VirtualContestSearch, VirtualContestState,
+    VirtualContestTeam, MAX_TEAM_MEMBER_NUM, RECENT_CONTEST_NUM,
 };
-use tide::{Request, Response, Result};
+use sql_client::PgPool;
+use std::collections::BTreeSet;
+use tide::{Request, Response, Result, StatusCode};
+
+const DEFAULT_SEARCH_LIMIT: i64 = 100;
+
+fn forbidden(message: &'static str) -> tide::Error {
+    tide::Error::from_str(StatusCode::Forbidden, message)
+}
+
+fn parse_mode(mode: Option<&str>) -> Result<Option<VirtualContestMode>> {
+    mode.map(|mode| {
+        mode.parse()
+            .map_err(|_| tide::Error::from_str(StatusCode::BadRequest, "Invalid mode."))
+    })
+    .transpose()
+}
+
+fn validate_freeze_second(freeze_second: i64, duration_second: i64) -> Result<()> {
+    if freeze_second < 0 || freeze_second > duration_second {
+        return Err(tide::Error::from_str(
+            StatusCode::BadRequest,
+            "The freeze must be within the contest.",
+        ));
+    }
+    Ok(())
+}
+
+pub(crate) async fn get_existing_contest(
+    conn: &PgPool,
+    contest_id: &str,
+) -> Result<VirtualContestInfo> {
+    conn.get_single_contest_info(contest_id)
+        .await
+        .map_err(|_| tide::Error::from_str(StatusCode::NotFound, "The contest does not exist."))
+}
+
+/// Returns the contest if `user_id` is allowed to edit it, i.e. `404` if the contest does not
+/// exist and `403` if the user is neither the owner nor an organizer.
+async fn get_editable_contest(
+    conn: &PgPool,
+    contest_id: &str,
+    user_id: &str,
+) -> Result<VirtualContestInfo> {
+    let info = get_existing_contest(conn, contest_id).await?;
+    if !is_organizer(conn, &info, user_id).await? {
+        return Err(forbidden("Only the organizers can edit the contest."));
+    }
+    Ok(info)
+}
+
+pub(crate) async fn is_organizer(
+    conn: &PgPool,
+    info: &VirtualContestInfo,
+    user_id: &str,
+) -> Result<bool> {
+    if info.owner_user_id == user_id {
+        return Ok(true);
+    }
+    let organizers = conn.get_contest_organizers(&info.id).await?;
+    Ok(organizers.iter().any(|o| o.internal_user_id == user_id))
+}
+
+/// Returns the contest if `user_id` is the owner of it.
+async fn get_owned_contest(
+    conn: &PgPool,
+    contest_id: &str,
+    user_id: &str,
+) -> Result<VirtualContestInfo> {
+    let info = get_existing_contest(conn, contest_id).await?;
+    if info.owner_user_id != user_id {
+        return Err(forbidden("Only the owner can do this operation."));
+    }
+    Ok(info)
+}
+
+/// Maps the failure of entering the contest, either by joining it or by registering a team.
+fn join_error(error: anyhow::Error) -> tide::Error {
+    if error.is::<NotJoinableError>() {
+        forbidden("You are not allowed to join the contest.")
+    } else if sql_client::is_unique_violation(&error) {
+        tide::Error::from_str(StatusCode::Conflict, error.to_string())
+    } else {
+        error.into()
+    }
+}
 
 pub(crate) async fn create_contest<A>(request: Request<AppData<A>>) -> Result<Response>
 where
@@ -20,11 +109,15 @@ where
         mode: Option<String>,
         is_public: Option<bool>,
         penalty_second: i64,
+        freeze_second: Option<i64>,
     }
 
     let user_id = request.get_authorized_id().await?;
     let conn = request.state().pg_pool.clone();
     let q: Q = request.parse_body().await?;
+    let mode = parse_mode(q.mode.as_deref())?;
+    let freeze_second = q.freeze_second.unwrap_or(0);
+    validate_freeze_second(freeze_second, q.duration_second)?;
     let contest_id = conn
         .create_contest(
             &q.title,
@@ -32,9 +125,10 @@ where
             &user_id,
             q.start_epoch_second,
             q.duration_second,
-            q.mode.as_deref(),
+            mode,
             q.is_public.unwrap_or(true),
             q.penalty_second,
+            freeze_second,
         )
         .await?;
     let body = serde_json::json!({ "contest_id": contest_id });
@@ -56,26 +150,117 @@ where
         mode: Option<String>,
         is_public: Option<bool>,
         penalty_second: i64,
+        freeze_second: Option<i64>,
     }
 
-    request.get_authorized_id().await?;
+    let user_id = request.get_authorized_id().await?;
     let conn = request.state().pg_pool.clone();
     let q: Q = request.parse_body().await?;
+    let mode = parse_mode(q.mode.as_deref())?;
+    let info = get_editable_contest(&conn, &q.id, &user_id).await?;
+    // Keep the current freeze if the client does not know about it.
+    let freeze_second = q.freeze_second.unwrap_or(info.freeze_second);
+    validate_freeze_second(freeze_second, q.duration_second)?;
     conn.update_contest(
         &q.id,
         &q.title,
         &q.memo,
         q.start_epoch_second,
         q.duration_second,
-        q.mode.as_deref(),
+        mode,
         q.is_public.unwrap_or(true),
         q.penalty_second,
+        freeze_second,
+        &user_id,
     )
     .await?;
     let response = Response::empty_json();
     Ok(response)
 }
 
+pub(crate) async fn clone_contest<A>(request: Request<AppData<A>>) -> Result<Response>
+where
+    A: Authentication + Clone + Send + Sync + 'static,
+{
+    #[derive(Deserialize)]
+    struct Q {
+        contest_id: String,
+        start_epoch_second: i64,
+    }
+
+    let user_id = request.get_authorized_id().await?;
+    let conn = request.state().pg_pool.clone();
+    let q: Q = request.parse_body().await?;
+    let info = get_existing_contest(&conn, &q.contest_id).await?;
+    if !info.is_public && !is_organizer(&conn, &info, &user_id).await? {
+        return Err(forbidden(
+            "Only the organizers can clone a private contest.",
+        ));
+    }
+    let contest_id = conn
+        .clone_contest(&q.contest_id, &user_id, q.start_epoch_second)
+        .await?;
+    let body = serde_json::json!({ "contest_id": contest_id });
+    let response = Response::json(&body)?;
+    Ok(response)
+}
+
+pub(crate) async fn delete_contest<A>(request: Request<AppData<A>>) -> Result<Response>
+where
+    A: Authentication + Clone + Send + Sync + 'static,
+{
+    #[derive(Deserialize)]
+    struct Q {
+        contest_id: String,
+    }
+
+    let user_id = request.get_authorized_id().await?;
+    let conn = request.state().pg_pool.clone();
+    let q: Q = request.parse_body().await?;
+    get_owned_contest(&conn, &q.contest_id, &user_id).await?;
+    conn.delete_contest(&q.contest_id, &user_id, Utc::now().timestamp())
+        .await?;
+    let response = Response::empty_json();
+    Ok(response)
+}
+
+pub(crate) async fn restore_contest<A>(request: Request<AppData<A>>) -> Result<Response>
+where
+    A: Authentication + Clone + Send + Sync + 'static,
+{
+    #[derive(Deserialize)]
+    struct Q {
+        contest_id: String,
+    }
+
+    let user_id = request.get_authorized_id().await?;
+    let conn = request.state().pg_pool.clone();
+    let q: Q = request.parse_body().await?;
+    conn.restore_contest(&q.contest_id, &user_id, Utc::now().timestamp())
+        .await
+        .map_err(|_| {
+            tide::Error::from_str(
+                StatusCode::NotFound,
+                "The contest is not a restorable contest of yours.",
+            )
+        })?;
+    let response = Response::empty_json();
+    Ok(response)
+}
+
+pub(crate) async fn get_deleted_contests<A>(request: Request<AppData<A>>) -> Result<Response>
+where
+    A: Authentication + Clone + Send + Sync + 'static,
+{
+    let user_id = request.get_authorized_id().await?;
+    let conn = request.state().pg_pool.clone();
+    let contests = conn
+        .get_deleted_contests(&user_id, Utc::now().timestamp())
+        .await?;
+    let response = Response::json(&contests)?;
+    Ok(response)
+}
+
 pub(crate) async fn update_items<A: Authentication + Clone + Send + Sync + 'static>(
     request: Request<AppData<A>>,
 ) -> Result<Response> {
@@ -88,6 +273,7 @@ pub(crate) async fn update_items<A: Authentication + Clone + Send + Sync + 'stat
     let user_id = request.get_authorized_id().await?;
     let conn = request.state().pg_pool.clone();
     let q: Q = request.parse_body().await?;
+    get_editable_contest(&conn, &q.contest_id, &user_id).await?;
     conn.update_items(&q.contest_id, &q.problems, &user_id)
         .await?;
     let response = Response::empty_json();
@@ -123,6 +309,7 @@ where
         info: VirtualContestInfo,
         problems: Vec<VirtualContestItem>,
         participants: Vec<String>,
+        teams: Vec<VirtualContestTeam>,
     }
 
     let conn = request.state().pg_pool.clone();
@@ -131,10 +318,12 @@ where
     let info = conn.get_single_contest_info(&contest_id).await?;
     let participants = conn.get_single_contest_participants(&contest_id).await?;
     let problems = conn.get_single_contest_problems(&contest_id).await?;
+    let teams = conn.get_contest_teams(&contest_id).await?;
     let contest = VirtualContestDetails {
         info,
         participants,
         problems,
+        teams,
     };
     let response = Response::json(&contest)?;
     Ok(response)
@@ -147,17 +336,54 @@ pub(crate) async fn get_recent_contests<A>(request: Request<AppData<A>>) -> Resu
     Ok(response)
 }
 
+pub(crate) async fn search_contests<A>(request: Request<AppData<A>>) -> Result<Response> {
+    #[derive(Deserialize)]
+    struct Query {
+        keyword: Option<String>,
+        owner: Option<String>,
+        state: Option<VirtualContestState>,
+        problem: Option<String>,
+        offset: Option<i64>,
+        limit: Option<i64>,
+    }
+
+    let conn = request.state().pg_pool.clone();
+    let query = request.query::<Query>()?;
+    let search = VirtualContestSearch {
+        keyword: query
+            .keyword
+            .as_deref()
+            .filter(|keyword| !keyword.is_empty()),
+        owner_user_id: query.owner.as_deref(),
+        state: query.state,
+        problem_id: query.problem.as_deref(),
+        offset: query.offset.unwrap_or(0).max(0),
+        count: query
+            .limit
+            .unwrap_or(DEFAULT_SEARCH_LIMIT)
+            .clamp(1, RECENT_CONTEST_NUM),
+    };
+    let contests = conn.search_contests(search, Utc::now().timestamp()).await?;
+    let response = Response::json(&contests)?;
+    Ok(response)
+}
+
 pub(crate) async fn join_contest<A: Authentication + Clone + Send + Sync + 'static>(
     request: Request<AppData<A>>,
 ) -> Result<Response> {
     #[derive(Deserialize)]
     struct Q {
         contest_id: String,
+        invite_token: Option<String>,
     }
     let user_id = request.get_authorized_id().await?;
     let conn = request.state().pg_pool.clone();
     let q: Q = request.parse_body().await?;
-    conn.join_contest(&q.contest_id, &user_id).await?;
+    get_existing_contest(&conn, &q.contest_id).await?;
+    let now = Utc::now().timestamp();
+    conn.join_contest(&q.contest_id, &user_id, q.invite_token.as_deref(), now)
+        .await
+        .map_err(join_error)?;
     let response = Response::empty_json();
     Ok(response)
 }
@@ -176,3 +402,286 @@ pub(crate) async fn leave_contest<A: Authentication + Clone + Send + Sync + 'sta
     let response = Response::empty_json();
     Ok(response)
 }
+
+pub(crate) async fn get_organizers<A>(request: Request<AppData<A>>) -> Result<Response> {
+    let conn = request.state().pg_pool.clone();
+    let contest_id = request.param("contest_id")?;
+    get_existing_contest(&conn, contest_id).await?;
+    let organizers = conn.get_contest_organizers(contest_id).await?;
+    let response = Response::json(&organizers)?;
+    Ok(response)
+}
+
+pub(crate) async fn add_organizer<A: Authentication + Clone + Send + Sync + 'static>(
+    request: Request<AppData<A>>,
+) -> Result<Response> {
+    #[derive(Deserialize)]
+    struct Q {
+        contest_id: String,
+        user_id: String,
+    }
+    let user_id = request.get_authorized_id().await?;
+    let conn = request.state().pg_pool.clone();
+    let q: Q = request.parse_body().await?;
+    get_owned_contest(&conn, &q.contest_id, &user_id).await?;
+    conn.get_internal_user_info(&q.user_id)
+        .await
+        .map_err(|_| tide::Error::from_str(StatusCode::NotFound, "The user does not exist."))?;
+    conn.add_contest_organizer(&q.contest_id, &q.user_id)
+        .await?;
+    let response = Response::empty_json();
+    Ok(response)
+}
+
+pub(crate) async fn remove_organizer<A: Authentication + Clone + Send + Sync + 'static>(
+    request: Request<AppData<A>>,
+) -> Result<Response> {
+    #[derive(Deserialize)]
+    struct Q {
+        contest_id: String,
+        user_id: String,
+    }
+    let user_id = request.get_authorized_id().await?;
+    let conn = request.state().pg_pool.clone();
+    let q: Q = request.parse_body().await?;
+    let info = get_existing_contest(&conn, &q.contest_id).await?;
+    // Organizers can step down by themselves.
+    if info.owner_user_id != user_id && q.user_id != user_id {
+        return Err(forbidden("Only the owner can remove other organizers."));
+    }
+    conn.remove_contest_organizer(&q.contest_id, &q.user_id)
+        .await?;
+    let response = Response::empty_json();
+    Ok(response)
+}
+
+pub(crate) async fn get_invite_token<A: Authentication + Clone + Send + Sync + 'static>(
+    request: Request<AppData<A>>,
+) -> Result<Response> {
+    let user_id = request.get_authorized_id().await?;
+    let conn = request.state().pg_pool.clone();
+    let contest_id = request.param("contest_id")?;
+    get_owned_contest(&conn, contest_id, &user_id).await?;
+    let invite_token = conn.get_invite_token(contest_id).await?;
+    let body = serde_json::json!({ "invite_token": invite_token });
+    let response = Response::json(&body)?;
+    Ok(response)
+}
+
+pub(crate) async fn generate_invite_token<A: Authentication + Clone + Send + Sync + 'static>(
+    request: Request<AppData<A>>,
+) -> Result<Response> {
+    #[derive(Deserialize)]
+    struct Q {
+        contest_id: String,
+    }
+    let user_id = request.get_authorized_id().await?;
+    let conn = request.state().pg_pool.clone();
+    let q: Q = request.parse_body().await?;
+    get_owned_contest(&conn, &q.contest_id, &user_id).await?;
+    let invite_token = conn.generate_invite_token(&q.contest_id).await?;
+    let body = serde_json::json!({ "invite_token": invite_token });
+    let response = Response::json(&body)?;
+    Ok(response)
+}
+
+pub(crate) async fn revoke_invite_token<A: Authentication + Clone + Send + Sync + 'static>(
+    request: Request<AppData<A>>,
+) -> Result<Response> {
+    #[derive(Deserialize)]
+    struct Q {
+        contest_id: String,
+    }
+    let user_id = request.get_authorized_id().await?;
+    let conn = request.state().pg_pool.clone();
+    let q: Q = request.parse_body().await?;
+    get_owned_contest(&conn, &q.contest_id, &user_id).await?;
+    conn.revoke_invite_token(&q.contest_id).await?;
+    let response = Response::empty_json();
+    Ok(response)
+}
+
+pub(crate) async fn get_teams<A>(request: Request<AppData<A>>) -> Result<Response> {
+    let conn = request.state().pg_pool.clone();
+    let contest_id = request.param("contest_id")?;
+    get_existing_contest(&conn, contest_id).await?;
+    let teams = conn.get_contest_teams(contest_id).await?;
+    let response = Response::json(&teams)?;
+    Ok(response)
+}
+
+pub(crate) async fn create_team<A: Authentication + Clone + Send + Sync + 'static>(
+    request: Request<AppData<A>>,
+) -> Result<Response> {
+    #[derive(Deserialize)]
+    struct Q {
+        contest_id: String,
+        name: String,
+        members: Vec<String>,
+        invite_token: Option<String>,
+    }
+    let user_id = request.get_authorized_id().await?;
+    let conn = request.state().pg_pool.clone();
+    let q: Q = request.parse_body().await?;
+    get_existing_contest(&conn, &q.contest_id).await?;
+
+    // AtCoder IDs are case-insensitive, so the members are stored in lowercase.
+    let members = q
+        .members
+        .iter()
+        .map(|member| member.trim().to_lowercase())
+        .filter(|member| !member.is_empty())
+        .collect::<BTreeSet<_>>();
+    let members = members.iter().map(|m| m.as_str()).collect::<Vec<_>>();
+    if q.name.trim().is_empty() || members.is_empty() || members.len() > MAX_TEAM_MEMBER_NUM {
+        return Err(tide::Error::from_str(
+            StatusCode::BadRequest,
+            format!(
+                "A team needs a name and 1 to {} members.",
+                MAX_TEAM_MEMBER_NUM
+            ),
+        ));
+    }
+    let now = Utc::now().timestamp();
+    let team_id = conn
+        .create_team(
+            &q.contest_id,
+            &user_id,
+            q.invite_token.as_deref(),
+            q.name.trim(),
+            &members,
+            now,
+        )
+        .await
+        .map_err(join_error)?;
+    let body = serde_json::json!({ "team_id": team_id });
+    let response = Response::json(&body)?;
+    Ok(response)
+}
+
+pub(crate) async fn delete_team<A: Authentication + Clone + Send + Sync + 'static>(
+    request: Request<AppData<A>>,
+) -> Result<Response> {
+    #[derive(Deserialize)]
+    struct Q {
+        contest_id: String,
+        team_id: String,
+    }
+    let user_id = request.get_authorized_id().await?;
+    let conn = request.state().pg_pool.clone();
+    let q: Q = request.parse_body().await?;
+    let info = get_existing_contest(&conn, &q.contest_id).await?;
+    let teams = conn.get_contest_teams(&q.contest_id).await?;
+    let team = teams
+        .iter()
+        .find(|team| team.id == q.team_id)
+        .ok_or_else(|| tide::Error::from_str(StatusCode::NotFound, "The team does not exist."))?;
+    // The user who registered the team can withdraw it.
+    if team.internal_user_id != user_id && !is_organizer(&conn, &info, &user_id).await? {
+        return Err(forbidden(
+            "Only the organizers can delete a team of another user.",
+        ));
+    }
+    conn.delete_team(&q.contest_id, &q.team_id).await?;
+    let response = Response::empty_json();
+    Ok(response)
+}
+
+#[derive(Deserialize)]
+struct ParticipantQuery {
+    contest_id: String,
+    user_id: String,
+}
+
+pub(crate) async fn get_participants<A: Authentication + Clone + Send + Sync + 'static>(
+    request: Request<AppData<A>>,
+) -> Result<Response> {
+    let user_id = request.get_authorized_id().await?;
+    let conn = request.state().pg_pool.clone();
+    let contest_id = request.param("contest_id")?;
+    get_owned_contest(&conn, contest_id, &user_id).await?;
+    let participants = conn.get_contest_participant_users(contest_id).await?;
+    let banned = conn.get_banned_users(contest_id).await?;
+    let body = serde_json::json!({ "participants": participants, "banned": banned });
+    let response = Response::json(&body)?;
+    Ok(response)
+}
+
+pub(crate) async fn remove_participant<A: Authentication + Clone + Send + Sync + 'static>(
+    request: Request<AppData<A>>,
+) -> Result<Response> {
+    let user_id = request.get_authorized_id().await?;
+    let conn = request.state().pg_pool.clone();
+    let q: ParticipantQuery = request.parse_body().await?;
+    get_owned_contest(&conn, &q.contest_id, &user_id).await?;
+    conn.leave_contest(&q.contest_id, &q.user_id).await?;
+    let response = Response::empty_json();
+    Ok(response)
+}
+
+pub(crate) async fn ban_participant<A: Authentication + Clone + Send + Sync + 'static>(
+    request: Request<AppData<A>>,
+) -> Result<Response> {
+    let user_id = request.get_authorized_id().await?;
+    let conn = request.state().pg_pool.clone();
+    let q: ParticipantQuery = request.parse_body().await?;
+    get_owned_contest(&conn, &q.contest_id, &user_id).await?;
+    if q.user_id == user_id {
+        return Err(tide::Error::from_str(
+            StatusCode::BadRequest,
+            "The owner cannot ban themselves.",
+        ));
+    }
+    conn.get_internal_user_info(&q.user_id)
+        .await
+        .map_err(|_| tide::Error::from_str(StatusCode::NotFound, "The user does not exist."))?;
+    conn.ban_user(&q.contest_id, &q.user_id).await?;
+    let response = Response::empty_json();
+    Ok(response)
+}
+
+pub(crate) async fn unban_participant<A: Authentication + Clone + Send + Sync + 'static>(
+    request: Request<AppData<A>>,
+) -> Result<Response> {
+    let user_id = request.get_authorized_id().await?;
+    let conn = request.state().pg_pool.clone();
+    let q: ParticipantQuery = request.parse_body().await?;
+    get_owned_contest(&conn, &q.contest_id, &user_id).await?;
+    conn.unban_user(&q.contest_id, &q.user_id).await?;
+    let response = Response::empty_json();
+    Ok(response)
+}
+
+pub(crate) async fn get_registration<A>(request: Request<AppData<A>>) -> Result<Response> {
+    let conn = request.state().pg_pool.clone();
+    let contest_id = request.param("contest_id")?;
+    get_existing_contest(&conn, contest_id).await?;
+    let registration = conn.get_registration(contest_id).await?;
+    let response = Response::json(&registration)?;
+    Ok(response)
+}
+
+pub(crate) async fn update_registration<A: Authentication + Clone + Send + Sync + 'static>(
+    request: Request<AppData<A>>,
+) -> Result<Response> {
+    #[derive(Deserialize)]
+    struct Q {
+        contest_id: String,
+        #[serde(flatten)]
+        registration: VirtualContestRegistration,
+    }
+    let user_id = request.get_authorized_id().await?;
+    let conn = request.state().pg_pool.clone();
+    let q: Q = request.parse_body().await?;
+    get_owned_contest(&conn, &q.contest_id, &user_id).await?;
+    if matches!(q.registration.max_participants, Some(m) if m < 1) {
+        return Err(tide::Error::from_str(
+            StatusCode::BadRequest,
+            "max_participants must be positive.",
+        ));
+    }
+    conn.update_registration(&q.contest_id, &q.registration)
+        .await?;
+    let response = Response::empty_json();
+    Ok(response)
+}
@@ -0,0 +1,68 @@
+use crate::server::utils::RequestUnpack;
+use crate::server::{AppData, Authentication, CommonResponse};
+
+use serde::Deserialize;
+use sql_client::internal::virtual_contest_manager::MAX_PROBLEM_NUM_PER_CONTEST;
+use sql_client::internal::virtual_contest_series_manager::{
+    VirtualContestSeriesConfig, VirtualContestSeriesManager,
+};
+use tide::{Request, Response, Result, StatusCode};
+
+fn validate(config: &VirtualContestSeriesConfig) -> Result<()> {
+    let message = if config.problem_count < 1
+        || config.problem_count > MAX_PROBLEM_NUM_PER_CONTEST as i64
+    {
+        "The number of problems is out of range."
+    } else if config.duration_second <= 0 {
+        "The duration must be positive."
+    } else if matches!((config.min_point, config.max_point), (Some(min), Some(max)) if min > max) {
+        "min_point must not be greater than max_point."
+    } else {
+        return Ok(());
+    };
+    Err(tide::Error::from_str(StatusCode::BadRequest, message))
+}
+
+pub(crate) async fn create_series<A>(request: Request<AppData<A>>) -> Result<Response>
+where
+    A: Authentication + Clone + Send + Sync + 'static,
+{
+    let user_id = request.get_authorized_id().await?;
+    let conn = request.state().pg_pool.clone();
+    let config: VirtualContestSeriesConfig = request.parse_body().await?;
+    validate(&config)?;
+    let series_id = conn.create_series(&user_id, &config).await?;
+    let body = serde_json::json!({ "series_id": series_id });
+    let response = Response::json(&body)?;
+    Ok(response)
+}
+
+pub(crate) async fn delete_series<A>(request: Request<AppData<A>>) -> Result<Response>
+where
+    A: Authentication + Clone + Send + Sync + 'static,
+{
+    #[derive(Deserialize)]
+    struct Q {
+        series_id: String,
+    }
+
+    let user_id = request.get_authorized_id().await?;
+    let conn = request.state().pg_pool.clone();
+    let q: Q = request.parse_body().await?;
+    conn.delete_series(&q.series_id, &user_id)
+        .await
+        .map_err(|e| tide::Error::new(StatusCode::NotFound, e))?;
+    let response = Response::empty_json();
+    Ok(response)
+}
+
+pub(crate) async fn get_my_series<A>(request: Request<AppData<A>>) -> Result<Response>
+where
+    A: Authentication + Clone + Send + Sync + 'static,
+{
+    let user_id = request.get_authorized_id().await?;
+    let conn = request.state().pg_pool.clone();
+    let series = conn.get_own_series(&user_id).await?;
+    let response = Response::json(&series)?;
+    Ok(response)
+}
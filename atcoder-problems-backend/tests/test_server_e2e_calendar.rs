@@ -0,0 +1,101 @@
+use async_std::prelude::*;
+use async_std::task;
+use async_trait::async_trait;
+use atcoder_problems_backend::server::{run_server, Authentication, GitHubUserResponse};
+use rand::Rng;
+use serde_json::{json, Value};
+use std::time::Duration;
+use tide::Result;
+
+pub mod utils;
+
+#[derive(Clone)]
+struct MockAuth;
+#[async_trait]
+impl Authentication for MockAuth {
+    async fn get_token(&self, _: &str) -> Result<String> {
+        Ok(String::new())
+    }
+
+    async fn get_user_id(&self, _: &str) -> Result<GitHubUserResponse> {
+        Ok(GitHubUserResponse::default())
+    }
+}
+
+async fn setup() -> u16 {
+    utils::initialize_and_connect_to_test_sql().await;
+    let mut rng = rand::thread_rng();
+    rng.gen::<u16>() % 30000 + 30000
+}
+
+fn url(path: &str, port: u16) -> String {
+    format!("http://localhost:{}{}", port, path)
+}
+
+#[async_std::test]
+async fn test_calendar() {
+    let port = setup().await;
+    let server = async_std::task::spawn(async move {
+        let pg_pool = sql_client::initialize_pool(utils::get_sql_url_from_env())
+            .await
+            .unwrap();
+        run_server(pg_pool, MockAuth, port).await.unwrap();
+    });
+    task::sleep(Duration::from_millis(1000)).await;
+
+    let response = surf::get(url("/internal-api/authorize?code=a", port))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 302);
+
+    let response = surf::post(url("/internal-api/contest/create", port))
+        .header("Cookie", "token=a")
+        .body(json!({
+            "title": "contest title",
+            "memo": "contest memo",
+            "start_epoch_second": 1,
+            "duration_second": 2,
+            "penalty_second": 0,
+        }))
+        .await
+        .unwrap();
+    assert!(response.status().is_success());
+
+    let response = surf::get(url("/internal-api/calendar/token", port))
+        .header("Cookie", "token=a")
+        .recv_json::<Value>()
+        .await
+        .unwrap();
+    assert_eq!(response, json!({ "calendar_token": null }));
+
+    let response = surf::post(url("/internal-api/calendar/token/generate", port))
+        .header("Cookie", "token=a")
+        .recv_json::<Value>()
+        .await
+        .unwrap();
+    let token = response["calendar_token"].as_str().unwrap().to_owned();
+
+    let mut response = surf::get(url(&format!("/internal-api/calendar/user/{}", token), port))
+        .await
+        .unwrap();
+    assert!(response.status().is_success());
+    assert_eq!(response.content_type().unwrap().essence(), "text/calendar");
+    let body = response.body_string().await.unwrap();
+    assert!(body.starts_with("BEGIN:VCALENDAR\r\n"));
+    assert!(body.contains("SUMMARY:contest title\r\n"));
+    assert!(body.contains("DTSTART:19700101T000001Z\r\n"));
+
+    let response = surf::get(url("/internal-api/calendar/user/invalid", port))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 404);
+
+    let mut response = surf::get(url("/internal-api/calendar/recent", port))
+        .await
+        .unwrap();
+    assert!(response.status().is_success());
+    let body = response.body_string().await.unwrap();
+    assert!(body.contains("SUMMARY:contest title\r\n"));
+
+    server.race(async_std::future::ready(())).await;
+}
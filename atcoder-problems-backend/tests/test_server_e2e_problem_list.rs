@@ -17,18 +17,22 @@ struct MockAuth;
 
 const VALID_CODE: &str = "VALID-CODE";
 const VALID_TOKEN: &str = "VALID-TOKEN";
+const OTHER_CODE: &str = "OTHER-CODE";
+const OTHER_TOKEN: &str = "OTHER-TOKEN";
 
 #[async_trait]
 impl Authentication for MockAuth {
     async fn get_token(&self, code: &str) -> Result<String> {
         match code {
             VALID_CODE => Ok(VALID_TOKEN.to_owned()),
+            OTHER_CODE => Ok(OTHER_TOKEN.to_owned()),
             _ => Err(anyhow::anyhow!("error").into()),
         }
     }
     async fn get_user_id(&self, token: &str) -> Result<GitHubUserResponse> {
         match token {
             VALID_TOKEN => Ok(GitHubUserResponse::default()),
+            OTHER_TOKEN => Ok(serde_json::from_value(json!({"id": 1, "login": "other"}))?),
             _ => Err(anyhow::anyhow!("error").into()),
         }
     }
@@ -101,6 +105,8 @@ async fn test_list() {
                 "internal_list_id": internal_list_id,
                 "internal_user_id": "0",
                 "internal_list_name": "a",
+                "source_list_id": null,
+                "fork_count": 0,
                 "items": []
             }
         ])
@@ -127,6 +133,8 @@ async fn test_list() {
                 "internal_list_id": internal_list_id,
                 "internal_user_id": "0",
                 "internal_list_name": "b",
+                "source_list_id": null,
+                "fork_count": 0,
                 "items": []
             }
         ])
@@ -146,6 +154,8 @@ async fn test_list() {
             "internal_list_id": internal_list_id,
             "internal_user_id": "0",
             "internal_list_name": "b",
+            "source_list_id": null,
+            "fork_count": 0,
             "items": []
         })
     );
@@ -243,7 +253,9 @@ async fn test_list_item() {
                 "internal_list_id": internal_list_id,
                 "internal_list_name": "a",
                 "internal_user_id": "0",
-                "items": [{"problem_id": "problem_1", "memo":""}]
+                "source_list_id": null,
+                "fork_count": 0,
+                "items": [{"problem_id": "problem_1", "memo": "", "tags": []}]
             }
         ])
     );
@@ -271,7 +283,9 @@ async fn test_list_item() {
                 "internal_list_id": internal_list_id,
                 "internal_list_name": "a",
                 "internal_user_id": "0",
-                "items": [{"problem_id": "problem_1", "memo":"memo_1"}]
+                "source_list_id": null,
+                "fork_count": 0,
+                "items": [{"problem_id": "problem_1", "memo": "memo_1", "tags": []}]
             }
         ])
     );
@@ -297,6 +311,8 @@ async fn test_list_item() {
                 "internal_list_id": internal_list_id,
                 "internal_list_name": "a",
                 "internal_user_id": "0",
+                "source_list_id": null,
+                "fork_count": 0,
                 "items": []
             }
         ])
@@ -392,3 +408,481 @@ async fn test_register_twice() {
     assert_eq!(response.status(), 302);
     server.race(ready(())).await;
 }
+
+#[async_std::test]
+async fn test_fork_list() {
+    let port = setup().await;
+    let server = task::spawn(async move {
+        let pg_pool = sql_client::initialize_pool(utils::get_sql_url_from_env())
+            .await
+            .unwrap();
+        run_server(pg_pool, MockAuth, port).await.unwrap();
+    });
+    task::sleep(std::time::Duration::from_millis(1000)).await;
+
+    surf::get(url(
+        &format!("/internal-api/authorize?code={}", VALID_CODE),
+        port,
+    ))
+    .await
+    .unwrap();
+    let cookie_header = format!("token={}", VALID_TOKEN);
+
+    let mut response = surf::post(url("/internal-api/list/create", port))
+        .header("Cookie", cookie_header.as_str())
+        .body(json!({"list_name":"a"}))
+        .await
+        .unwrap();
+    assert!(response.status().is_success(), "{:?}", response);
+    let value: Value = response.body_json().await.unwrap();
+    let internal_list_id = value["internal_list_id"].as_str().unwrap().to_owned();
+
+    let response = surf::post(url("/internal-api/list/item/add", port))
+        .header("Cookie", cookie_header.as_str())
+        .body(json!({
+            "internal_list_id": internal_list_id,
+            "problem_id": "problem_1"
+        }))
+        .await
+        .unwrap();
+    assert!(response.status().is_success(), "{:?}", response);
+
+    let response = surf::post(url("/internal-api/list/fork", port))
+        .body(json!({ "internal_list_id": internal_list_id }))
+        .await
+        .unwrap();
+    assert!(!response.status().is_success());
+
+    let mut response = surf::post(url("/internal-api/list/fork", port))
+        .header("Cookie", cookie_header.as_str())
+        .body(json!({ "internal_list_id": internal_list_id }))
+        .await
+        .unwrap();
+    assert!(response.status().is_success(), "{:?}", response);
+    let value: Value = response.body_json().await.unwrap();
+    let fork_id = value["internal_list_id"].as_str().unwrap();
+
+    let fork = surf::get(url(&format!("/internal-api/list/get/{}", fork_id), port))
+        .recv_json::<Value>()
+        .await
+        .unwrap();
+    assert_eq!(
+        fork,
+        json!({
+            "internal_list_id": fork_id,
+            "internal_list_name": "a",
+            "internal_user_id": "0",
+            "source_list_id": internal_list_id,
+            "fork_count": 0,
+            "items": [{"problem_id": "problem_1", "memo": "", "tags": []}]
+        })
+    );
+
+    let list = surf::get(url(
+        &format!("/internal-api/list/get/{}", internal_list_id),
+        port,
+    ))
+    .recv_json::<Value>()
+    .await
+    .unwrap();
+    assert_eq!(list["fork_count"], json!(1));
+
+    server.race(ready(())).await;
+}
+
+#[async_std::test]
+async fn test_reorder_and_tag_items() {
+    let port = setup().await;
+    let server = task::spawn(async move {
+        let pg_pool = sql_client::initialize_pool(utils::get_sql_url_from_env())
+            .await
+            .unwrap();
+        run_server(pg_pool, MockAuth, port).await.unwrap();
+    });
+    task::sleep(std::time::Duration::from_millis(1000)).await;
+
+    surf::get(url(
+        &format!("/internal-api/authorize?code={}", VALID_CODE),
+        port,
+    ))
+    .await
+    .unwrap();
+    let cookie_header = format!("token={}", VALID_TOKEN);
+
+    let mut response = surf::post(url("/internal-api/list/create", port))
+        .header("Cookie", cookie_header.as_str())
+        .body(json!({"list_name":"a"}))
+        .await
+        .unwrap();
+    assert!(response.status().is_success(), "{:?}", response);
+    let value: Value = response.body_json().await.unwrap();
+    let internal_list_id = value["internal_list_id"].as_str().unwrap().to_owned();
+    for problem_id in ["problem_1", "problem_2"].iter() {
+        let response = surf::post(url("/internal-api/list/item/add", port))
+            .header("Cookie", cookie_header.as_str())
+            .body(json!({
+                "internal_list_id": internal_list_id,
+                "problem_id": problem_id
+            }))
+            .await
+            .unwrap();
+        assert!(response.status().is_success(), "{:?}", response);
+    }
+
+    let response = surf::post(url("/internal-api/list/item/reorder", port))
+        .header("Cookie", cookie_header.as_str())
+        .body(json!({
+            "internal_list_id": internal_list_id,
+            "problem_ids": ["problem_2", "problem_1"]
+        }))
+        .await
+        .unwrap();
+    assert!(response.status().is_success(), "{:?}", response);
+    let response = surf::post(url("/internal-api/list/item/reorder", port))
+        .header("Cookie", cookie_header.as_str())
+        .body(json!({
+            "internal_list_id": internal_list_id,
+            "problem_ids": ["problem_2"]
+        }))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 400);
+
+    let response = surf::post(url("/internal-api/list/item/tags", port))
+        .header("Cookie", cookie_header.as_str())
+        .body(json!({
+            "internal_list_id": internal_list_id,
+            "problem_id": "problem_1",
+            "tags": ["graph", "dp"]
+        }))
+        .await
+        .unwrap();
+    assert!(response.status().is_success(), "{:?}", response);
+
+    for tags in [json!(["dp", ""]), json!(["x".repeat(256)])].iter() {
+        let response = surf::post(url("/internal-api/list/item/tags", port))
+            .header("Cookie", cookie_header.as_str())
+            .body(json!({
+                "internal_list_id": internal_list_id,
+                "problem_id": "problem_1",
+                "tags": tags
+            }))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 400);
+    }
+
+    let list = surf::get(url(
+        &format!("/internal-api/list/get/{}", internal_list_id),
+        port,
+    ))
+    .recv_json::<Value>()
+    .await
+    .unwrap();
+    assert_eq!(
+        list["items"],
+        json!([
+            {"problem_id": "problem_2", "memo": "", "tags": []},
+            {"problem_id": "problem_1", "memo": "", "tags": ["dp", "graph"]}
+        ])
+    );
+
+    server.race(ready(())).await;
+}
+
+#[async_std::test]
+async fn test_import_and_export_list() {
+    let port = setup().await;
+    let server = task::spawn(async move {
+        let pg_pool = sql_client::initialize_pool(utils::get_sql_url_from_env())
+            .await
+            .unwrap();
+        run_server(pg_pool, MockAuth, port).await.unwrap();
+    });
+    task::sleep(std::time::Duration::from_millis(1000)).await;
+
+    let conn = sql_client::initialize_pool(utils::get_sql_url_from_env())
+        .await
+        .unwrap();
+    sql_client::query(
+        r"
+        INSERT INTO problems (id, contest_id, title)
+        VALUES
+            ('problem_1', 'contest', 'A'),
+            ('problem_2', 'contest', 'B')
+        ",
+    )
+    .execute(&conn)
+    .await
+    .unwrap();
+
+    surf::get(url(
+        &format!("/internal-api/authorize?code={}", VALID_CODE),
+        port,
+    ))
+    .await
+    .unwrap();
+    let cookie_header = format!("token={}", VALID_TOKEN);
+
+    let mut response = surf::post(url(
+        "/internal-api/list/import?format=csv&list_name=imported",
+        port,
+    ))
+    .header("Cookie", cookie_header.as_str())
+    .body("problem_id,memo\r\nproblem_2,\"b, memo\"\r\nproblem_3,x\r\nproblem_1,a\r\n")
+    .await
+    .unwrap();
+    assert!(response.status().is_success(), "{:?}", response);
+    let value: Value = response.body_json().await.unwrap();
+    let internal_list_id = value["internal_list_id"].as_str().unwrap().to_owned();
+    assert_eq!(value["imported"], json!(2));
+    assert_eq!(
+        value["rejected"],
+        json!([{"row": 2, "problem_id": "problem_3", "reason": "unknown_problem"}])
+    );
+
+    let mut response = surf::get(url(
+        &format!("/internal-api/list/export/{}?format=csv", internal_list_id),
+        port,
+    ))
+    .await
+    .unwrap();
+    assert!(response.status().is_success());
+    assert_eq!(
+        response.body_string().await.unwrap(),
+        "problem_id,memo\r\nproblem_2,\"b, memo\"\r\nproblem_1,a\r\n"
+    );
+
+    let mut exported = surf::get(url(
+        &format!("/internal-api/list/export/{}", internal_list_id),
+        port,
+    ))
+    .recv_json::<Value>()
+    .await
+    .unwrap();
+    assert_eq!(exported["internal_list_name"], json!("imported"));
+    assert_eq!(exported["items"][0]["memo"], json!("b, memo"));
+
+    let mut response = surf::post(url("/internal-api/list/create", port))
+        .header("Cookie", cookie_header.as_str())
+        .body(json!({"list_name":"a"}))
+        .await
+        .unwrap();
+    let value: Value = response.body_json().await.unwrap();
+    let other_list_id = value["internal_list_id"].as_str().unwrap().to_owned();
+    // The problem ids are trimmed as well as the CSV cells.
+    exported["items"][0]["problem_id"] = json!(" problem_2 ");
+    let mut response = surf::post(url(
+        &format!(
+            "/internal-api/list/import?internal_list_id={}",
+            other_list_id
+        ),
+        port,
+    ))
+    .header("Cookie", cookie_header.as_str())
+    .body(exported)
+    .await
+    .unwrap();
+    assert!(response.status().is_success(), "{:?}", response);
+    let value: Value = response.body_json().await.unwrap();
+    assert_eq!(value["internal_list_id"], json!(other_list_id));
+    assert_eq!(value["imported"], json!(2));
+
+    let response = surf::post(url("/internal-api/list/import?format=csv", port))
+        .header("Cookie", cookie_header.as_str())
+        .body("memo\r\nx\r\n")
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 400);
+
+    server.race(ready(())).await;
+}
+
+#[async_std::test]
+async fn test_list_progress() {
+    let port = setup().await;
+    let server = task::spawn(async move {
+        let pg_pool = sql_client::initialize_pool(utils::get_sql_url_from_env())
+            .await
+            .unwrap();
+        run_server(pg_pool, MockAuth, port).await.unwrap();
+    });
+    task::sleep(std::time::Duration::from_millis(1000)).await;
+
+    let conn = sql_client::initialize_pool(utils::get_sql_url_from_env())
+        .await
+        .unwrap();
+    sql_client::query(
+        r"
+        INSERT INTO submissions
+            (id, epoch_second, problem_id, contest_id, user_id, language, point, length, result)
+        VALUES
+            (1, 100, 'problem_1', 'contest', 'user', 'Rust', 0.0, 0, 'WA'),
+            (2, 200, 'problem_2', 'contest', 'user', 'Rust', 100.0, 0, 'AC')
+        ",
+    )
+    .execute(&conn)
+    .await
+    .unwrap();
+
+    surf::get(url(
+        &format!("/internal-api/authorize?code={}", VALID_CODE),
+        port,
+    ))
+    .await
+    .unwrap();
+    let cookie_header = format!("token={}", VALID_TOKEN);
+
+    let mut response = surf::post(url("/internal-api/list/create", port))
+        .header("Cookie", cookie_header.as_str())
+        .body(json!({"list_name":"a"}))
+        .await
+        .unwrap();
+    let value: Value = response.body_json().await.unwrap();
+    let internal_list_id = value["internal_list_id"].as_str().unwrap().to_owned();
+    for problem_id in ["problem_1", "problem_2", "problem_3"].iter() {
+        let response = surf::post(url("/internal-api/list/item/add", port))
+            .header("Cookie", cookie_header.as_str())
+            .body(json!({
+                "internal_list_id": internal_list_id,
+                "problem_id": problem_id
+            }))
+            .await
+            .unwrap();
+        assert!(response.status().is_success(), "{:?}", response);
+    }
+
+    let progress = surf::get(url(
+        &format!("/internal-api/list/progress/{}?user=user", internal_list_id),
+        port,
+    ))
+    .recv_json::<Value>()
+    .await
+    .unwrap();
+    assert_eq!(
+        progress,
+        json!([
+            {"problem_id": "problem_1", "status": "tried", "first_ac_epoch_second": null},
+            {"problem_id": "problem_2", "status": "accepted", "first_ac_epoch_second": 200},
+            {"problem_id": "problem_3", "status": "untouched", "first_ac_epoch_second": null}
+        ])
+    );
+
+    let response = surf::get(url(
+        "/internal-api/list/progress/nonexistent?user=user",
+        port,
+    ))
+    .await
+    .unwrap();
+    assert_eq!(response.status(), 404);
+
+    server.race(ready(())).await;
+}
+
+#[async_std::test]
+async fn test_list_editors() {
+    let port = setup().await;
+    let server = task::spawn(async move {
+        let pg_pool = sql_client::initialize_pool(utils::get_sql_url_from_env())
+            .await
+            .unwrap();
+        run_server(pg_pool, MockAuth, port).await.unwrap();
+    });
+    task::sleep(std::time::Duration::from_millis(1000)).await;
+    for code in [VALID_CODE, OTHER_CODE].iter() {
+        surf::get(url(&format!("/internal-api/authorize?code={}", code), port))
+            .await
+            .unwrap();
+    }
+    let cookie_header = format!("token={}", VALID_TOKEN);
+    let other_cookie_header = format!("token={}", OTHER_TOKEN);
+
+    let mut response = surf::post(url("/internal-api/list/create", port))
+        .header("Cookie", cookie_header.as_str())
+        .body(json!({"list_name":"a"}))
+        .await
+        .unwrap();
+    let value: Value = response.body_json().await.unwrap();
+    let internal_list_id = value["internal_list_id"].as_str().unwrap().to_owned();
+
+    let add_item = json!({
+        "internal_list_id": internal_list_id,
+        "problem_id": "problem_1"
+    });
+    let response = surf::post(url("/internal-api/list/item/add", port))
+        .header("Cookie", other_cookie_header.as_str())
+        .body(add_item.clone())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 403);
+
+    let editor = json!({ "internal_list_id": internal_list_id, "user_id": "1" });
+    let response = surf::post(url("/internal-api/list/editor/add", port))
+        .header("Cookie", other_cookie_header.as_str())
+        .body(editor.clone())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 403);
+    let response = surf::post(url("/internal-api/list/editor/add", port))
+        .header("Cookie", cookie_header.as_str())
+        .body(editor.clone())
+        .await
+        .unwrap();
+    assert!(response.status().is_success(), "{:?}", response);
+    let editors = surf::get(url(
+        &format!("/internal-api/list/editor/list/{}", internal_list_id),
+        port,
+    ))
+    .recv_json::<Value>()
+    .await
+    .unwrap();
+    assert_eq!(
+        editors,
+        json!([{"internal_user_id": "1", "atcoder_user_id": null}])
+    );
+
+    let response = surf::post(url("/internal-api/list/item/add", port))
+        .header("Cookie", other_cookie_header.as_str())
+        .body(add_item)
+        .await
+        .unwrap();
+    assert!(response.status().is_success(), "{:?}", response);
+    let shared = surf::get(url("/internal-api/list/shared", port))
+        .header("Cookie", other_cookie_header.as_str())
+        .recv_json::<Value>()
+        .await
+        .unwrap();
+    assert_eq!(shared[0]["internal_list_id"], json!(internal_list_id));
+    assert_eq!(shared[0]["items"][0]["problem_id"], json!("problem_1"));
+
+    let response = surf::post(url("/internal-api/list/update", port))
+        .header("Cookie", other_cookie_header.as_str())
+        .body(json!({ "internal_list_id": internal_list_id, "name": "b" }))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 403);
+    let response = surf::post(url("/internal-api/list/delete", port))
+        .header("Cookie", other_cookie_header.as_str())
+        .body(json!({ "internal_list_id": internal_list_id }))
+        .await
+        .unwrap();
+    assert!(!response.status().is_success());
+
+    let response = surf::post(url("/internal-api/list/editor/remove", port))
+        .header("Cookie", other_cookie_header.as_str())
+        .body(editor)
+        .await
+        .unwrap();
+    assert!(response.status().is_success(), "{:?}", response);
+    let response = surf::post(url("/internal-api/list/item/delete", port))
+        .header("Cookie", other_cookie_header.as_str())
+        .body(json!({
+            "internal_list_id": internal_list_id,
+            "problem_id": "problem_1"
+        }))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 403);
+
+    server.race(ready(())).await;
+}
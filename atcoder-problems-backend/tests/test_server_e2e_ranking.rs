@@ -0,0 +1,192 @@
+use async_std::future::ready;
+use async_std::prelude::*;
+use async_std::task;
+use async_trait::async_trait;
+use atcoder_problems_backend::server::GitHubUserResponse;
+use atcoder_problems_backend::server::{run_server, Authentication};
+use rand::Rng;
+use serde_json::Value;
+use sql_client::PgPool;
+use tide::Result;
+
+pub mod utils;
+
+#[derive(Clone)]
+struct MockAuth;
+
+#[async_trait]
+impl Authentication for MockAuth {
+    async fn get_token(&self, _: &str) -> Result<String> {
+        unimplemented!()
+    }
+    async fn get_user_id(&self, _: &str) -> Result<GitHubUserResponse> {
+        unimplemented!()
+    }
+}
+
+async fn prepare_data_set(conn: &PgPool) {
+    sql_client::query(
+        r"
+        INSERT INTO accepted_count (user_id, problem_count)
+        VALUES ('u1', 5), ('u2', 4), ('u3', 3), ('u4', 3), ('u5', 1)",
+    )
+    .execute(conn)
+    .await
+    .unwrap();
+    sql_client::query(
+        r"
+        INSERT INTO language_count (user_id, simplified_language, problem_count)
+        VALUES ('u1', 'Rust', 2), ('u2', 'Rust', 3), ('u3', 'C++', 3)",
+    )
+    .execute(conn)
+    .await
+    .unwrap();
+    sql_client::query(
+        r"
+        INSERT INTO max_streaks (user_id, streak, current_streak, last_ac_date)
+        VALUES
+            ('u1', 9, 2, (NOW() AT TIME ZONE 'Asia/Tokyo')::DATE),
+            ('u2', 9, 4, (NOW() AT TIME ZONE 'Asia/Tokyo')::DATE - 1),
+            ('u3', 9, 9, '2000-01-01')",
+    )
+    .execute(conn)
+    .await
+    .unwrap();
+}
+
+fn url(path: &str, port: u16) -> String {
+    format!("http://localhost:{}{}", port, path)
+}
+
+async fn setup() -> u16 {
+    prepare_data_set(&utils::initialize_and_connect_to_test_sql().await).await;
+    let mut rng = rand::thread_rng();
+    rng.gen::<u16>() % 30000 + 30000
+}
+
+fn user_ids(entries: &Value) -> Vec<&str> {
+    entries
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|e| e["user_id"].as_str().unwrap())
+        .collect()
+}
+
+#[async_std::test]
+async fn test_ranking() {
+    let port = setup().await;
+    let server = task::spawn(async move {
+        let pg_pool = sql_client::initialize_pool(utils::get_sql_url_from_env())
+            .await
+            .unwrap();
+        run_server(pg_pool, MockAuth, port).await.unwrap();
+    });
+    task::sleep(std::time::Duration::from_millis(1000)).await;
+
+    let response: Value = surf::get(url("/atcoder-api/v3/ranking/ac?offset=1&limit=2", port))
+        .await
+        .unwrap()
+        .body_json()
+        .await
+        .unwrap();
+    assert_eq!(user_ids(&response), vec!["u2", "u3"]);
+
+    let response: Value = surf::get(url(
+        "/atcoder-api/v3/ranking/ac/around?user=u4&limit=3",
+        port,
+    ))
+    .await
+    .unwrap()
+    .body_json()
+    .await
+    .unwrap();
+    assert_eq!(response["rank"], 2);
+    assert_eq!(response["offset"], 2);
+    assert_eq!(user_ids(&response["entries"]), vec!["u3", "u4", "u5"]);
+
+    let response: Value = surf::get(url(
+        "/atcoder-api/v3/ranking/lang/around?user=u1&language=Rust",
+        port,
+    ))
+    .await
+    .unwrap()
+    .body_json()
+    .await
+    .unwrap();
+    assert_eq!(response["rank"], 1);
+    assert_eq!(response["offset"], 0);
+    assert_eq!(user_ids(&response["entries"]), vec!["u2", "u1"]);
+
+    let response = surf::get(url("/atcoder-api/v3/ranking/ac/around?user=u9", port))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 404);
+
+    let response = surf::get(url(
+        "/atcoder-api/v3/ranking/lang/around?user=u3&language=Rust",
+        port,
+    ))
+    .await
+    .unwrap();
+    assert_eq!(response.status(), 404);
+
+    server.race(ready(())).await;
+}
+
+#[async_std::test]
+async fn test_current_streak() {
+    let port = setup().await;
+    let server = task::spawn(async move {
+        let pg_pool = sql_client::initialize_pool(utils::get_sql_url_from_env())
+            .await
+            .unwrap();
+        run_server(pg_pool, MockAuth, port).await.unwrap();
+    });
+    task::sleep(std::time::Duration::from_millis(1000)).await;
+
+    let response: Value = surf::get(url("/atcoder-api/v3/ranking/current_streak", port))
+        .await
+        .unwrap()
+        .body_json()
+        .await
+        .unwrap();
+    assert_eq!(user_ids(&response), vec!["u2", "u1"]);
+
+    let response: Value = surf::get(url(
+        "/atcoder-api/v3/ranking/current_streak/around?user=u1",
+        port,
+    ))
+    .await
+    .unwrap()
+    .body_json()
+    .await
+    .unwrap();
+    assert_eq!(response["rank"], 1);
+
+    let response = surf::get(url(
+        "/atcoder-api/v3/ranking/current_streak/around?user=u3",
+        port,
+    ))
+    .await
+    .unwrap();
+    assert_eq!(response.status(), 404);
+
+    let response: Value = surf::get(url("/atcoder-api/v2/user_info?user=u1", port))
+        .await
+        .unwrap()
+        .body_json()
+        .await
+        .unwrap();
+    assert_eq!(response["current_streak"], 2);
+    let response: Value = surf::get(url("/atcoder-api/v2/user_info?user=u3", port))
+        .await
+        .unwrap()
+        .body_json()
+        .await
+        .unwrap();
+    assert_eq!(response["current_streak"], 0);
+    assert_eq!(response["last_ac_date"], "2000-01-01");
+
+    server.race(ready(())).await;
+}
@@ -0,0 +1,150 @@
+use async_std::prelude::*;
+use async_std::task;
+use async_trait::async_trait;
+use atcoder_problems_backend::server::{run_server, Authentication, GitHubUserResponse};
+use rand::Rng;
+use serde_json::{json, Value};
+use std::time::Duration;
+use tide::Result;
+
+pub mod utils;
+
+#[derive(Clone)]
+struct MockAuth;
+#[async_trait]
+impl Authentication for MockAuth {
+    async fn get_token(&self, _: &str) -> Result<String> {
+        Ok(String::new())
+    }
+
+    async fn get_user_id(&self, _: &str) -> Result<GitHubUserResponse> {
+        Ok(GitHubUserResponse::default())
+    }
+}
+
+async fn setup() -> u16 {
+    let conn = utils::initialize_and_connect_to_test_sql().await;
+    // 2014-11-28T23:30:00+09:00 and 2014-11-29T00:30:00+09:00
+    sql_client::query(
+        r"
+        INSERT INTO
+            submissions (epoch_second, problem_id, contest_id, user_id, result, id, language, point, length)
+            VALUES
+                (1417185000, 'p1', 'c1', 'u1', 'AC', 1, 'Rust', 0.0, 0),
+                (1417188600, 'p2', 'c1', 'u1', 'AC', 2, 'Rust', 0.0, 0),
+                (1417188600, 'p3', 'c1', 'u1', 'WA', 3, 'Rust', 0.0, 0)",
+    )
+    .execute(&conn)
+    .await
+    .unwrap();
+    let mut rng = rand::thread_rng();
+    rng.gen::<u16>() % 30000 + 30000
+}
+
+fn url(path: &str, port: u16) -> String {
+    format!("http://localhost:{}{}", port, path)
+}
+
+#[async_std::test]
+async fn test_streak_timezone() {
+    let port = setup().await;
+    let server = async_std::task::spawn(async move {
+        let pg_pool = sql_client::initialize_pool(utils::get_sql_url_from_env())
+            .await
+            .unwrap();
+        run_server(pg_pool, MockAuth, port).await.unwrap();
+    });
+    task::sleep(Duration::from_millis(1000)).await;
+
+    let response = surf::get(url("/atcoder-api/v3/user/streak?user=u1", port))
+        .recv_json::<Value>()
+        .await
+        .unwrap();
+    assert_eq!(response["timezone_offset"], 540);
+    assert_eq!(response["max_streak"], 2);
+    assert_eq!(response["current_streak"], 0);
+    assert_eq!(response["last_ac_date"], "2014-11-29");
+    assert_eq!(
+        response["daily_accepted_count"],
+        json!([
+            {"date": "2014-11-28", "count": 1},
+            {"date": "2014-11-29", "count": 1}
+        ])
+    );
+
+    let response = surf::get(url(
+        "/atcoder-api/v3/user/streak?user=u1&timezone_offset=0",
+        port,
+    ))
+    .recv_json::<Value>()
+    .await
+    .unwrap();
+    assert_eq!(response["max_streak"], 1);
+    assert_eq!(response["last_ac_date"], "2014-11-28");
+
+    let response = surf::get(url(
+        "/atcoder-api/v3/user/streak?user=u1&timezone_offset=1440",
+        port,
+    ))
+    .await
+    .unwrap();
+    assert_eq!(response.status(), 400);
+
+    // The user is not registered before the authorization.
+    let response = surf::post(url("/internal-api/user/update", port))
+        .header("Cookie", "token=a")
+        .body(json!({"atcoder_user_id": "u1", "timezone_offset": 0}))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 404);
+
+    let response = surf::get(url("/internal-api/authorize?code=a", port))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 302);
+
+    let response = surf::get(url("/internal-api/user/streak", port))
+        .header("Cookie", "token=a")
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 400);
+
+    let response = surf::post(url("/internal-api/user/update", port))
+        .header("Cookie", "token=a")
+        .body(json!({"atcoder_user_id": "u1", "timezone_offset": 0}))
+        .await
+        .unwrap();
+    assert!(response.status().is_success());
+
+    let response = surf::get(url("/internal-api/user/get", port))
+        .header("Cookie", "token=a")
+        .recv_json::<Value>()
+        .await
+        .unwrap();
+    assert_eq!(response["timezone_offset"], 0);
+
+    let response = surf::get(url("/internal-api/user/streak", port))
+        .header("Cookie", "token=a")
+        .recv_json::<Value>()
+        .await
+        .unwrap();
+    assert_eq!(response["user_id"], "u1");
+    assert_eq!(response["timezone_offset"], 0);
+    assert_eq!(response["max_streak"], 1);
+
+    let response = surf::get(url("/internal-api/user/streak?timezone_offset=540", port))
+        .header("Cookie", "token=a")
+        .recv_json::<Value>()
+        .await
+        .unwrap();
+    assert_eq!(response["max_streak"], 2);
+
+    let response = surf::post(url("/internal-api/user/update", port))
+        .header("Cookie", "token=a")
+        .body(json!({"atcoder_user_id": "u1", "timezone_offset": -100000}))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 400);
+
+    server.race(async_std::future::ready(())).await;
+}
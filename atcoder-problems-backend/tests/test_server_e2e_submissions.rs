@@ -5,7 +5,9 @@ use async_trait::async_trait;
 use atcoder_problems_backend::server::GitHubUserResponse;
 use atcoder_problems_backend::server::{run_server, Authentication};
 use rand::Rng;
+use serde_json::Value;
 use sql_client::models::Submission;
+use sql_client::submission_client::SubmissionClient;
 use sql_client::PgPool;
 use tide::Result;
 
@@ -98,6 +100,211 @@ async fn test_user_submissions() {
     server.race(ready(())).await;
 }
 
+#[async_std::test]
+async fn test_user_submission_page() {
+    let port = setup().await;
+    let server = task::spawn(async move {
+        let pg_pool = sql_client::initialize_pool(utils::get_sql_url_from_env())
+            .await
+            .unwrap();
+        run_server(pg_pool, MockAuth, port).await.unwrap();
+    });
+    task::sleep(std::time::Duration::from_millis(1000)).await;
+
+    let response: Value = surf::get(url(
+        "/atcoder-api/v4/user/submissions?user=u1&limit=3",
+        port,
+    ))
+    .await
+    .unwrap()
+    .body_json()
+    .await
+    .unwrap();
+    let ids = response["submissions"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|s| s["id"].as_i64().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(ids, vec![1, 2, 3]);
+    assert_eq!(response["next"]["from_second"], 2);
+    assert_eq!(response["next"]["from_id"], 3);
+
+    let response: Value = surf::get(url(
+        "/atcoder-api/v4/user/submissions?user=u1&limit=3&from_second=2&from_id=3",
+        port,
+    ))
+    .await
+    .unwrap()
+    .body_json()
+    .await
+    .unwrap();
+    let ids = response["submissions"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|s| s["id"].as_i64().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(ids, vec![4, 5]);
+    assert!(response["next"].is_null());
+
+    let response = surf::get(url("/atcoder-api/v4/user/submissions", port))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 400);
+
+    server.race(ready(())).await;
+}
+
+#[async_std::test]
+async fn test_conditional_get() {
+    let port = setup().await;
+    let server = task::spawn(async move {
+        let pg_pool = sql_client::initialize_pool(utils::get_sql_url_from_env())
+            .await
+            .unwrap();
+        run_server(pg_pool, MockAuth, port).await.unwrap();
+    });
+    task::sleep(std::time::Duration::from_millis(1000)).await;
+
+    let response = surf::get(url("/atcoder-api/results?user=u1", port))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 200);
+    let etag = response.header("etag").unwrap().as_str().to_owned();
+    assert!(response.header("last-modified").is_none());
+
+    let response = surf::get(url("/atcoder-api/results?user=u1", port))
+        .header("If-None-Match", etag.as_str())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 304);
+    assert_eq!(response.header("etag").unwrap().as_str(), etag);
+
+    let response = surf::get(url("/atcoder-api/results?user=u2", port))
+        .header("If-None-Match", etag.as_str())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 200);
+
+    let response = surf::get(url("/atcoder-api/v3/from/100", port))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 200);
+    let etag = response.header("etag").unwrap().as_str().to_owned();
+
+    let response = surf::get(url("/atcoder-api/v3/from/100", port))
+        .header("If-None-Match", etag.as_str())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 304);
+
+    sql_client::query(
+        r"
+        INSERT INTO submissions (epoch_second, problem_id, contest_id, user_id, result, id, language, point, length)
+        VALUES (300, 'p1', 'c1', 'u1', 'AC', 11, 'Rust', 0.0, 0)",
+    )
+    .execute(
+        &sql_client::initialize_pool(utils::get_sql_url_from_env())
+            .await
+            .unwrap(),
+    )
+    .await
+    .unwrap();
+
+    let response = surf::get(url("/atcoder-api/v3/from/100", port))
+        .header("If-None-Match", etag.as_str())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 200);
+
+    let response = surf::get(url("/atcoder-api/results?user=u1", port))
+        .await
+        .unwrap();
+    let etag = response.header("etag").unwrap().as_str().to_owned();
+    sql_client::initialize_pool(utils::get_sql_url_from_env())
+        .await
+        .unwrap()
+        .update_submissions(&[Submission {
+            id: 11,
+            epoch_second: 300,
+            problem_id: "p1".to_owned(),
+            contest_id: "c1".to_owned(),
+            user_id: "u1".to_owned(),
+            language: "Rust".to_owned(),
+            result: "WA".to_owned(),
+            ..Default::default()
+        }])
+        .await
+        .unwrap();
+    let response = surf::get(url("/atcoder-api/results?user=u1", port))
+        .header("If-None-Match", etag.as_str())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 200);
+
+    server.race(ready(())).await;
+}
+
+#[async_std::test]
+async fn test_filtered_submissions() {
+    let port = setup().await;
+    let server = task::spawn(async move {
+        let pg_pool = sql_client::initialize_pool(utils::get_sql_url_from_env())
+            .await
+            .unwrap();
+        run_server(pg_pool, MockAuth, port).await.unwrap();
+    });
+    task::sleep(std::time::Duration::from_millis(1000)).await;
+
+    let submissions: Vec<Submission> = surf::get(url(
+        "/atcoder-api/v4/submissions?users=u1,u2&problems=p1&results=WA,RE&language=Rust",
+        port,
+    ))
+    .await
+    .unwrap()
+    .body_json()
+    .await
+    .unwrap();
+    let ids = submissions.iter().map(|s| s.id).collect::<Vec<_>>();
+    assert_eq!(ids, vec![1, 2, 6, 7]);
+
+    let submissions: Vec<Submission> = surf::get(url(
+        "/atcoder-api/v4/submissions?users=u2&results=AC&order=desc&limit=2",
+        port,
+    ))
+    .await
+    .unwrap()
+    .body_json()
+    .await
+    .unwrap();
+    let ids = submissions.iter().map(|s| s.id).collect::<Vec<_>>();
+    assert_eq!(ids, vec![10, 9]);
+
+    let submissions: Vec<Submission> = surf::get(url(
+        "/atcoder-api/v4/submissions?contests=c1&from=3&to=100",
+        port,
+    ))
+    .await
+    .unwrap()
+    .body_json()
+    .await
+    .unwrap();
+    let ids = submissions.iter().map(|s| s.id).collect::<Vec<_>>();
+    assert_eq!(ids, vec![4, 6, 7, 8, 9, 5]);
+
+    let response = surf::get(url("/atcoder-api/v4/submissions?results=AC", port))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 400);
+    let response = surf::get(url("/atcoder-api/v4/submissions?users=,&results=AC", port))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 400);
+
+    server.race(ready(())).await;
+}
+
 #[async_std::test]
 async fn test_time_submissions() {
     let port = setup().await;
@@ -15,18 +15,22 @@ struct MockAuth;
 
 const VALID_CODE: &str = "VALID-CODE";
 const VALID_TOKEN: &str = "VALID-TOKEN";
+const OTHER_CODE: &str = "OTHER-CODE";
+const OTHER_TOKEN: &str = "OTHER-TOKEN";
 
 #[async_trait]
 impl Authentication for MockAuth {
     async fn get_token(&self, code: &str) -> Result<String> {
         match code {
             VALID_CODE => Ok(VALID_TOKEN.to_owned()),
+            OTHER_CODE => Ok(OTHER_TOKEN.to_owned()),
             _ => Err(anyhow::anyhow!("error").into()),
         }
     }
     async fn get_user_id(&self, token: &str) -> Result<GitHubUserResponse> {
         match token {
             VALID_TOKEN => Ok(GitHubUserResponse::default()),
+            OTHER_TOKEN => Ok(serde_json::from_value(json!({"id": 1, "login": "other"}))?),
             _ => Err(anyhow::anyhow!("error").into()),
         }
     }
@@ -84,6 +88,21 @@ async fn test_virtual_contest() {
     let body = response.body_json::<Value>().await.unwrap();
     let contest_id = body["contest_id"].as_str().unwrap();
 
+    let response = surf::post(url("/internal-api/contest/update", port))
+        .header("Cookie", cookie_header.as_str())
+        .body(json!({
+            "id": format!("{}", contest_id),
+            "title": "contest title",
+            "memo": "contest memo",
+            "start_epoch_second": 1,
+            "duration_second": 2,
+            "mode": "unknown",
+            "penalty_second": 300,
+        }))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 400);
+
     let response = surf::post(url("/internal-api/contest/update", port))
         .header("Cookie", cookie_header.as_str())
         .body(json!({
@@ -116,6 +135,7 @@ async fn test_virtual_contest() {
                 "mode": null,
                 "is_public": true,
                 "penalty_second": 300,
+                "freeze_second": 0,
             }
         ])
     );
@@ -154,6 +174,7 @@ async fn test_virtual_contest() {
                 "mode": null,
                 "is_public": true,
                 "penalty_second": 300,
+                "freeze_second": 0,
             }
         ])
     );
@@ -201,6 +222,7 @@ async fn test_virtual_contest() {
                 "mode": null,
                 "is_public": true,
                 "penalty_second": 300,
+                "freeze_second": 0,
             }
         ])
     );
@@ -253,6 +275,7 @@ async fn test_virtual_contest() {
                 "mode": null,
                 "is_public": true,
                 "penalty_second": 300,
+                "freeze_second": 0,
             }
         ])
     );
@@ -277,9 +300,11 @@ async fn test_virtual_contest() {
                 "mode": null,
                 "is_public": true,
                 "penalty_second": 300,
+                "freeze_second": 0,
             },
             "problems": [{ "id": "problem_1", "point": 100, "order": null }, { "id": "problem_2", "point": null, "order": null }],
             "participants": ["atcoder_user1"],
+            "teams": [],
         })
     );
 
@@ -300,10 +325,32 @@ async fn test_virtual_contest() {
                 "id": format!("{}", contest_id),
                 "mode": null,
                 "penalty_second": 300,
+                "freeze_second": 0,
             }
         ])
     );
 
+    let response = surf::get(url(
+        "/internal-api/contest/search?keyword=TITLE&state=finished&owner=0",
+        port,
+    ))
+    .recv_json::<Value>()
+    .await
+    .unwrap();
+    assert_eq!(response[0]["id"].as_str().unwrap(), contest_id);
+    assert_eq!(response.as_array().unwrap().len(), 1);
+
+    let response = surf::get(url("/internal-api/contest/search?state=upcoming", port))
+        .recv_json::<Value>()
+        .await
+        .unwrap();
+    assert_eq!(response, json!([]));
+
+    let response = surf::get(url("/internal-api/contest/search?state=unknown", port))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 400);
+
     server.race(async_std::future::ready(())).await;
 }
 
@@ -414,3 +461,1302 @@ async fn test_virtual_contest_visibility() {
 
     server.race(async_std::future::ready(())).await;
 }
+
+#[async_std::test]
+async fn test_virtual_contest_standings() {
+    let port = setup().await;
+    let server = task::spawn(async move {
+        let pg_pool = sql_client::initialize_pool(utils::get_sql_url_from_env())
+            .await
+            .unwrap();
+        run_server(pg_pool, MockAuth, port).await.unwrap();
+    });
+    task::sleep(std::time::Duration::from_millis(1000)).await;
+    surf::get(url(
+        &format!("/internal-api/authorize?code={}", VALID_CODE),
+        port,
+    ))
+    .await
+    .unwrap();
+    let cookie_header = format!("token={}", VALID_TOKEN);
+
+    let response = surf::post(url("/internal-api/user/update", port))
+        .header("Cookie", cookie_header.as_str())
+        .body(json!({
+            "atcoder_user_id": "atcoder_user1"
+        }))
+        .await
+        .unwrap();
+    assert!(response.status().is_success());
+    let mut response = surf::post(url("/internal-api/contest/create", port))
+        .header("Cookie", cookie_header.as_str())
+        .body(json!({
+            "title": "contest title",
+            "memo": "contest memo",
+            "start_epoch_second": 100,
+            "duration_second": 1000,
+            "penalty_second": 300,
+        }))
+        .await
+        .unwrap();
+    assert!(response.status().is_success());
+    let body = response.body_json::<Value>().await.unwrap();
+    let contest_id = body["contest_id"].as_str().unwrap();
+
+    let response = surf::post(url("/internal-api/contest/item/update", port))
+        .header("Cookie", cookie_header.as_str())
+        .body(json!({
+            "contest_id": contest_id,
+            "problems": [{ "id": "problem_1", "point": 500 }, { "id": "problem_2" }],
+        }))
+        .await
+        .unwrap();
+    assert!(response.status().is_success());
+    let response = surf::post(url("/internal-api/contest/join", port))
+        .header("Cookie", cookie_header.as_str())
+        .body(json!({ "contest_id": contest_id }))
+        .await
+        .unwrap();
+    assert!(response.status().is_success());
+
+    let conn = sql_client::initialize_pool(utils::get_sql_url_from_env())
+        .await
+        .unwrap();
+    sql_client::query(
+        r"
+        INSERT INTO
+            submissions (epoch_second, problem_id, contest_id, user_id, result, id, language, point, length)
+            VALUES
+                (50,   'problem_1', 'c1', 'atcoder_user1', 'AC', 1, 'Rust', 100.0, 0),
+                (110,  'problem_1', 'c1', 'atcoder_user1', 'WA', 2, 'Rust', 0.0,   0),
+                (120,  'problem_1', 'c1', 'atcoder_user1', 'AC', 3, 'Rust', 100.0, 0),
+                (200,  'problem_2', 'c1', 'atcoder_user1', 'AC', 4, 'Rust', 300.0, 0),
+                (150,  'problem_3', 'c1', 'atcoder_user1', 'AC', 5, 'Rust', 100.0, 0),
+                (150,  'problem_1', 'c1', 'atcoder_user2', 'AC', 6, 'Rust', 100.0, 0),
+                (1200, 'problem_2', 'c1', 'atcoder_user1', 'AC', 7, 'Rust', 400.0, 0)",
+    )
+    .execute(&conn)
+    .await
+    .unwrap();
+
+    let response = surf::get(url(
+        &format!("/internal-api/contest/standings/{}", contest_id),
+        port,
+    ))
+    .recv_json::<Value>()
+    .await
+    .unwrap();
+    assert_eq!(
+        response,
+        json!({
+            "contest_id": contest_id,
+            "penalty_second": 300,
+            "frozen": false,
+            "standings": [{
+                "rank": 1,
+                "user_id": "atcoder_user1",
+                "point": 800.0,
+                "penalties": 1,
+                "elapsed_second": 100,
+                "time_second": 400,
+                "problems": [
+                    {
+                        "problem_id": "problem_1",
+                        "point": 500.0,
+                        "accepted": true,
+                        "trials": 2,
+                        "penalties": 1,
+                        "elapsed_second": 20,
+                    },
+                    {
+                        "problem_id": "problem_2",
+                        "point": 300.0,
+                        "accepted": true,
+                        "trials": 1,
+                        "penalties": 0,
+                        "elapsed_second": 100,
+                    }
+                ]
+            }]
+        })
+    );
+
+    let response = surf::get(url(
+        &format!("/internal-api/contest/standings/export/{}", contest_id),
+        port,
+    ))
+    .recv_json::<Value>()
+    .await
+    .unwrap();
+    assert_eq!(response["title"], json!("contest title"));
+    assert_eq!(response["problem_ids"], json!(["problem_1", "problem_2"]));
+    assert_eq!(
+        response["standings"][0]["problems"][1]["point"],
+        json!(300.0)
+    );
+    assert_eq!(response["standings"][0]["team_members"], json!(null));
+
+    let mut response = surf::get(url(
+        &format!(
+            "/internal-api/contest/standings/export/{}?format=csv",
+            contest_id
+        ),
+        port,
+    ))
+    .await
+    .unwrap();
+    assert!(response.status().is_success());
+    assert_eq!(
+        response.body_string().await.unwrap(),
+        "rank,user_id,team_members,\
+         problem_1 point,problem_1 elapsed_second,problem_1 penalties,\
+         problem_2 point,problem_2 elapsed_second,problem_2 penalties,\
+         point,penalties,time_second\r\n\
+         1,atcoder_user1,,500,20,1,300,100,0,800,1,400\r\n"
+    );
+
+    let response = surf::get(url(
+        &format!(
+            "/internal-api/contest/standings/export/{}?format=xml",
+            contest_id
+        ),
+        port,
+    ))
+    .await
+    .unwrap();
+    assert_eq!(response.status(), 400);
+
+    for path in ["standings", "standings/export"].iter() {
+        let response = surf::get(url(
+            &format!("/internal-api/contest/{}/unknown_contest", path),
+            port,
+        ))
+        .await
+        .unwrap();
+        assert_eq!(response.status(), 404);
+    }
+
+    server.race(async_std::future::ready(())).await;
+}
+
+#[async_std::test]
+async fn test_virtual_contest_owner_authorization() {
+    let port = setup().await;
+    let server = task::spawn(async move {
+        let pg_pool = sql_client::initialize_pool(utils::get_sql_url_from_env())
+            .await
+            .unwrap();
+        run_server(pg_pool, MockAuth, port).await.unwrap();
+    });
+    task::sleep(std::time::Duration::from_millis(1000)).await;
+    for code in [VALID_CODE, OTHER_CODE].iter() {
+        surf::get(url(&format!("/internal-api/authorize?code={}", code), port))
+            .await
+            .unwrap();
+    }
+    let cookie_header = format!("token={}", VALID_TOKEN);
+    let other_cookie_header = format!("token={}", OTHER_TOKEN);
+
+    let mut response = surf::post(url("/internal-api/contest/create", port))
+        .header("Cookie", cookie_header.as_str())
+        .body(json!({
+            "title": "contest title",
+            "memo": "contest memo",
+            "start_epoch_second": 1,
+            "duration_second": 2,
+            "penalty_second": 0,
+        }))
+        .await
+        .unwrap();
+    assert!(response.status().is_success());
+    let body = response.body_json::<Value>().await.unwrap();
+    let contest_id = body["contest_id"].as_str().unwrap();
+
+    let update_body = json!({
+        "id": contest_id,
+        "title": "hijacked",
+        "memo": "",
+        "start_epoch_second": 1,
+        "duration_second": 2,
+        "penalty_second": 0,
+    });
+    let response = surf::post(url("/internal-api/contest/update", port))
+        .header("Cookie", other_cookie_header.as_str())
+        .body(update_body.clone())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 403);
+
+    let response = surf::post(url("/internal-api/contest/item/update", port))
+        .header("Cookie", other_cookie_header.as_str())
+        .body(json!({
+            "contest_id": contest_id,
+            "problems": [{ "id": "problem_1" }],
+        }))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 403);
+
+    let response = surf::get(url(
+        &format!("/internal-api/contest/get/{}", contest_id),
+        port,
+    ))
+    .recv_json::<Value>()
+    .await
+    .unwrap();
+    assert_eq!(response["info"]["title"], "contest title");
+    assert_eq!(response["problems"], json!([]));
+
+    let response = surf::post(url("/internal-api/contest/update", port))
+        .header("Cookie", other_cookie_header.as_str())
+        .body(json!({
+            "id": "NON-EXISTING-CONTEST",
+            "title": "",
+            "memo": "",
+            "start_epoch_second": 1,
+            "duration_second": 2,
+            "penalty_second": 0,
+        }))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 404);
+
+    let response = surf::post(url("/internal-api/contest/update", port))
+        .header("Cookie", cookie_header.as_str())
+        .body(update_body)
+        .await
+        .unwrap();
+    assert!(response.status().is_success());
+
+    server.race(async_std::future::ready(())).await;
+}
+
+#[async_std::test]
+async fn test_virtual_contest_organizers() {
+    let port = setup().await;
+    let server = task::spawn(async move {
+        let pg_pool = sql_client::initialize_pool(utils::get_sql_url_from_env())
+            .await
+            .unwrap();
+        run_server(pg_pool, MockAuth, port).await.unwrap();
+    });
+    task::sleep(std::time::Duration::from_millis(1000)).await;
+    for code in [VALID_CODE, OTHER_CODE].iter() {
+        surf::get(url(&format!("/internal-api/authorize?code={}", code), port))
+            .await
+            .unwrap();
+    }
+    let cookie_header = format!("token={}", VALID_TOKEN);
+    let other_cookie_header = format!("token={}", OTHER_TOKEN);
+
+    let mut response = surf::post(url("/internal-api/contest/create", port))
+        .header("Cookie", cookie_header.as_str())
+        .body(json!({
+            "title": "contest title",
+            "memo": "contest memo",
+            "start_epoch_second": 1,
+            "duration_second": 2,
+            "penalty_second": 0,
+        }))
+        .await
+        .unwrap();
+    assert!(response.status().is_success());
+    let body = response.body_json::<Value>().await.unwrap();
+    let contest_id = body["contest_id"].as_str().unwrap();
+
+    let response = surf::post(url("/internal-api/contest/organizer/add", port))
+        .header("Cookie", other_cookie_header.as_str())
+        .body(json!({ "contest_id": contest_id, "user_id": "1" }))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 403);
+
+    let response = surf::post(url("/internal-api/contest/organizer/add", port))
+        .header("Cookie", cookie_header.as_str())
+        .body(json!({ "contest_id": contest_id, "user_id": "999" }))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 404);
+
+    let response = surf::post(url("/internal-api/contest/organizer/add", port))
+        .header("Cookie", cookie_header.as_str())
+        .body(json!({ "contest_id": contest_id, "user_id": "1" }))
+        .await
+        .unwrap();
+    assert!(response.status().is_success());
+
+    let response = surf::get(url(
+        &format!("/internal-api/contest/organizer/list/{}", contest_id),
+        port,
+    ))
+    .recv_json::<Value>()
+    .await
+    .unwrap();
+    assert_eq!(
+        response,
+        json!([{ "internal_user_id": "1", "atcoder_user_id": null }])
+    );
+
+    let response = surf::get(url("/internal-api/contest/my", port))
+        .header("Cookie", other_cookie_header.as_str())
+        .recv_json::<Value>()
+        .await
+        .unwrap();
+    assert_eq!(response[0]["id"], contest_id);
+
+    let response = surf::post(url("/internal-api/contest/update", port))
+        .header("Cookie", other_cookie_header.as_str())
+        .body(json!({
+            "id": contest_id,
+            "title": "updated by organizer",
+            "memo": "",
+            "start_epoch_second": 1,
+            "duration_second": 2,
+            "penalty_second": 0,
+        }))
+        .await
+        .unwrap();
+    assert!(response.status().is_success());
+    let response = surf::post(url("/internal-api/contest/item/update", port))
+        .header("Cookie", other_cookie_header.as_str())
+        .body(json!({
+            "contest_id": contest_id,
+            "problems": [{ "id": "problem_1" }],
+        }))
+        .await
+        .unwrap();
+    assert!(response.status().is_success());
+
+    let response = surf::post(url("/internal-api/contest/organizer/remove", port))
+        .header("Cookie", other_cookie_header.as_str())
+        .body(json!({ "contest_id": contest_id, "user_id": "0" }))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 403);
+
+    let response = surf::post(url("/internal-api/contest/organizer/remove", port))
+        .header("Cookie", other_cookie_header.as_str())
+        .body(json!({ "contest_id": contest_id, "user_id": "1" }))
+        .await
+        .unwrap();
+    assert!(response.status().is_success());
+
+    let response = surf::post(url("/internal-api/contest/item/update", port))
+        .header("Cookie", other_cookie_header.as_str())
+        .body(json!({
+            "contest_id": contest_id,
+            "problems": [],
+        }))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 403);
+
+    server.race(async_std::future::ready(())).await;
+}
+
+#[async_std::test]
+async fn test_private_virtual_contest() {
+    let port = setup().await;
+    let server = task::spawn(async move {
+        let pg_pool = sql_client::initialize_pool(utils::get_sql_url_from_env())
+            .await
+            .unwrap();
+        run_server(pg_pool, MockAuth, port).await.unwrap();
+    });
+    task::sleep(std::time::Duration::from_millis(1000)).await;
+    for code in [VALID_CODE, OTHER_CODE].iter() {
+        surf::get(url(&format!("/internal-api/authorize?code={}", code), port))
+            .await
+            .unwrap();
+    }
+    let cookie_header = format!("token={}", VALID_TOKEN);
+    let other_cookie_header = format!("token={}", OTHER_TOKEN);
+
+    let mut response = surf::post(url("/internal-api/contest/create", port))
+        .header("Cookie", cookie_header.as_str())
+        .body(json!({
+            "title": "private",
+            "memo": "",
+            "start_epoch_second": 1,
+            "duration_second": 2,
+            "is_public": false,
+            "penalty_second": 0,
+        }))
+        .await
+        .unwrap();
+    assert!(response.status().is_success());
+    let body = response.body_json::<Value>().await.unwrap();
+    let contest_id = body["contest_id"].as_str().unwrap();
+
+    let response = surf::post(url("/internal-api/contest/join", port))
+        .header("Cookie", other_cookie_header.as_str())
+        .body(json!({ "contest_id": contest_id }))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 403);
+
+    let response = surf::post(url("/internal-api/contest/invite/generate", port))
+        .header("Cookie", other_cookie_header.as_str())
+        .body(json!({ "contest_id": contest_id }))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 403);
+
+    let response = surf::post(url("/internal-api/contest/invite/generate", port))
+        .header("Cookie", cookie_header.as_str())
+        .body(json!({ "contest_id": contest_id }))
+        .recv_json::<Value>()
+        .await
+        .unwrap();
+    let invite_token = response["invite_token"].as_str().unwrap().to_owned();
+
+    let response = surf::get(url(
+        &format!("/internal-api/contest/invite/get/{}", contest_id),
+        port,
+    ))
+    .header("Cookie", cookie_header.as_str())
+    .recv_json::<Value>()
+    .await
+    .unwrap();
+    assert_eq!(response["invite_token"], invite_token.as_str());
+
+    let response = surf::post(url("/internal-api/contest/join", port))
+        .header("Cookie", other_cookie_header.as_str())
+        .body(json!({ "contest_id": contest_id, "invite_token": "wrong" }))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 403);
+
+    let response = surf::post(url("/internal-api/contest/join", port))
+        .header("Cookie", other_cookie_header.as_str())
+        .body(json!({ "contest_id": contest_id, "invite_token": invite_token }))
+        .await
+        .unwrap();
+    assert!(response.status().is_success());
+
+    let response = surf::post(url("/internal-api/contest/join", port))
+        .header("Cookie", cookie_header.as_str())
+        .body(json!({ "contest_id": contest_id }))
+        .await
+        .unwrap();
+    assert!(response.status().is_success());
+
+    let response = surf::post(url("/internal-api/contest/leave", port))
+        .header("Cookie", other_cookie_header.as_str())
+        .body(json!({ "contest_id": contest_id }))
+        .await
+        .unwrap();
+    assert!(response.status().is_success());
+
+    let response = surf::post(url("/internal-api/contest/invite/revoke", port))
+        .header("Cookie", cookie_header.as_str())
+        .body(json!({ "contest_id": contest_id }))
+        .await
+        .unwrap();
+    assert!(response.status().is_success());
+
+    let response = surf::post(url("/internal-api/contest/join", port))
+        .header("Cookie", other_cookie_header.as_str())
+        .body(json!({ "contest_id": contest_id, "invite_token": invite_token }))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 403);
+
+    server.race(async_std::future::ready(())).await;
+}
+
+#[async_std::test]
+async fn test_virtual_contest_participant_moderation() {
+    let port = setup().await;
+    let server = task::spawn(async move {
+        let pg_pool = sql_client::initialize_pool(utils::get_sql_url_from_env())
+            .await
+            .unwrap();
+        run_server(pg_pool, MockAuth, port).await.unwrap();
+    });
+    task::sleep(std::time::Duration::from_millis(1000)).await;
+    for code in [VALID_CODE, OTHER_CODE].iter() {
+        surf::get(url(&format!("/internal-api/authorize?code={}", code), port))
+            .await
+            .unwrap();
+    }
+    let cookie_header = format!("token={}", VALID_TOKEN);
+    let other_cookie_header = format!("token={}", OTHER_TOKEN);
+
+    let mut response = surf::post(url("/internal-api/contest/create", port))
+        .header("Cookie", cookie_header.as_str())
+        .body(json!({
+            "title": "contest title",
+            "memo": "",
+            "start_epoch_second": 1,
+            "duration_second": 2,
+            "penalty_second": 0,
+        }))
+        .await
+        .unwrap();
+    assert!(response.status().is_success());
+    let body = response.body_json::<Value>().await.unwrap();
+    let contest_id = body["contest_id"].as_str().unwrap();
+
+    let response = surf::post(url("/internal-api/contest/join", port))
+        .header("Cookie", other_cookie_header.as_str())
+        .body(json!({ "contest_id": contest_id }))
+        .await
+        .unwrap();
+    assert!(response.status().is_success());
+
+    let response = surf::get(url(
+        &format!("/internal-api/contest/participant/list/{}", contest_id),
+        port,
+    ))
+    .header("Cookie", other_cookie_header.as_str())
+    .await
+    .unwrap();
+    assert_eq!(response.status(), 403);
+
+    let response = surf::post(url("/internal-api/contest/participant/remove", port))
+        .header("Cookie", cookie_header.as_str())
+        .body(json!({ "contest_id": contest_id, "user_id": "1" }))
+        .await
+        .unwrap();
+    assert!(response.status().is_success());
+    let response = surf::get(url(
+        &format!("/internal-api/contest/participant/list/{}", contest_id),
+        port,
+    ))
+    .header("Cookie", cookie_header.as_str())
+    .recv_json::<Value>()
+    .await
+    .unwrap();
+    assert_eq!(response, json!({ "participants": [], "banned": [] }));
+
+    let response = surf::post(url("/internal-api/contest/participant/ban", port))
+        .header("Cookie", cookie_header.as_str())
+        .body(json!({ "contest_id": contest_id, "user_id": "1" }))
+        .await
+        .unwrap();
+    assert!(response.status().is_success());
+    let response = surf::post(url("/internal-api/contest/join", port))
+        .header("Cookie", other_cookie_header.as_str())
+        .body(json!({ "contest_id": contest_id }))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 403);
+    let response = surf::get(url(
+        &format!("/internal-api/contest/participant/list/{}", contest_id),
+        port,
+    ))
+    .header("Cookie", cookie_header.as_str())
+    .recv_json::<Value>()
+    .await
+    .unwrap();
+    assert_eq!(
+        response,
+        json!({
+            "participants": [],
+            "banned": [{ "internal_user_id": "1", "atcoder_user_id": null }],
+        })
+    );
+
+    let response = surf::post(url("/internal-api/contest/participant/unban", port))
+        .header("Cookie", cookie_header.as_str())
+        .body(json!({ "contest_id": contest_id, "user_id": "1" }))
+        .await
+        .unwrap();
+    assert!(response.status().is_success());
+
+    let response = surf::post(url("/internal-api/contest/registration/update", port))
+        .header("Cookie", cookie_header.as_str())
+        .body(json!({ "contest_id": contest_id, "max_participants": 0 }))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 400);
+    let response = surf::post(url("/internal-api/contest/registration/update", port))
+        .header("Cookie", cookie_header.as_str())
+        .body(json!({ "contest_id": contest_id, "max_participants": 1 }))
+        .await
+        .unwrap();
+    assert!(response.status().is_success());
+    let response = surf::get(url(
+        &format!("/internal-api/contest/registration/get/{}", contest_id),
+        port,
+    ))
+    .recv_json::<Value>()
+    .await
+    .unwrap();
+    assert_eq!(
+        response,
+        json!({ "max_participants": 1, "registration_deadline_second": null })
+    );
+
+    let response = surf::post(url("/internal-api/contest/join", port))
+        .header("Cookie", cookie_header.as_str())
+        .body(json!({ "contest_id": contest_id }))
+        .await
+        .unwrap();
+    assert!(response.status().is_success());
+    let response = surf::post(url("/internal-api/contest/join", port))
+        .header("Cookie", other_cookie_header.as_str())
+        .body(json!({ "contest_id": contest_id }))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 403);
+
+    let response = surf::post(url("/internal-api/contest/registration/update", port))
+        .header("Cookie", cookie_header.as_str())
+        .body(json!({ "contest_id": contest_id, "registration_deadline_second": 0 }))
+        .await
+        .unwrap();
+    assert!(response.status().is_success());
+    let response = surf::post(url("/internal-api/contest/join", port))
+        .header("Cookie", other_cookie_header.as_str())
+        .body(json!({ "contest_id": contest_id }))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 403);
+
+    server.race(async_std::future::ready(())).await;
+}
+
+#[async_std::test]
+async fn test_clone_virtual_contest() {
+    let port = setup().await;
+    let server = task::spawn(async move {
+        let pg_pool = sql_client::initialize_pool(utils::get_sql_url_from_env())
+            .await
+            .unwrap();
+        run_server(pg_pool, MockAuth, port).await.unwrap();
+    });
+    task::sleep(std::time::Duration::from_millis(1000)).await;
+    for code in [VALID_CODE, OTHER_CODE].iter() {
+        surf::get(url(&format!("/internal-api/authorize?code={}", code), port))
+            .await
+            .unwrap();
+    }
+    let cookie_header = format!("token={}", VALID_TOKEN);
+    let other_cookie_header = format!("token={}", OTHER_TOKEN);
+
+    let mut contest_ids = vec![];
+    for is_public in [true, false].iter() {
+        let response = surf::post(url("/internal-api/contest/create", port))
+            .header("Cookie", cookie_header.as_str())
+            .body(json!({
+                "title": "template",
+                "memo": "memo",
+                "start_epoch_second": 1,
+                "duration_second": 2,
+                "mode": null,
+                "is_public": is_public,
+                "penalty_second": 300,
+            }))
+            .recv_json::<Value>()
+            .await
+            .unwrap();
+        let contest_id = response["contest_id"].as_str().unwrap().to_owned();
+        let response = surf::post(url("/internal-api/contest/item/update", port))
+            .header("Cookie", cookie_header.as_str())
+            .body(json!({
+                "contest_id": contest_id,
+                "problems": [{ "id": "problem_1", "point": 100, "order": 0 }],
+            }))
+            .await
+            .unwrap();
+        assert!(response.status().is_success());
+        contest_ids.push(contest_id);
+    }
+
+    let response = surf::post(url("/internal-api/contest/clone", port))
+        .header("Cookie", other_cookie_header.as_str())
+        .body(json!({ "contest_id": "NON_EXISTING", "start_epoch_second": 100 }))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 404);
+
+    let response = surf::post(url("/internal-api/contest/clone", port))
+        .header("Cookie", other_cookie_header.as_str())
+        .body(json!({ "contest_id": contest_ids[1], "start_epoch_second": 100 }))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 403);
+
+    let response = surf::post(url("/internal-api/contest/clone", port))
+        .header("Cookie", other_cookie_header.as_str())
+        .body(json!({ "contest_id": contest_ids[0], "start_epoch_second": 100 }))
+        .recv_json::<Value>()
+        .await
+        .unwrap();
+    let cloned_id = response["contest_id"].as_str().unwrap();
+    let response = surf::get(url(
+        &format!("/internal-api/contest/get/{}", cloned_id),
+        port,
+    ))
+    .recv_json::<Value>()
+    .await
+    .unwrap();
+    assert_eq!(
+        response,
+        json!({
+            "info": {
+                "id": cloned_id,
+                "title": "template",
+                "memo": "memo",
+                "owner_user_id": "1",
+                "start_epoch_second": 100,
+                "duration_second": 2,
+                "mode": null,
+                "is_public": true,
+                "penalty_second": 300,
+                "freeze_second": 0,
+            },
+            "problems": [{ "id": "problem_1", "point": 100, "order": 0 }],
+            "participants": [],
+            "teams": [],
+        })
+    );
+
+    let response = surf::post(url("/internal-api/contest/clone", port))
+        .header("Cookie", cookie_header.as_str())
+        .body(json!({ "contest_id": contest_ids[1], "start_epoch_second": 100 }))
+        .await
+        .unwrap();
+    assert!(response.status().is_success());
+
+    server.race(async_std::future::ready(())).await;
+}
+
+#[async_std::test]
+async fn test_virtual_contest_series() {
+    let port = setup().await;
+    let server = task::spawn(async move {
+        let pg_pool = sql_client::initialize_pool(utils::get_sql_url_from_env())
+            .await
+            .unwrap();
+        run_server(pg_pool, MockAuth, port).await.unwrap();
+    });
+    task::sleep(std::time::Duration::from_millis(1000)).await;
+    for code in [VALID_CODE, OTHER_CODE].iter() {
+        surf::get(url(&format!("/internal-api/authorize?code={}", code), port))
+            .await
+            .unwrap();
+    }
+    let cookie_header = format!("token={}", VALID_TOKEN);
+    let other_cookie_header = format!("token={}", OTHER_TOKEN);
+
+    let config = json!({
+        "title": "weekly",
+        "memo": "",
+        "mode": null,
+        "is_public": true,
+        "penalty_second": 300,
+        "recurrence": "weekly",
+        "first_start_epoch_second": 1000,
+        "duration_second": 3600,
+        "problem_count": 0,
+        "min_point": null,
+        "max_point": null,
+    });
+    let response = surf::post(url("/internal-api/contest/series/create", port))
+        .header("Cookie", cookie_header.as_str())
+        .body(config.clone())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 400);
+
+    let mut config = config;
+    config["problem_count"] = json!(3);
+    let response = surf::post(url("/internal-api/contest/series/create", port))
+        .header("Cookie", cookie_header.as_str())
+        .body(config.clone())
+        .recv_json::<Value>()
+        .await
+        .unwrap();
+    let series_id = response["series_id"].as_str().unwrap().to_owned();
+
+    let response = surf::get(url("/internal-api/contest/series/my", port))
+        .header("Cookie", cookie_header.as_str())
+        .recv_json::<Value>()
+        .await
+        .unwrap();
+    let mut expected = config;
+    expected["id"] = json!(series_id);
+    expected["owner_user_id"] = json!("0");
+    assert_eq!(response, json!([expected]));
+
+    let response = surf::post(url("/internal-api/contest/series/delete", port))
+        .header("Cookie", other_cookie_header.as_str())
+        .body(json!({ "series_id": series_id }))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 404);
+
+    let response = surf::post(url("/internal-api/contest/series/delete", port))
+        .header("Cookie", cookie_header.as_str())
+        .body(json!({ "series_id": series_id }))
+        .await
+        .unwrap();
+    assert!(response.status().is_success());
+
+    let response = surf::get(url("/internal-api/contest/series/my", port))
+        .header("Cookie", cookie_header.as_str())
+        .recv_json::<Value>()
+        .await
+        .unwrap();
+    assert_eq!(response, json!([]));
+
+    server.race(async_std::future::ready(())).await;
+}
+
+#[async_std::test]
+async fn test_virtual_contest_freeze() {
+    let port = setup().await;
+    let server = task::spawn(async move {
+        let pg_pool = sql_client::initialize_pool(utils::get_sql_url_from_env())
+            .await
+            .unwrap();
+        run_server(pg_pool, MockAuth, port).await.unwrap();
+    });
+    task::sleep(std::time::Duration::from_millis(1000)).await;
+    for code in [VALID_CODE, OTHER_CODE].iter() {
+        surf::get(url(&format!("/internal-api/authorize?code={}", code), port))
+            .await
+            .unwrap();
+    }
+    let cookie_header = format!("token={}", VALID_TOKEN);
+    let other_cookie_header = format!("token={}", OTHER_TOKEN);
+
+    let response = surf::post(url("/internal-api/user/update", port))
+        .header("Cookie", cookie_header.as_str())
+        .body(json!({ "atcoder_user_id": "atcoder_user1" }))
+        .await
+        .unwrap();
+    assert!(response.status().is_success());
+
+    let now = chrono::Utc::now().timestamp();
+    let response = surf::post(url("/internal-api/contest/create", port))
+        .header("Cookie", cookie_header.as_str())
+        .body(json!({
+            "title": "frozen",
+            "memo": "",
+            "start_epoch_second": now - 1000,
+            "duration_second": 2000,
+            "penalty_second": 0,
+            "freeze_second": 3000,
+        }))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 400);
+
+    let response = surf::post(url("/internal-api/contest/create", port))
+        .header("Cookie", cookie_header.as_str())
+        .body(json!({
+            "title": "frozen",
+            "memo": "",
+            "start_epoch_second": now - 1000,
+            "duration_second": 2000,
+            "penalty_second": 0,
+            "freeze_second": 1500,
+        }))
+        .recv_json::<Value>()
+        .await
+        .unwrap();
+    let contest_id = response["contest_id"].as_str().unwrap().to_owned();
+    let response = surf::post(url("/internal-api/contest/item/update", port))
+        .header("Cookie", cookie_header.as_str())
+        .body(json!({
+            "contest_id": contest_id,
+            "problems": [{ "id": "problem_1" }, { "id": "problem_2" }],
+        }))
+        .await
+        .unwrap();
+    assert!(response.status().is_success());
+    let response = surf::post(url("/internal-api/contest/join", port))
+        .header("Cookie", cookie_header.as_str())
+        .body(json!({ "contest_id": contest_id }))
+        .await
+        .unwrap();
+    assert!(response.status().is_success());
+
+    let conn = sql_client::initialize_pool(utils::get_sql_url_from_env())
+        .await
+        .unwrap();
+    sql_client::query(
+        r"
+        INSERT INTO
+            submissions (epoch_second, problem_id, contest_id, user_id, result, id, language, point, length)
+            VALUES
+                ($1, 'problem_1', 'c1', 'atcoder_user1', 'AC', 1, 'Rust', 100.0, 0),
+                ($2, 'problem_2', 'c1', 'atcoder_user1', 'AC', 2, 'Rust', 200.0, 0)",
+    )
+    .bind(now - 800)
+    .bind(now - 200)
+    .execute(&conn)
+    .await
+    .unwrap();
+
+    let standings_url = url(
+        &format!("/internal-api/contest/standings/{}", contest_id),
+        port,
+    );
+    let response = surf::get(&standings_url)
+        .header("Cookie", cookie_header.as_str())
+        .recv_json::<Value>()
+        .await
+        .unwrap();
+    assert_eq!(response["frozen"], json!(false));
+    assert_eq!(response["standings"][0]["point"], json!(300.0));
+
+    for cookie in [Some(other_cookie_header.as_str()), None].iter() {
+        let mut request = surf::get(&standings_url);
+        if let Some(cookie) = cookie {
+            request = request.header("Cookie", *cookie);
+        }
+        let response = request.recv_json::<Value>().await.unwrap();
+        assert_eq!(response["frozen"], json!(true));
+        assert_eq!(response["standings"][0]["point"], json!(100.0));
+    }
+
+    // An edit without `freeze_second` keeps the current freeze.
+    let response = surf::post(url("/internal-api/contest/update", port))
+        .header("Cookie", cookie_header.as_str())
+        .body(json!({
+            "id": contest_id,
+            "title": "frozen updated",
+            "memo": "",
+            "start_epoch_second": now - 1000,
+            "duration_second": 2000,
+            "penalty_second": 0,
+        }))
+        .await
+        .unwrap();
+    assert!(response.status().is_success());
+    let response = surf::get(&standings_url)
+        .header("Cookie", other_cookie_header.as_str())
+        .recv_json::<Value>()
+        .await
+        .unwrap();
+    assert_eq!(response["frozen"], json!(true));
+    let response = surf::post(url("/internal-api/contest/update", port))
+        .header("Cookie", cookie_header.as_str())
+        .body(json!({
+            "id": contest_id,
+            "title": "frozen updated",
+            "memo": "",
+            "start_epoch_second": now - 1000,
+            "duration_second": 1000,
+            "penalty_second": 0,
+        }))
+        .await
+        .unwrap();
+    assert_eq!(
+        response.status(),
+        400,
+        "The kept freeze must be validated against the new duration."
+    );
+
+    // The freeze applies without telling the contest.
+    let submissions_url = url(
+        &format!(
+            "/atcoder-api/v3/users_and_time?users=atcoder_user1&problems=problem_1,problem_2&from={}&to={}",
+            now - 1000,
+            now + 1000,
+        ),
+        port,
+    );
+    let response = surf::get(&submissions_url)
+        .header("Cookie", cookie_header.as_str())
+        .recv_json::<Value>()
+        .await
+        .unwrap();
+    assert_eq!(response.as_array().unwrap().len(), 2);
+    let response = surf::get(&submissions_url)
+        .header("Cookie", other_cookie_header.as_str())
+        .recv_json::<Value>()
+        .await
+        .unwrap();
+    assert_eq!(response.as_array().unwrap().len(), 1);
+    assert_eq!(response[0]["id"], json!(1));
+
+    server.race(async_std::future::ready(())).await;
+}
+
+#[async_std::test]
+async fn test_delete_and_restore_contest() {
+    let port = setup().await;
+    let server = task::spawn(async move {
+        let pg_pool = sql_client::initialize_pool(utils::get_sql_url_from_env())
+            .await
+            .unwrap();
+        run_server(pg_pool, MockAuth, port).await.unwrap();
+    });
+    task::sleep(std::time::Duration::from_millis(1000)).await;
+    for code in [VALID_CODE, OTHER_CODE].iter() {
+        surf::get(url(&format!("/internal-api/authorize?code={}", code), port))
+            .await
+            .unwrap();
+    }
+    let cookie_header = format!("token={}", VALID_TOKEN);
+    let other_cookie_header = format!("token={}", OTHER_TOKEN);
+
+    let mut response = surf::post(url("/internal-api/contest/create", port))
+        .header("Cookie", cookie_header.as_str())
+        .body(json!({
+            "title": "contest title",
+            "memo": "",
+            "start_epoch_second": 1,
+            "duration_second": 2,
+            "penalty_second": 0,
+        }))
+        .await
+        .unwrap();
+    assert!(response.status().is_success());
+    let body = response.body_json::<Value>().await.unwrap();
+    let contest_id = body["contest_id"].as_str().unwrap();
+
+    let response = surf::post(url("/internal-api/contest/delete", port))
+        .header("Cookie", other_cookie_header.as_str())
+        .body(json!({ "contest_id": contest_id }))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 403);
+
+    let response = surf::post(url("/internal-api/contest/delete", port))
+        .header("Cookie", cookie_header.as_str())
+        .body(json!({ "contest_id": contest_id }))
+        .await
+        .unwrap();
+    assert!(response.status().is_success());
+
+    let response = surf::get(url(
+        &format!("/internal-api/contest/get/{}", contest_id),
+        port,
+    ))
+    .await
+    .unwrap();
+    assert!(!response.status().is_success());
+    let response = surf::get(url("/internal-api/contest/recent", port))
+        .recv_json::<Value>()
+        .await
+        .unwrap();
+    assert_eq!(response, json!([]));
+    let response = surf::get(url("/internal-api/contest/deleted", port))
+        .header("Cookie", cookie_header.as_str())
+        .recv_json::<Value>()
+        .await
+        .unwrap();
+    assert_eq!(response[0]["id"].as_str().unwrap(), contest_id);
+
+    let response = surf::post(url("/internal-api/contest/restore", port))
+        .header("Cookie", other_cookie_header.as_str())
+        .body(json!({ "contest_id": contest_id }))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 404);
+
+    let response = surf::post(url("/internal-api/contest/restore", port))
+        .header("Cookie", cookie_header.as_str())
+        .body(json!({ "contest_id": contest_id }))
+        .await
+        .unwrap();
+    assert!(response.status().is_success());
+    let response = surf::get(url("/internal-api/contest/recent", port))
+        .recv_json::<Value>()
+        .await
+        .unwrap();
+    assert_eq!(response[0]["id"].as_str().unwrap(), contest_id);
+
+    server.race(async_std::future::ready(())).await;
+}
+
+#[async_std::test]
+async fn test_team_participation() {
+    let port = setup().await;
+    let server = task::spawn(async move {
+        let pg_pool = sql_client::initialize_pool(utils::get_sql_url_from_env())
+            .await
+            .unwrap();
+        run_server(pg_pool, MockAuth, port).await.unwrap();
+    });
+    task::sleep(std::time::Duration::from_millis(1000)).await;
+    for code in [VALID_CODE, OTHER_CODE].iter() {
+        surf::get(url(&format!("/internal-api/authorize?code={}", code), port))
+            .await
+            .unwrap();
+    }
+    let cookie_header = format!("token={}", VALID_TOKEN);
+    let other_cookie_header = format!("token={}", OTHER_TOKEN);
+
+    let mut response = surf::post(url("/internal-api/contest/create", port))
+        .header("Cookie", cookie_header.as_str())
+        .body(json!({
+            "title": "team contest",
+            "memo": "",
+            "start_epoch_second": 0,
+            "duration_second": 1000,
+            "penalty_second": 0,
+        }))
+        .await
+        .unwrap();
+    let body = response.body_json::<Value>().await.unwrap();
+    let contest_id = body["contest_id"].as_str().unwrap();
+    let response = surf::post(url("/internal-api/contest/item/update", port))
+        .header("Cookie", cookie_header.as_str())
+        .body(json!({
+            "contest_id": contest_id,
+            "problems": [{ "id": "problem_1", "point": 100 }, { "id": "problem_2", "point": 200 }],
+        }))
+        .await
+        .unwrap();
+    assert!(response.status().is_success());
+
+    let response = surf::post(url("/internal-api/contest/team/create", port))
+        .header("Cookie", other_cookie_header.as_str())
+        .body(json!({
+            "contest_id": contest_id,
+            "name": "team",
+            "members": [],
+        }))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 400);
+
+    let mut response = surf::post(url("/internal-api/contest/team/create", port))
+        .header("Cookie", other_cookie_header.as_str())
+        .body(json!({
+            "contest_id": contest_id,
+            "name": "team",
+            "members": ["member2", "Member1", "member1"],
+        }))
+        .await
+        .unwrap();
+    assert!(response.status().is_success());
+    let body = response.body_json::<Value>().await.unwrap();
+    let team_id = body["team_id"].as_str().unwrap().to_owned();
+
+    let response = surf::post(url("/internal-api/contest/team/create", port))
+        .header("Cookie", cookie_header.as_str())
+        .body(json!({
+            "contest_id": contest_id,
+            "name": "another team",
+            "members": ["member1"],
+        }))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 409);
+
+    let conn = sql_client::initialize_pool(utils::get_sql_url_from_env())
+        .await
+        .unwrap();
+    sql_client::query(
+        r"INSERT INTO internal_users (internal_user_id, atcoder_user_id) VALUES ('2', 'Member3')",
+    )
+    .execute(&conn)
+    .await
+    .unwrap();
+    let response = surf::post(url("/internal-api/contest/participant/ban", port))
+        .header("Cookie", cookie_header.as_str())
+        .body(json!({ "contest_id": contest_id, "user_id": "2" }))
+        .await
+        .unwrap();
+    assert!(response.status().is_success());
+    let response = surf::post(url("/internal-api/contest/team/create", port))
+        .header("Cookie", cookie_header.as_str())
+        .body(json!({
+            "contest_id": contest_id,
+            "name": "banned team",
+            "members": ["member3"],
+        }))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 403);
+
+    let response = surf::get(url(
+        &format!("/internal-api/contest/team/list/{}", contest_id),
+        port,
+    ))
+    .recv_json::<Value>()
+    .await
+    .unwrap();
+    assert_eq!(
+        response,
+        json!([{
+            "id": team_id,
+            "name": "team",
+            "internal_user_id": "1",
+            "members": ["member1", "member2"],
+        }])
+    );
+
+    sql_client::query(
+        r"
+        INSERT INTO
+            submissions (epoch_second, problem_id, contest_id, user_id, result, id, language, point, length)
+            VALUES
+                (10, 'problem_1', 'c1', 'member1', 'AC', 1, 'Rust', 100.0, 0),
+                (20, 'problem_2', 'c1', 'Member2', 'AC', 2, 'Rust', 200.0, 0),
+                (30, 'problem_1', 'c1', 'member2', 'AC', 3, 'Rust', 100.0, 0)",
+    )
+    .execute(&conn)
+    .await
+    .unwrap();
+
+    let response = surf::get(url(
+        &format!("/internal-api/contest/standings/{}", contest_id),
+        port,
+    ))
+    .recv_json::<Value>()
+    .await
+    .unwrap();
+    let standings = response["standings"].as_array().unwrap();
+    assert_eq!(standings.len(), 1);
+    assert_eq!(standings[0]["user_id"], json!("team"));
+    assert_eq!(standings[0]["team_members"], json!(["member1", "member2"]));
+    assert_eq!(standings[0]["point"], json!(300.0));
+    assert_eq!(standings[0]["problems"][0]["trials"], json!(2));
+
+    let response = surf::post(url("/internal-api/user/update", port))
+        .header("Cookie", cookie_header.as_str())
+        .body(json!({ "atcoder_user_id": "Member1" }))
+        .await
+        .unwrap();
+    assert!(response.status().is_success());
+    let response = surf::post(url("/internal-api/contest/join", port))
+        .header("Cookie", cookie_header.as_str())
+        .body(json!({ "contest_id": contest_id }))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 403);
+
+    let response = surf::post(url("/internal-api/contest/team/delete", port))
+        .header("Cookie", other_cookie_header.as_str())
+        .body(json!({ "contest_id": contest_id, "team_id": team_id }))
+        .await
+        .unwrap();
+    assert!(response.status().is_success());
+    let response = surf::get(url(
+        &format!("/internal-api/contest/team/list/{}", contest_id),
+        port,
+    ))
+    .recv_json::<Value>()
+    .await
+    .unwrap();
+    assert_eq!(response, json!([]));
+
+    let response = surf::post(url("/internal-api/contest/join", port))
+        .header("Cookie", cookie_header.as_str())
+        .body(json!({ "contest_id": contest_id }))
+        .await
+        .unwrap();
+    assert!(response.status().is_success());
+    let response = surf::post(url("/internal-api/contest/team/create", port))
+        .header("Cookie", other_cookie_header.as_str())
+        .body(json!({
+            "contest_id": contest_id,
+            "name": "team",
+            "members": ["MEMBER1"],
+        }))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 403);
+
+    server.race(async_std::future::ready(())).await;
+}
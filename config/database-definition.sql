@@ -3,6 +3,8 @@
 -- SET client_encoding = 'UTF8';
 
 DROP TABLE IF EXISTS submissions;
+DROP SEQUENCE IF EXISTS submission_update_id;
+CREATE SEQUENCE submission_update_id;
 CREATE TABLE submissions (
   id            BIGINT NOT NULL,
   epoch_second  BIGINT NOT NULL,
@@ -14,11 +16,17 @@ CREATE TABLE submissions (
   length        INT NOT NULL,
   result        VARCHAR(255) NOT NULL,
   execution_time  INT,
+  -- Taken from submission_update_id when the submission is inserted or rejudged.
+  update_id     BIGINT NOT NULL DEFAULT nextval('submission_update_id'),
   PRIMARY KEY (id)
 );
 CREATE INDEX ON submissions (user_id);
 CREATE INDEX ON submissions (LOWER(user_id));
+CREATE INDEX ON submissions (LOWER(user_id), epoch_second, id);
+CREATE INDEX ON submissions (LOWER(user_id), update_id);
 CREATE INDEX ON submissions (epoch_second);
+CREATE INDEX ON submissions (problem_id);
+CREATE INDEX ON submissions (contest_id);
 
 DROP TABLE IF EXISTS problems;
 CREATE TABLE problems (
@@ -75,6 +83,7 @@ CREATE TABLE accepted_count (
   problem_count INT           NOT NULL,
   PRIMARY KEY (user_id)
 );
+CREATE INDEX ON accepted_count (problem_count DESC, user_id ASC);
 
 DROP TABLE IF EXISTS points;
 CREATE TABLE points (
@@ -90,6 +99,7 @@ CREATE TABLE rated_point_sum (
   point_sum       DOUBLE PRECISION NOT NULL,
   PRIMARY KEY (user_id)
 );
+CREATE INDEX ON rated_point_sum (point_sum DESC, user_id ASC);
 
 DROP TABLE IF EXISTS language_count;
 CREATE TABLE language_count (
@@ -98,6 +108,7 @@ CREATE TABLE language_count (
   problem_count         INT NOT NULL,
   PRIMARY KEY (user_id, simplified_language)
 );
+CREATE INDEX ON language_count (simplified_language, problem_count DESC, user_id ASC);
 
 DROP TABLE IF EXISTS predicted_rating;
 CREATE TABLE predicted_rating (
@@ -117,8 +128,12 @@ DROP TABLE IF EXISTS max_streaks;
 CREATE TABLE max_streaks (
   user_id               VARCHAR(255) NOT NULL,
   streak                BIGINT NOT NULL,
+  current_streak        BIGINT NOT NULL DEFAULT 0,
+  last_ac_date          DATE,
   PRIMARY KEY (user_id)
 );
+CREATE INDEX ON max_streaks (streak DESC, user_id ASC);
+CREATE INDEX ON max_streaks (current_streak DESC, user_id ASC);
 
 DROP TABLE IF EXISTS submission_count;
 CREATE TABLE submission_count (
@@ -126,14 +141,22 @@ CREATE TABLE submission_count (
   count                 BIGINT NOT NULL,
   PRIMARY KEY (user_id)
 );
+CREATE INDEX ON submission_count (LOWER(user_id));
 
 -- For internal services:
+DROP TABLE IF EXISTS internal_problem_list_editors;
+DROP TABLE IF EXISTS internal_problem_list_item_tags;
 DROP TABLE IF EXISTS internal_problem_list_items;
 DROP TABLE IF EXISTS internal_problem_lists;
 
+DROP TABLE IF EXISTS internal_virtual_contest_team_members;
+DROP TABLE IF EXISTS internal_virtual_contest_teams;
+DROP TABLE IF EXISTS internal_virtual_contest_bans;
+DROP TABLE IF EXISTS internal_virtual_contest_organizers;
 DROP TABLE IF EXISTS internal_virtual_contest_participants;
 DROP TABLE IF EXISTS internal_virtual_contest_items;
 DROP TABLE IF EXISTS internal_virtual_contests;
+DROP TABLE IF EXISTS internal_virtual_contest_series;
 
 DROP TABLE IF EXISTS internal_progress_reset;
 
@@ -142,25 +165,65 @@ DROP TABLE IF EXISTS internal_users;
 CREATE TABLE internal_users (
   internal_user_id      VARCHAR(255) NOT NULL,
   atcoder_user_id       VARCHAR(255) DEFAULT NULL,
+  timezone_offset       INTEGER NOT NULL DEFAULT 540,
+  calendar_token        VARCHAR(255) DEFAULT NULL,
   PRIMARY KEY (internal_user_id)
 );
+CREATE UNIQUE INDEX ON internal_users (calendar_token);
 
 CREATE TABLE internal_problem_lists (
   internal_list_id      VARCHAR(255) NOT NULL,
   internal_user_id      VARCHAR(255) REFERENCES internal_users ON DELETE CASCADE ON UPDATE CASCADE,
   internal_list_name    VARCHAR(255) DEFAULT '',
+  deleted_at            BIGINT DEFAULT NULL,
+  source_list_id        VARCHAR(255) DEFAULT NULL REFERENCES internal_problem_lists ON DELETE SET NULL ON UPDATE CASCADE,
   PRIMARY KEY (internal_list_id)
 );
 CREATE INDEX ON internal_problem_lists (internal_user_id);
+CREATE INDEX ON internal_problem_lists (source_list_id);
 
 CREATE TABLE internal_problem_list_items (
   internal_list_id      VARCHAR(255) REFERENCES internal_problem_lists ON DELETE CASCADE ON UPDATE CASCADE,
   problem_id            VARCHAR(255) NOT NULL,
   memo                  VARCHAR(255) DEFAULT '',
+  position              BIGINT NOT NULL DEFAULT 0,
   PRIMARY KEY (internal_list_id, problem_id)
 );
 CREATE INDEX ON internal_problem_list_items (internal_list_id);
 
+CREATE TABLE internal_problem_list_item_tags (
+  internal_list_id      VARCHAR(255) NOT NULL,
+  problem_id            VARCHAR(255) NOT NULL,
+  tag                   VARCHAR(255) NOT NULL,
+  PRIMARY KEY (internal_list_id, problem_id, tag),
+  FOREIGN KEY (internal_list_id, problem_id) REFERENCES internal_problem_list_items ON DELETE CASCADE ON UPDATE CASCADE
+);
+
+CREATE TABLE internal_problem_list_editors (
+  internal_list_id      VARCHAR(255) REFERENCES internal_problem_lists ON DELETE CASCADE ON UPDATE CASCADE,
+  internal_user_id      VARCHAR(255) REFERENCES internal_users ON DELETE CASCADE ON UPDATE CASCADE,
+  PRIMARY KEY (internal_list_id, internal_user_id)
+);
+CREATE INDEX ON internal_problem_list_editors (internal_user_id);
+
+CREATE TABLE internal_virtual_contest_series (
+  id        VARCHAR(255) NOT NULL,
+  title     VARCHAR(255) DEFAULT '',
+  memo      VARCHAR(255) DEFAULT '',
+  internal_user_id     VARCHAR(255) REFERENCES internal_users ON DELETE CASCADE ON UPDATE CASCADE,
+  mode      VARCHAR(255) DEFAULT NULL,
+  is_public BOOLEAN NOT NULL DEFAULT TRUE,
+  penalty_second   BIGINT NOT NULL DEFAULT 0,
+  recurrence       VARCHAR(255) NOT NULL,
+  first_start_epoch_second BIGINT NOT NULL,
+  duration_second  BIGINT NOT NULL,
+  problem_count    BIGINT NOT NULL,
+  min_point        DOUBLE PRECISION DEFAULT NULL,
+  max_point        DOUBLE PRECISION DEFAULT NULL,
+  PRIMARY KEY (id)
+);
+CREATE INDEX ON internal_virtual_contest_series (internal_user_id);
+
 CREATE TABLE internal_virtual_contests (
   id        VARCHAR(255) NOT NULL,
   title     VARCHAR(255) DEFAULT '',
@@ -171,10 +234,18 @@ CREATE TABLE internal_virtual_contests (
   mode      VARCHAR(255) DEFAULT NULL,
   is_public BOOLEAN NOT NULL DEFAULT TRUE,
   penalty_second   BIGINT NOT NULL DEFAULT 0,
+  freeze_second    BIGINT NOT NULL DEFAULT 0,
+  invite_token     VARCHAR(255) DEFAULT NULL,
+  max_participants BIGINT DEFAULT NULL,
+  registration_deadline_second BIGINT DEFAULT NULL,
+  series_id        VARCHAR(255) REFERENCES internal_virtual_contest_series(id) ON DELETE SET NULL ON UPDATE CASCADE,
+  deleted_at       BIGINT DEFAULT NULL,
   PRIMARY KEY (id)
 );
 CREATE INDEX ON internal_virtual_contests (internal_user_id);
 CREATE INDEX ON internal_virtual_contests (start_epoch_second);
+CREATE INDEX ON internal_virtual_contests ((start_epoch_second + duration_second)) WHERE freeze_second > 0;
+CREATE UNIQUE INDEX ON internal_virtual_contests (series_id, start_epoch_second);
 
 CREATE TABLE internal_virtual_contest_items (
   problem_id    VARCHAR(255) NOT NULL,
@@ -192,6 +263,36 @@ CREATE TABLE internal_virtual_contest_participants (
 );
 CREATE INDEX ON internal_virtual_contest_participants (internal_user_id);
 
+CREATE TABLE internal_virtual_contest_organizers (
+  internal_virtual_contest_id VARCHAR(255) REFERENCES internal_virtual_contests(id) ON DELETE CASCADE ON UPDATE CASCADE,
+  internal_user_id      VARCHAR(255) REFERENCES internal_users ON DELETE CASCADE ON UPDATE CASCADE,
+  PRIMARY KEY (internal_virtual_contest_id, internal_user_id)
+);
+CREATE INDEX ON internal_virtual_contest_organizers (internal_user_id);
+
+CREATE TABLE internal_virtual_contest_bans (
+  internal_virtual_contest_id VARCHAR(255) REFERENCES internal_virtual_contests(id) ON DELETE CASCADE ON UPDATE CASCADE,
+  internal_user_id      VARCHAR(255) REFERENCES internal_users ON DELETE CASCADE ON UPDATE CASCADE,
+  PRIMARY KEY (internal_virtual_contest_id, internal_user_id)
+);
+
+CREATE TABLE internal_virtual_contest_teams (
+  id        VARCHAR(255) NOT NULL,
+  internal_virtual_contest_id VARCHAR(255) REFERENCES internal_virtual_contests(id) ON DELETE CASCADE ON UPDATE CASCADE,
+  name      VARCHAR(255) NOT NULL,
+  internal_user_id      VARCHAR(255) REFERENCES internal_users ON DELETE CASCADE ON UPDATE CASCADE,
+  PRIMARY KEY (id)
+);
+CREATE UNIQUE INDEX ON internal_virtual_contest_teams (internal_virtual_contest_id, name);
+
+CREATE TABLE internal_virtual_contest_team_members (
+  team_id   VARCHAR(255) REFERENCES internal_virtual_contest_teams(id) ON DELETE CASCADE ON UPDATE CASCADE,
+  internal_virtual_contest_id VARCHAR(255) REFERENCES internal_virtual_contests(id) ON DELETE CASCADE ON UPDATE CASCADE,
+  atcoder_user_id VARCHAR(255) NOT NULL,
+  PRIMARY KEY (team_id, atcoder_user_id)
+);
+CREATE UNIQUE INDEX ON internal_virtual_contest_team_members (internal_virtual_contest_id, LOWER(atcoder_user_id));
+
 CREATE TABLE internal_progress_reset (
   internal_user_id    VARCHAR(255) REFERENCES internal_users ON DELETE CASCADE ON UPDATE CASCADE,
   problem_id          VARCHAR(255) NOT NULL,